@@ -16,6 +16,7 @@ import (
 	"storage/internal/mq"
 	"storage/internal/repository"
 	"storage/internal/service"
+	"storage/pkg/signature"
 )
 
 func main() {
@@ -48,11 +49,16 @@ func main() {
 	fileSvc := service.NewFileService(repo, metaRepo, sqsPublisher, generationTracker, cfg.MaxUploadMB)
 	shareSvc := service.NewShareService(metaRepo)
 
+	// Share Sweeper (runs in background)
+	shareSweeper := service.NewShareSweeper(metaRepo, cfg.ShareSweepInterval)
+	go shareSweeper.Start(context.Background())
+
 	fileHandler := handler.NewFileHandler(fileSvc, shareSvc)
 	shareHandler := handler.NewShareHandler(shareSvc, fileSvc)
 
 	// Routes
-	app.SetupRoutes(appHttp, fileHandler, shareHandler)
+	verifier := signature.NewVerifier(cfg.Signature.Secret, cfg.Signature.MaxSkew)
+	app.SetupRoutes(appHttp, fileHandler, shareHandler, app.VerifySignature(verifier))
 
 	appHttp.Listen(":" + cfg.Port)
 	log.Fatal(appHttp.Listen(":" + cfg.Port))
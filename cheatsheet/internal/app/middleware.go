@@ -1,6 +1,11 @@
 package app
 
 import (
+	"time"
+
+	"storage/pkg/httpx"
+	"storage/pkg/signature"
+
 	"github.com/gofiber/fiber/v2"
 	"github.com/gofiber/fiber/v2/middleware/logger"
 )
@@ -10,3 +15,23 @@ func Middlewares() []fiber.Handler {
 		logger.New(),
 	}
 }
+
+// VerifySignature rejects requests not signed by the gateway or the user service, so the
+// X-User-Id a handler reads can be trusted
+func VerifySignature(v *signature.Verifier) fiber.Handler {
+	return func(c *fiber.Ctx) error {
+		err := v.Verify(
+			time.Now(),
+			c.Get(signature.TimestampHeader),
+			c.Get(signature.SignatureHeader),
+			c.Method(),
+			c.OriginalURL(),
+			c.Get("X-User-Id"),
+			c.Body(),
+		)
+		if err != nil {
+			return httpx.Forbidden(c, err.Error())
+		}
+		return c.Next()
+	}
+}
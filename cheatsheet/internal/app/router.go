@@ -6,13 +6,24 @@ import (
 	"github.com/gofiber/fiber/v2"
 )
 
-func SetupRoutes(app *fiber.App, fh *handler.FileHandler, sh *handler.ShareHandler) {
-	app.Get("/files", fh.GetAllFiles)                          // GET /files?key=...
-	app.Get("/files/presign", fh.GetPresignedURL)              // GET /files/presign?key=...
+func SetupRoutes(app *fiber.App, fh *handler.FileHandler, sh *handler.ShareHandler, verify fiber.Handler) {
+	// liveness probe, also used by the gateway's readiness check
+	app.Get("/healthz", func(c *fiber.Ctx) error {
+		return c.SendStatus(fiber.StatusOK)
+	})
+
+	// everything below acts as the X-User-Id caller, so it must be a signed internal request
+	app.Use(verify)
+
+	app.Get("/files", fh.GetAllFiles)                          // GET /files?type=...&sort=...&limit=...&cursor=...
+	app.Get("/files/presign", fh.GetPresignedURL)              // GET /files/presign?key=...&ttl=...
+	app.Get("/files/by-key", fh.GetFileByKey)                  // GET /files/by-key?key=...
 	app.Get("/files/:fileId", fh.GetFile)                      // GET /files/:fileId
+	app.Get("/files/:fileId/role", fh.GetFileRole)             // GET /files/:fileId/role -> the caller's role
 	app.Delete("/files", fh.Remove)                            // DELETE /files?key=...
 	app.Get("/files/presign/upload", fh.GetPresignedUploadURL) // GET /files/presign/upload?filename=...
 	app.Post("/generate", fh.Generate)
+	app.Get("/generate/:requestId", fh.GetGenerationStage) // GET /generate/:requestId -> current stage
 
 	// share/unshare
 	app.Post("/share", sh.Share)
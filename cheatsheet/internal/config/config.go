@@ -6,6 +6,7 @@ import (
 	"log"
 	"os"
 	"strconv"
+	"time"
 
 	"github.com/aws/aws-sdk-go-v2/aws"
 	"github.com/aws/aws-sdk-go-v2/config"
@@ -19,6 +20,9 @@ type AppConfig struct {
 	AwsCfg      aws.Config
 	DynamoDB    DynamoDBConfig
 	SQS         SQSConfig
+	// ShareSweepInterval is how often expired shares are deleted
+	ShareSweepInterval time.Duration
+	Signature          SignatureConfig
 }
 
 type DynamoDBConfig struct {
@@ -26,6 +30,12 @@ type DynamoDBConfig struct {
 	SharesTable string
 }
 
+// SignatureConfig is how requests from the gateway and the user service are verified
+type SignatureConfig struct {
+	Secret  string
+	MaxSkew time.Duration
+}
+
 type SQSConfig struct {
 	RequestQueueURL  string
 	ResponseQueueURL string
@@ -37,6 +47,10 @@ func Load() AppConfig {
 	region := getEnv("AWS_REGION", "ap-southeast-1")
 	bucket := mustEnv("S3_BUCKET")
 	maxMB := int64(getInt("MAX_UPLOAD_MB", 20))
+	sweepSecs := getInt("SHARE_SWEEP_INTERVAL_SECONDS", 300)
+	if sweepSecs <= 0 {
+		log.Fatalf("SHARE_SWEEP_INTERVAL_SECONDS must be positive, got %d", sweepSecs)
+	}
 
 	awsCfg, err := config.LoadDefaultConfig(
 		ctx(),
@@ -60,6 +74,11 @@ func Load() AppConfig {
 			RequestQueueURL:  getEnv("SQS_REQUEST_QUEUE_URL", fmt.Sprintf("openexam-%s-queue", env)),
 			ResponseQueueURL: getEnv("SQS_RESPONSE_QUEUE_URL", fmt.Sprintf("openexam-%s-queue-responses", env)),
 		},
+		ShareSweepInterval: time.Duration(sweepSecs) * time.Second,
+		Signature: SignatureConfig{
+			Secret:  mustEnv("INTERNAL_SIGNING_SECRET"),
+			MaxSkew: time.Duration(getInt("SIGNATURE_MAX_SKEW_SECONDS", 60)) * time.Second,
+		},
 	}
 }
 
@@ -8,4 +8,6 @@ var (
 	ErrInvalidType   = errors.New("invalid content type")
 	ErrStorageFailed = errors.New("storage operation failed")
 	ErrTimeout       = errors.New("operation timeout")
+	ErrNotExpired    = errors.New("share has not expired")
+	ErrInvalidCursor = errors.New("invalid cursor")
 )
@@ -1,6 +1,14 @@
 package domain
 
-import "time"
+import (
+	"cmp"
+	"encoding/base64"
+	"encoding/json"
+	"slices"
+	"sort"
+	"strings"
+	"time"
+)
 
 type FileObject struct {
 	Key         string
@@ -15,3 +23,146 @@ type File struct {
 	Name      string    `dynamodbav:"name"`
 	Key       string    `dynamodbav:"key"`
 }
+
+// FileQuery narrows, sorts and pages the files a user can see. Zero values mean no filter.
+type FileQuery struct {
+	// KeyPrefix is the folder files are stored under, e.g. "slides/"
+	KeyPrefix string
+	// Ownership is "mine" for files the user created, "shared" for files shared with them
+	Ownership     string
+	Name          string
+	CreatedAfter  *time.Time
+	CreatedBefore *time.Time
+	// Sort is "name" or "created_at"; empty keeps storage order, by share key
+	Sort string
+	// Order is "asc" or "desc" and only applies together with Sort
+	Order string
+	// Limit is the most files to return; 0 returns them all
+	Limit int32
+	// Cursor is the NextCursor of the previous page
+	Cursor string
+}
+
+// Matches applies the filters that cannot be evaluated by the shares query itself
+func (q FileQuery) Matches(file File, userId string) bool {
+	switch q.Ownership {
+	case "mine":
+		if file.UserID != userId {
+			return false
+		}
+	case "shared":
+		if file.UserID == userId {
+			return false
+		}
+	}
+	if q.Name != "" && !strings.Contains(strings.ToLower(file.Name), strings.ToLower(q.Name)) {
+		return false
+	}
+	if q.CreatedAfter != nil && file.CreatedAt.Before(*q.CreatedAfter) {
+		return false
+	}
+	if q.CreatedBefore != nil && !file.CreatedAt.Before(*q.CreatedBefore) {
+		return false
+	}
+	return true
+}
+
+// ListedFile is a file the user can see, with the share that lets them see it
+type ListedFile struct {
+	File  File
+	Share Share
+}
+
+// FilePage is one page of a file listing, with the user's shares of those files
+type FilePage struct {
+	Files  []File
+	Shares []Share
+	// Total is the number of files matching the filters, across all pages
+	Total int
+	// NextCursor continues the listing; empty on the last page
+	NextCursor string
+}
+
+// filePosition is the last file of a page, as encoded in its cursor. The sort and order
+// are kept so a cursor cannot be reused with a different one.
+type filePosition struct {
+	Sort  string `json:"sort"`
+	Order string `json:"order"`
+	Value string `json:"value"`
+	ID    string `json:"id"`
+}
+
+// createdAtLayout has a fixed width, so timestamps in it sort as strings
+const createdAtLayout = "2006-01-02T15:04:05.000000000Z"
+
+// sortValue is what a file is ordered by; files with equal values are ordered by id
+func (q FileQuery) sortValue(file ListedFile) string {
+	switch q.Sort {
+	case "name":
+		return strings.ToLower(file.File.Name)
+	case "created_at":
+		return file.File.CreatedAt.UTC().Format(createdAtLayout)
+	default:
+		return file.Share.Key
+	}
+}
+
+// Paginate sorts the matching files and returns the page after q.Cursor. Cursors point
+// at the last file of the previous page rather than an offset, so they stay correct when
+// files are added or removed between pages.
+func (q FileQuery) Paginate(files []ListedFile) (FilePage, error) {
+	type sortable struct {
+		value string
+		file  ListedFile
+	}
+	compare := func(a sortable, value, id string) int {
+		c := cmp.Or(strings.Compare(a.value, value), strings.Compare(a.file.File.ID, id))
+		if q.Sort != "" && q.Order == "desc" {
+			return -c
+		}
+		return c
+	}
+
+	sorted := make([]sortable, 0, len(files))
+	for _, file := range files {
+		sorted = append(sorted, sortable{q.sortValue(file), file})
+	}
+	slices.SortFunc(sorted, func(a, b sortable) int {
+		return compare(a, b.value, b.file.File.ID)
+	})
+
+	start := 0
+	if q.Cursor != "" {
+		var after filePosition
+		raw, err := base64.RawURLEncoding.DecodeString(q.Cursor)
+		if err != nil || json.Unmarshal(raw, &after) != nil || after.Sort != q.Sort || after.Order != q.Order {
+			return FilePage{}, ErrInvalidCursor
+		}
+		start = sort.Search(len(sorted), func(i int) bool {
+			return compare(sorted[i], after.Value, after.ID) > 0
+		})
+	}
+	end := len(sorted)
+	if q.Limit > 0 && start+int(q.Limit) < end {
+		end = start + int(q.Limit)
+	}
+
+	page := FilePage{
+		Files:  make([]File, 0, end-start),
+		Shares: make([]Share, 0, end-start),
+		Total:  len(sorted),
+	}
+	for _, s := range sorted[start:end] {
+		page.Files = append(page.Files, s.file.File)
+		page.Shares = append(page.Shares, s.file.Share)
+	}
+	if end < len(sorted) {
+		last := sorted[end-1]
+		raw, err := json.Marshal(filePosition{Sort: q.Sort, Order: q.Order, Value: last.value, ID: last.file.File.ID})
+		if err != nil {
+			return FilePage{}, err
+		}
+		page.NextCursor = base64.RawURLEncoding.EncodeToString(raw)
+	}
+	return page, nil
+}
@@ -0,0 +1,117 @@
+package domain
+
+import (
+	"slices"
+	"testing"
+	"time"
+)
+
+func TestFileQueryMatches(t *testing.T) {
+	created := time.Date(2026, 3, 1, 12, 0, 0, 0, time.UTC)
+	before := created.Add(-time.Hour)
+	after := created.Add(time.Hour)
+	file := File{ID: "f1", UserID: "1", CreatedAt: created, Name: "Linear Algebra Notes", Key: "slides/1/ab_notes.pdf"}
+
+	cases := []struct {
+		name   string
+		query  FileQuery
+		userId string
+		want   bool
+	}{
+		{"no filters", FileQuery{}, "2", true},
+		{"mine as owner", FileQuery{Ownership: "mine"}, "1", true},
+		{"mine as recipient", FileQuery{Ownership: "mine"}, "2", false},
+		{"shared as recipient", FileQuery{Ownership: "shared"}, "2", true},
+		{"shared as owner", FileQuery{Ownership: "shared"}, "1", false},
+		{"name ignores case", FileQuery{Name: "algebra"}, "1", true},
+		{"name mismatch", FileQuery{Name: "calculus"}, "1", false},
+		{"created after is inclusive", FileQuery{CreatedAfter: &created}, "1", true},
+		{"created after excludes older", FileQuery{CreatedAfter: &after}, "1", false},
+		{"created before is exclusive", FileQuery{CreatedBefore: &created}, "1", false},
+		{"created before includes older", FileQuery{CreatedBefore: &after}, "1", true},
+		{"created range", FileQuery{CreatedAfter: &before, CreatedBefore: &after}, "1", true},
+	}
+	for _, tc := range cases {
+		if got := tc.query.Matches(file, tc.userId); got != tc.want {
+			t.Errorf("%s: got %v, want %v", tc.name, got, tc.want)
+		}
+	}
+}
+
+func listedFiles() []ListedFile {
+	file := func(id, name, key string, day int) ListedFile {
+		return ListedFile{
+			File:  File{ID: id, UserID: "1", CreatedAt: time.Date(2026, 1, day, 0, 0, 0, 0, time.UTC), Name: name, Key: key},
+			Share: Share{UserID: "1", Key: key, FileID: id},
+		}
+	}
+	return []ListedFile{
+		file("a", "Beta", "slides/1/c.pdf", 2),
+		file("b", "alpha", "slides/1/a.pdf", 3),
+		file("c", "Gamma", "slides/1/b.pdf", 1),
+	}
+}
+
+func pageIds(page FilePage) []string {
+	ids := []string{}
+	for _, file := range page.Files {
+		ids = append(ids, file.ID)
+	}
+	return ids
+}
+
+func TestFileQueryPaginate(t *testing.T) {
+	cases := []struct {
+		name  string
+		query FileQuery
+		want  []string
+	}{
+		{"storage order by key", FileQuery{}, []string{"b", "c", "a"}},
+		{"name ignores case", FileQuery{Sort: "name", Order: "asc"}, []string{"b", "a", "c"}},
+		{"newest first", FileQuery{Sort: "created_at", Order: "desc"}, []string{"b", "a", "c"}},
+		{"oldest first", FileQuery{Sort: "created_at", Order: "asc"}, []string{"c", "a", "b"}},
+	}
+	for _, tc := range cases {
+		page, err := tc.query.Paginate(listedFiles())
+		if err != nil {
+			t.Fatalf("%s: %v", tc.name, err)
+		}
+		if got := pageIds(page); !slices.Equal(got, tc.want) {
+			t.Errorf("%s: got %v, want %v", tc.name, got, tc.want)
+		}
+		if page.Total != 3 || page.NextCursor != "" {
+			t.Errorf("%s: got total %d and cursor %q for a single page", tc.name, page.Total, page.NextCursor)
+		}
+	}
+}
+
+func TestFileQueryPaginateFollowsCursor(t *testing.T) {
+	query := FileQuery{Sort: "name", Order: "asc", Limit: 2}
+	first, err := query.Paginate(listedFiles())
+	if err != nil {
+		t.Fatal(err)
+	}
+	if got := pageIds(first); !slices.Equal(got, []string{"b", "a"}) || first.Total != 3 || first.NextCursor == "" {
+		t.Fatalf("first page: got %v, total %d, cursor %q", got, first.Total, first.NextCursor)
+	}
+
+	query.Cursor = first.NextCursor
+	second, err := query.Paginate(listedFiles())
+	if err != nil {
+		t.Fatal(err)
+	}
+	if got := pageIds(second); !slices.Equal(got, []string{"c"}) || second.Total != 3 || second.NextCursor != "" {
+		t.Fatalf("second page: got %v, total %d, cursor %q", got, second.Total, second.NextCursor)
+	}
+
+	for _, other := range []FileQuery{
+		{Sort: "created_at", Order: "asc", Cursor: first.NextCursor},
+		{Sort: "name", Order: "desc", Cursor: first.NextCursor},
+		{Cursor: first.NextCursor},
+		{Sort: "name", Order: "asc", Cursor: "not a cursor"},
+	} {
+		if _, err := other.Paginate(listedFiles()); err != ErrInvalidCursor {
+			t.Errorf("%+v: got %v, want ErrInvalidCursor", other, err)
+		}
+	}
+}
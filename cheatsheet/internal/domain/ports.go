@@ -19,8 +19,11 @@ type FileService interface {
 	GetPresignedURL(ctx context.Context, key string, ttl time.Duration) (string, error)
 	GetPresignedUploadURL(ctx context.Context, userId string, filename string, ttl time.Duration) (string, string, error)
 	GetAllFiles(ctx context.Context, userId string) ([]File, error)
+	ListFiles(ctx context.Context, userId string, query FileQuery) (FilePage, error)
 	GetFile(ctx context.Context, id string) (File, error)
-	Generate(ctx context.Context, fileIDs []string, userId string) (GenerateResult, error)
+	GetFileByKey(ctx context.Context, key string) (File, error)
+	Generate(ctx context.Context, requestID string, fileIDs []string, userId string) (GenerateResult, error)
+	GenerationStage(ctx context.Context, requestID string, userId string) (string, error)
 }
 
 type GenerateResult struct {
@@ -29,15 +32,25 @@ type GenerateResult struct {
 }
 
 type MetadataRepository interface {
-	ShareFile(ctx context.Context, userId, key, fileId string) error
+	ShareFile(ctx context.Context, userId, key, fileId string, role Role, expiresAt *time.Time) error
 	UnshareFile(ctx context.Context, userId, key string) error
+	GetSharesOfUser(ctx context.Context, userId string) ([]Share, error)
+	// GetExpiredShares returns every time-limited share that expired at or before now
+	GetExpiredShares(ctx context.Context, now time.Time) ([]Share, error)
+	// UnshareExpired removes a share only if it still expires at or before now, returning
+	// ErrNotExpired if it was renewed or made permanent since it was listed
+	UnshareExpired(ctx context.Context, userId, key string, now time.Time) error
 	GetAllFiles(ctx context.Context, userId string) ([]File, error)
+	// ListFiles returns a page of the files userId can see through an active share
+	ListFiles(ctx context.Context, userId string, query FileQuery) (FilePage, error)
 	GetFile(ctx context.Context, id string) (File, error)
+	FindFileByKey(ctx context.Context, key string) (File, error)
 	GetSharesOfFile(ctx context.Context, fileId, key string) ([]Share, error)
 }
 
 type ShareService interface {
-	Share(ctx context.Context, userId, key, fileId string) error
+	Share(ctx context.Context, userId, key, fileId string, role Role, expiresAt *time.Time) error
 	Unshare(ctx context.Context, userId, key string) error
 	GetSharesOfFile(ctx context.Context, fileId, key string) ([]Share, error)
+	GetSharesOfUser(ctx context.Context, userId string) ([]Share, error)
 }
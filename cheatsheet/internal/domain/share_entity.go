@@ -1,7 +1,81 @@
 package domain
 
+import "time"
+
+// Role is what a share lets its user do with the file. Each role includes the ones below it.
+type Role string
+
+const (
+	// RoleViewer can read and download the file
+	RoleViewer Role = "viewer"
+	// RoleEditor can also generate cheatsheets from it
+	RoleEditor Role = "editor"
+	// RoleResharer can also share it with others, up to their own role
+	RoleResharer Role = "resharer"
+	// RoleCoOwner can also unshare and delete it
+	RoleCoOwner Role = "co_owner"
+	// RoleOwner is held by the file's creator only and is never granted
+	RoleOwner Role = "owner"
+)
+
+var roleRanks = map[Role]int{
+	RoleViewer:   1,
+	RoleEditor:   2,
+	RoleResharer: 3,
+	RoleCoOwner:  4,
+	RoleOwner:    5,
+}
+
+// ParseGrantableRole accepts the roles a share may be created with; empty means viewer
+func ParseGrantableRole(s string) (Role, bool) {
+	if s == "" {
+		return RoleViewer, true
+	}
+	role := Role(s)
+	if role == RoleOwner {
+		return "", false
+	}
+	_, ok := roleRanks[role]
+	return role, ok
+}
+
+// AtLeast reports whether r includes everything other allows. Shares stored before roles
+// existed have no role and count as viewer.
+func (r Role) AtLeast(other Role) bool {
+	rank, ok := roleRanks[r]
+	if !ok {
+		rank = roleRanks[RoleViewer]
+	}
+	return rank >= roleRanks[other]
+}
+
 type Share struct {
 	UserID string `dynamodbav:"userId"`
 	Key    string `dynamodbav:"key"`
 	FileID string `dynamodbav:"fileId"`
+	Role   Role   `dynamodbav:"role,omitempty"`
+	// ExpiresAt is when a time-limited share stops granting access; nil for permanent shares
+	ExpiresAt *time.Time `dynamodbav:"expiresAt,omitempty,unixtime"`
+}
+
+// Expired reports whether the share no longer grants access at now
+func (s Share) Expired(now time.Time) bool {
+	return s.ExpiresAt != nil && !now.Before(*s.ExpiresAt)
+}
+
+// RoleOf returns the role userId holds on file, given the file's shares
+func RoleOf(file File, shares []Share, userId string) (Role, bool) {
+	if file.UserID == userId {
+		return RoleOwner, true
+	}
+	now := time.Now()
+	for _, share := range shares {
+		if share.UserID == userId && !share.Expired(now) {
+			if share.Role == "" {
+				return RoleViewer, true
+			}
+			return share.Role, true
+		}
+	}
+	return "", false
 }
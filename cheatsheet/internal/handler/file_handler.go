@@ -2,6 +2,9 @@ package handler
 
 import (
 	"context"
+	"errors"
+	"fmt"
+	"strconv"
 	"time"
 
 	"storage/internal/domain"
@@ -10,6 +13,9 @@ import (
 	"github.com/gofiber/fiber/v2"
 )
 
+// maxPresignGetTTL is how long download URLs stay valid unless a shorter ttl is asked for
+const maxPresignGetTTL = 10 * time.Minute
+
 type FileHandler struct {
 	svc      domain.FileService
 	shareSvc domain.ShareService
@@ -21,6 +27,8 @@ func NewFileHandler(svc domain.FileService, shareSvc domain.ShareService) *FileH
 
 type GenerateRequest struct {
 	FileIDs []string `json:"file_ids" validate:"required"`
+	// RequestID optionally names the generation so its progress can be looked up
+	RequestID string `json:"request_id"`
 }
 
 func (h *FileHandler) Generate(c *fiber.Ctx) error {
@@ -51,7 +59,7 @@ func (h *FileHandler) Generate(c *fiber.Ctx) error {
 		}
 	}
 
-	result, err := h.svc.Generate(c.Context(), req.FileIDs, userId)
+	result, err := h.svc.Generate(c.Context(), req.RequestID, req.FileIDs, userId)
 	if err != nil {
 		return httpx.FromDomainError(c, err)
 	}
@@ -62,6 +70,18 @@ func (h *FileHandler) Generate(c *fiber.Ctx) error {
 	})
 }
 
+func (h *FileHandler) GetGenerationStage(c *fiber.Ctx) error {
+	userId := c.Get("X-User-Id")
+	if userId == "" {
+		return httpx.BadRequest(c, "userId is required")
+	}
+	stage, err := h.svc.GenerationStage(c.Context(), c.Params("requestId"), userId)
+	if err != nil {
+		return httpx.FromDomainError(c, err)
+	}
+	return httpx.Ok(c, fiber.Map{"stage": stage})
+}
+
 func (h *FileHandler) Remove(c *fiber.Ctx) error {
 	fileType := c.Query("file_type")
 	userId := c.Query("user_id")
@@ -81,11 +101,66 @@ func (h *FileHandler) GetAllFiles(c *fiber.Ctx) error {
 	if userId == "" {
 		return httpx.BadRequest(c, "userId is required")
 	}
-	files, err := h.svc.GetAllFiles(c.Context(), userId)
+	query, err := parseFileQuery(c)
+	if err != nil {
+		return httpx.BadRequest(c, err.Error())
+	}
+	page, err := h.svc.ListFiles(c.Context(), userId, query)
 	if err != nil {
 		return httpx.FromDomainError(c, err)
 	}
-	return httpx.Ok(c, fiber.Map{"files": files})
+	// the caller's shares of the listed files, so clients can tell when access expires
+	body := fiber.Map{"files": page.Files, "shares": page.Shares, "total": page.Total}
+	if page.NextCursor != "" {
+		body["nextCursor"] = page.NextCursor
+	}
+	return httpx.Ok(c, body)
+}
+
+// parseFileQuery reads the optional filters, sort and paging of GET /files
+func parseFileQuery(c *fiber.Ctx) (domain.FileQuery, error) {
+	query := domain.FileQuery{
+		Ownership: c.Query("ownership"),
+		Name:      c.Query("name"),
+		Sort:      c.Query("sort"),
+		Cursor:    c.Query("cursor"),
+	}
+	if fileType := c.Query("type"); fileType != "" {
+		query.KeyPrefix = fileType + "/"
+	}
+	if query.Ownership != "" && query.Ownership != "mine" && query.Ownership != "shared" {
+		return query, errors.New("ownership must be mine or shared")
+	}
+	switch query.Sort {
+	case "":
+	case "name", "created_at":
+		query.Order = c.Query("order", "desc")
+		if query.Order != "asc" && query.Order != "desc" {
+			return query, errors.New("order must be asc or desc")
+		}
+	default:
+		return query, errors.New("sort must be name or created_at")
+	}
+	for name, bound := range map[string]**time.Time{
+		"created_after":  &query.CreatedAfter,
+		"created_before": &query.CreatedBefore,
+	} {
+		if raw := c.Query(name); raw != "" {
+			t, err := time.Parse(time.RFC3339, raw)
+			if err != nil {
+				return query, fmt.Errorf("%s must be an RFC 3339 timestamp", name)
+			}
+			*bound = &t
+		}
+	}
+	if raw := c.Query("limit"); raw != "" {
+		limit, err := strconv.ParseInt(raw, 10, 32)
+		if err != nil || limit <= 0 {
+			return query, errors.New("limit must be a positive number")
+		}
+		query.Limit = int32(limit)
+	}
+	return query, nil
 }
 
 func (h *FileHandler) GetFile(c *fiber.Ctx) error {
@@ -108,31 +183,92 @@ func (h *FileHandler) GetFile(c *fiber.Ctx) error {
 		return httpx.FromDomainError(c, err)
 	}
 
-	hasAccess := false
-	for _, share := range shares {
-		if share.UserID == userId {
-			hasAccess = true
-			break
-		}
+	if _, ok := domain.RoleOf(file, shares, userId); !ok {
+		return httpx.BadRequest(c, "user does not have access to file")
 	}
-	if !hasAccess {
+
+	return httpx.Ok(c, fiber.Map{"file": file, "shares": shares})
+}
+
+// GetFileByKey answers like GetFile for the file stored under key
+func (h *FileHandler) GetFileByKey(c *fiber.Ctx) error {
+	userId := c.Get("X-User-Id")
+	if userId == "" {
+		return httpx.BadRequest(c, "userId is required")
+	}
+	key := c.Query("key")
+	if key == "" {
+		return httpx.BadRequest(c, "key is required")
+	}
+
+	file, err := h.svc.GetFileByKey(c.Context(), key)
+	if err != nil {
+		return httpx.FromDomainError(c, err)
+	}
+
+	shares, err := h.shareSvc.GetSharesOfFile(c.Context(), file.ID, file.Key)
+	if err != nil {
+		return httpx.FromDomainError(c, err)
+	}
+
+	if _, ok := domain.RoleOf(file, shares, userId); !ok {
 		return httpx.BadRequest(c, "user does not have access to file")
 	}
 
 	return httpx.Ok(c, fiber.Map{"file": file, "shares": shares})
 }
 
+// GetFileRole reports the role the caller holds on a file, for services that check access
+// to a file without needing the file itself
+func (h *FileHandler) GetFileRole(c *fiber.Ctx) error {
+	userId := c.Get("X-User-Id")
+	if userId == "" {
+		return httpx.BadRequest(c, "userId is required")
+	}
+	fileId := c.Params("fileId")
+	if fileId == "" {
+		return httpx.BadRequest(c, "fileId is required")
+	}
+
+	file, err := h.svc.GetFile(c.Context(), fileId)
+	if err != nil {
+		return httpx.FromDomainError(c, err)
+	}
+
+	shares, err := h.shareSvc.GetSharesOfFile(c.Context(), file.ID, file.Key)
+	if err != nil {
+		return httpx.FromDomainError(c, err)
+	}
+
+	role, ok := domain.RoleOf(file, shares, userId)
+	if !ok {
+		return httpx.Forbidden(c, "user does not have access to file")
+	}
+
+	return httpx.Ok(c, fiber.Map{"role": role})
+}
+
 func (h *FileHandler) GetPresignedURL(c *fiber.Ctx) error {
 	key := c.Query("key")
 	if key == "" {
 		return httpx.BadRequest(c, "key is required")
 	}
 
-	url, err := h.svc.GetPresignedURL(context.Background(), key, 10*time.Minute)
+	// callers may ask for a shorter lifetime, e.g. for links opened without an account
+	ttl := maxPresignGetTTL
+	if raw := c.Query("ttl"); raw != "" {
+		secs, err := strconv.Atoi(raw)
+		if err != nil || secs <= 0 {
+			return httpx.BadRequest(c, "ttl must be a positive number of seconds")
+		}
+		ttl = min(time.Duration(secs)*time.Second, maxPresignGetTTL)
+	}
+
+	url, err := h.svc.GetPresignedURL(context.Background(), key, ttl)
 	if err != nil {
 		return httpx.FromDomainError(c, err)
 	}
-	return httpx.Ok(c, fiber.Map{"url": url, "expiresIn": 600})
+	return httpx.Ok(c, fiber.Map{"url": url, "expiresIn": int(ttl.Seconds())})
 }
 
 func (h *FileHandler) GetPresignedUploadURL(c *fiber.Ctx) error {
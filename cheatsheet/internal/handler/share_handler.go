@@ -1,6 +1,9 @@
 package handler
 
 import (
+	"context"
+	"time"
+
 	"storage/internal/domain"
 	"storage/pkg/httpx"
 
@@ -16,42 +19,89 @@ func NewShareHandler(svc domain.ShareService, fileSvc domain.FileService) *Share
 	return &ShareHandler{svc: svc, fileSvc: fileSvc}
 }
 
+// roles looks up the file and the roles the caller and the other user hold on it
+func (h *ShareHandler) roles(ctx context.Context, fileId, callerId, userId string) (domain.File, domain.Role, domain.Role, error) {
+	file, err := h.fileSvc.GetFile(ctx, fileId)
+	if err != nil {
+		return domain.File{}, "", "", err
+	}
+	shares, err := h.svc.GetSharesOfFile(ctx, file.ID, file.Key)
+	if err != nil {
+		return domain.File{}, "", "", err
+	}
+	callerRole, _ := domain.RoleOf(file, shares, callerId)
+	userRole, _ := domain.RoleOf(file, shares, userId)
+	return file, callerRole, userRole, nil
+}
+
 func (h *ShareHandler) Share(c *fiber.Ctx) error {
-	ownerId := c.Get("X-User-Id")
+	callerId := c.Get("X-User-Id")
 	userId := c.FormValue("user_id")
 	fileId := c.FormValue("file_id")
 
-	file, err := h.fileSvc.GetFile(c.Context(), fileId)
+	role, ok := domain.ParseGrantableRole(c.FormValue("role"))
+	if !ok {
+		return httpx.BadRequest(c, "role must be viewer, editor, resharer or co_owner")
+	}
+
+	var expiresAt *time.Time
+	if value := c.FormValue("expires_at"); value != "" {
+		t, err := time.Parse(time.RFC3339, value)
+		if err != nil {
+			return httpx.BadRequest(c, "expires_at must be an RFC 3339 timestamp")
+		}
+		if !t.After(time.Now()) {
+			return httpx.BadRequest(c, "expires_at must be in the future")
+		}
+		expiresAt = &t
+	}
+
+	file, callerRole, userRole, err := h.roles(c.Context(), fileId, callerId, userId)
 	if err != nil {
 		return httpx.FromDomainError(c, err)
 	}
 
-	if ownerId != file.UserID {
-		return httpx.BadRequest(c, "You are not the owner of this file")
+	if !callerRole.AtLeast(domain.RoleResharer) {
+		return httpx.Forbidden(c, "You are not allowed to share this file")
+	}
+	if !callerRole.AtLeast(role) {
+		return httpx.Forbidden(c, "You cannot grant a role above your own")
+	}
+	if userRole == domain.RoleOwner {
+		return httpx.BadRequest(c, "The owner already has access to this file")
+	}
+	if userRole != "" && !callerRole.AtLeast(userRole) {
+		return httpx.Forbidden(c, "You cannot change the role of a user above you")
 	}
 
-	if err := h.svc.Share(c.Context(), userId, file.Key, fileId); err != nil {
+	if err := h.svc.Share(c.Context(), userId, file.Key, fileId, role, expiresAt); err != nil {
 		return httpx.FromDomainError(c, err)
 	}
 	return httpx.Ok(c, fiber.Map{"shared": true})
 }
 
 func (h *ShareHandler) Unshare(c *fiber.Ctx) error {
-	ownerId := c.Get("X-User-Id")
+	callerId := c.Get("X-User-Id")
 	userId := c.FormValue("user_id")
 	fileId := c.FormValue("file_id")
 
-	if ownerId == userId {
+	if callerId == userId {
 		return httpx.BadRequest(c, "You cannot unshare yourself")
 	}
 
-	file, err := h.fileSvc.GetFile(c.Context(), fileId)
+	file, callerRole, userRole, err := h.roles(c.Context(), fileId, callerId, userId)
 	if err != nil {
 		return httpx.FromDomainError(c, err)
 	}
 
-	if ownerId != file.UserID {
-		return httpx.BadRequest(c, "You are not the owner of this file")
+	if !callerRole.AtLeast(domain.RoleCoOwner) {
+		return httpx.Forbidden(c, "You are not allowed to unshare this file")
+	}
+	if userRole == domain.RoleOwner {
+		return httpx.BadRequest(c, "The owner cannot be removed from their file")
+	}
+	if userRole != "" && !callerRole.AtLeast(userRole) {
+		return httpx.Forbidden(c, "You cannot remove a user above you")
 	}
 
 	if err := h.svc.Unshare(c.Context(), userId, file.Key); err != nil {
@@ -20,6 +20,8 @@ type GenerateResponse struct {
 	Key       string `json:"key"`
 	Success   bool   `json:"success"`
 	Error     string `json:"error,omitempty"`
+	// Stage is only set on progress messages sent before the final response
+	Stage string `json:"stage,omitempty"`
 }
 
 type SQSConsumer struct {
@@ -79,6 +81,15 @@ func (c *SQSConsumer) processMessage(ctx context.Context, message types.Message)
 		return
 	}
 
+	if response.Stage != "" {
+		// Progress is best-effort: a stale update for a finished request is simply dropped
+		if err := c.tracker.SetStage(response.RequestID, response.Stage); err != nil {
+			log.Printf("Ignoring progress for request %s: %v", response.RequestID, err)
+		}
+		c.deleteMessage(ctx, message.ReceiptHandle)
+		return
+	}
+
 	log.Printf("Received generation response: requestId=%s, success=%v", response.RequestID, response.Success)
 
 	// Complete the pending generation request
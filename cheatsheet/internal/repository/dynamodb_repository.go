@@ -2,6 +2,9 @@ package repository
 
 import (
 	"context"
+	"errors"
+	"strconv"
+	"time"
 
 	"github.com/aws/aws-sdk-go-v2/aws"
 	"github.com/aws/aws-sdk-go-v2/feature/dynamodb/attributevalue"
@@ -26,54 +29,119 @@ func NewDynamoDBRepository(client *dynamodb.Client, config *config.DynamoDBConfi
 	}
 }
 
+// GetAllFiles returns every file the user holds a share of, skipping expired shares as
+// ListFiles does, so access ends at expiry even before the sweeper removes the share
 func (r *DynamoDBRepository) GetAllFiles(ctx context.Context, userId string) ([]domain.File, error) {
-	// Step 1: Query shares table to get all fileIds user has access to
-	sharesOut, err := r.client.Query(ctx, &dynamodb.QueryInput{
-		TableName:              &r.sharesTable,
-		KeyConditionExpression: aws.String("userId = :uid"),
-		ExpressionAttributeValues: map[string]types.AttributeValue{
-			":uid": &types.AttributeValueMemberS{Value: userId},
-		},
-	})
+	shares, err := r.activeShares(ctx, userId, "")
 	if err != nil {
 		return nil, err
 	}
 
-	if len(sharesOut.Items) == 0 {
-		return []domain.File{}, nil // Return empty array if no shares found
+	var fileIds []string
+	for _, share := range shares {
+		fileIds = append(fileIds, share.FileID)
+	}
+	byId, err := r.batchGetFiles(ctx, fileIds)
+	if err != nil {
+		return nil, err
+	}
+	files := make([]domain.File, 0, len(byId))
+	for _, file := range byId {
+		files = append(files, file)
 	}
+	return files, nil
+}
 
-	// Step 2: Extract fileIds from shares and build keys for batch get
-	var keysToFetch []map[string]types.AttributeValue
-	for _, item := range sharesOut.Items {
-		var share domain.Share
-		if err := attributevalue.UnmarshalMap(item, &share); err != nil {
-			continue // Skip invalid items
+// ListFiles fetches the files behind the user's active shares, filters them and returns
+// the page query asks for. Neither table can be read in name or creation order, so every
+// match is loaded to sort and count them before the page is cut.
+func (r *DynamoDBRepository) ListFiles(ctx context.Context, userId string, query domain.FileQuery) (domain.FilePage, error) {
+	shares, err := r.activeShares(ctx, userId, query.KeyPrefix)
+	if err != nil {
+		return domain.FilePage{}, err
+	}
+
+	fileIds := make([]string, 0, len(shares))
+	for _, share := range shares {
+		fileIds = append(fileIds, share.FileID)
+	}
+	files, err := r.batchGetFiles(ctx, fileIds)
+	if err != nil {
+		return domain.FilePage{}, err
+	}
+
+	matches := make([]domain.ListedFile, 0, len(shares))
+	for _, share := range shares {
+		file, ok := files[share.FileID]
+		if !ok || !query.Matches(file, userId) {
+			continue
 		}
+		matches = append(matches, domain.ListedFile{File: file, Share: share})
+	}
+	return query.Paginate(matches)
+}
 
-		keysToFetch = append(keysToFetch, map[string]types.AttributeValue{
-			"id": &types.AttributeValueMemberS{Value: share.FileID},
-		})
+// activeShares returns the user's unexpired shares, optionally only those whose key starts
+// with keyPrefix. It follows the query across pages, as one page stops at 1 MB.
+func (r *DynamoDBRepository) activeShares(ctx context.Context, userId, keyPrefix string) ([]domain.Share, error) {
+	keyCondition := "userId = :uid"
+	values := map[string]types.AttributeValue{
+		":uid": &types.AttributeValueMemberS{Value: userId},
+		":now": &types.AttributeValueMemberN{Value: strconv.FormatInt(time.Now().Unix(), 10)},
+	}
+	input := &dynamodb.QueryInput{
+		TableName:                 &r.sharesTable,
+		FilterExpression:          aws.String("attribute_not_exists(expiresAt) OR expiresAt > :now"),
+		ExpressionAttributeValues: values,
 	}
+	if keyPrefix != "" {
+		keyCondition += " AND begins_with(#k, :prefix)"
+		values[":prefix"] = &types.AttributeValueMemberS{Value: keyPrefix}
+		input.ExpressionAttributeNames = map[string]string{"#k": "key"}
+	}
+	input.KeyConditionExpression = aws.String(keyCondition)
+	return r.queryShares(ctx, input)
+}
 
-	if len(keysToFetch) == 0 {
-		return []domain.File{}, nil
+// queryShares runs a query on the shares table through every page
+func (r *DynamoDBRepository) queryShares(ctx context.Context, input *dynamodb.QueryInput) ([]domain.Share, error) {
+	shares := []domain.Share{}
+	paginator := dynamodb.NewQueryPaginator(r.client, input)
+	for paginator.HasMorePages() {
+		page, err := paginator.NextPage(ctx)
+		if err != nil {
+			return nil, err
+		}
+		for _, item := range page.Items {
+			var share domain.Share
+			if err := attributevalue.UnmarshalMap(item, &share); err != nil {
+				continue // Skip invalid items
+			}
+			shares = append(shares, share)
+		}
 	}
+	return shares, nil
+}
 
-	// Step 3: Batch get all files in chunks of 100 (DynamoDB limit)
+// batchGetFiles fetches files by id in chunks of 100 (the DynamoDB limit). Ids that no
+// longer exist are left out.
+func (r *DynamoDBRepository) batchGetFiles(ctx context.Context, fileIds []string) (map[string]domain.File, error) {
 	const batchSize = 100
-	var files []domain.File
+	files := make(map[string]domain.File, len(fileIds))
 
-	for i := 0; i < len(keysToFetch); i += batchSize {
-		end := i + batchSize
-		if end > len(keysToFetch) {
-			end = len(keysToFetch)
+	for i := 0; i < len(fileIds); i += batchSize {
+		end := min(i+batchSize, len(fileIds))
+		keys := make([]map[string]types.AttributeValue, 0, end-i)
+		for _, id := range fileIds[i:end] {
+			keys = append(keys, map[string]types.AttributeValue{
+				"id": &types.AttributeValueMemberS{Value: id},
+			})
 		}
 
 		batchOut, err := r.client.BatchGetItem(ctx, &dynamodb.BatchGetItemInput{
 			RequestItems: map[string]types.KeysAndAttributes{
 				r.filesTable: {
-					Keys: keysToFetch[i:end],
+					Keys: keys,
 				},
 			},
 		})
@@ -81,16 +149,14 @@ func (r *DynamoDBRepository) GetAllFiles(ctx context.Context, userId string) ([]
 			return nil, err
 		}
 
-		// Step 4: Unmarshal files from this batch
 		for _, item := range batchOut.Responses[r.filesTable] {
 			var file domain.File
 			if err := attributevalue.UnmarshalMap(item, &file); err != nil {
 				continue // Skip if unmarshal fails
 			}
-			files = append(files, file)
+			files[file.ID] = file
 		}
 	}
-
 	return files, nil
 }
 
@@ -143,26 +209,88 @@ func (r *DynamoDBRepository) GetSharesOfFile(ctx context.Context, fileId, key st
 	return shares, nil
 }
 
-func (r *DynamoDBRepository) ShareFile(ctx context.Context, userId, key, fileId string) error {
+func (r *DynamoDBRepository) ShareFile(ctx context.Context, userId, key, fileId string, role domain.Role, expiresAt *time.Time) error {
+	item := map[string]types.AttributeValue{
+		"userId": &types.AttributeValueMemberS{Value: userId},
+		"key":    &types.AttributeValueMemberS{Value: key},
+		"fileId": &types.AttributeValueMemberS{Value: fileId},
+		"role":   &types.AttributeValueMemberS{Value: string(role)},
+	}
+	if expiresAt != nil {
+		item["expiresAt"] = &types.AttributeValueMemberN{Value: strconv.FormatInt(expiresAt.Unix(), 10)}
+	}
+
 	_, err := r.client.PutItem(ctx, &dynamodb.PutItemInput{
 		TableName: &r.sharesTable,
-		Item: map[string]types.AttributeValue{
+		Item:      item,
+	})
+	return err
+}
+
+func (r *DynamoDBRepository) GetSharesOfUser(ctx context.Context, userId string) ([]domain.Share, error) {
+	return r.queryShares(ctx, &dynamodb.QueryInput{
+		TableName:              &r.sharesTable,
+		KeyConditionExpression: aws.String("userId = :uid"),
+		ExpressionAttributeValues: map[string]types.AttributeValue{
+			":uid": &types.AttributeValueMemberS{Value: userId},
+		},
+	})
+}
+
+func (r *DynamoDBRepository) GetExpiredShares(ctx context.Context, now time.Time) ([]domain.Share, error) {
+	// Time-limited shares are rare, so a filtered scan is cheaper than an index on expiresAt
+	paginator := dynamodb.NewScanPaginator(r.client, &dynamodb.ScanInput{
+		TableName:        &r.sharesTable,
+		FilterExpression: aws.String("expiresAt <= :now"),
+		ExpressionAttributeValues: map[string]types.AttributeValue{
+			":now": &types.AttributeValueMemberN{Value: strconv.FormatInt(now.Unix(), 10)},
+		},
+	})
+
+	var shares []domain.Share
+	for paginator.HasMorePages() {
+		page, err := paginator.NextPage(ctx)
+		if err != nil {
+			return nil, err
+		}
+		for _, item := range page.Items {
+			var share domain.Share
+			if err := attributevalue.UnmarshalMap(item, &share); err != nil {
+				continue // Skip invalid items
+			}
+			shares = append(shares, share)
+		}
+	}
+	return shares, nil
+}
+
+func (r *DynamoDBRepository) UnshareFile(ctx context.Context, userId, key string) error {
+	_, err := r.client.DeleteItem(ctx, &dynamodb.DeleteItemInput{
+		TableName: &r.sharesTable,
+		Key: map[string]types.AttributeValue{
 			"userId": &types.AttributeValueMemberS{Value: userId},
 			"key":    &types.AttributeValueMemberS{Value: key},
-			"fileId": &types.AttributeValueMemberS{Value: fileId},
 		},
 	})
 	return err
 }
 
-func (r *DynamoDBRepository) UnshareFile(ctx context.Context, userId, key string) error {
+func (r *DynamoDBRepository) UnshareExpired(ctx context.Context, userId, key string, now time.Time) error {
 	_, err := r.client.DeleteItem(ctx, &dynamodb.DeleteItemInput{
 		TableName: &r.sharesTable,
 		Key: map[string]types.AttributeValue{
 			"userId": &types.AttributeValueMemberS{Value: userId},
 			"key":    &types.AttributeValueMemberS{Value: key},
 		},
+		ConditionExpression: aws.String("expiresAt <= :now"),
+		ExpressionAttributeValues: map[string]types.AttributeValue{
+			":now": &types.AttributeValueMemberN{Value: strconv.FormatInt(now.Unix(), 10)},
+		},
 	})
+	var conditionFailed *types.ConditionalCheckFailedException
+	if errors.As(err, &conditionFailed) {
+		return domain.ErrNotExpired
+	}
 	return err
 }
 
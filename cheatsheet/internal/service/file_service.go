@@ -80,10 +80,18 @@ func (s *FileServiceImpl) GetAllFiles(ctx context.Context, userId string) ([]dom
 	return s.metaRepo.GetAllFiles(ctx, userId)
 }
 
+func (s *FileServiceImpl) ListFiles(ctx context.Context, userId string, query domain.FileQuery) (domain.FilePage, error) {
+	return s.metaRepo.ListFiles(ctx, userId, query)
+}
+
 func (s *FileServiceImpl) GetFile(ctx context.Context, id string) (domain.File, error) {
 	return s.metaRepo.GetFile(ctx, id)
 }
 
+func (s *FileServiceImpl) GetFileByKey(ctx context.Context, key string) (domain.File, error) {
+	return s.metaRepo.FindFileByKey(ctx, key)
+}
+
 func (s *FileServiceImpl) GetPresignedURL(ctx context.Context, key string, ttl time.Duration) (string, error) {
 	return s.repo.PresignGet(ctx, key, ttl)
 }
@@ -99,9 +107,13 @@ func (s *FileServiceImpl) GetPresignedUploadURL(ctx context.Context, userId stri
 	return result, key, nil
 }
 
-func (s *FileServiceImpl) Generate(ctx context.Context, fileIDs []string, userId string) (domain.GenerateResult, error) {
-	requestID := uuid.NewString()
-	resultCh := s.generationTracker.Register(requestID)
+// Generate runs a generation under the caller's requestID, so the caller can follow its
+// progress with GenerationStage. A new ID is used when none is given.
+func (s *FileServiceImpl) Generate(ctx context.Context, requestID string, fileIDs []string, userId string) (domain.GenerateResult, error) {
+	if requestID == "" {
+		requestID = uuid.NewString()
+	}
+	resultCh := s.generationTracker.Register(requestID, userId)
 
 	if err := s.sqsPublisher.PublishGenerateRequest(ctx, fileIDs, userId, requestID); err != nil {
 		return domain.GenerateResult{}, err
@@ -117,3 +129,12 @@ func (s *FileServiceImpl) Generate(ctx context.Context, fileIDs []string, userId
 		Key:    result.Key,
 	}, nil
 }
+
+// GenerationStage reports the progress of a pending generation the user started
+func (s *FileServiceImpl) GenerationStage(ctx context.Context, requestID string, userId string) (string, error) {
+	stage, ok := s.generationTracker.Stage(requestID, userId)
+	if !ok {
+		return "", domain.ErrNotFound
+	}
+	return stage, nil
+}
@@ -19,10 +19,21 @@ func (r GenerationResult) IsError() bool {
 	return r.Error != nil
 }
 
+// StageQueued is the stage of a request that has been published but not picked up yet
+const StageQueued = "queued"
+
+// generationProgress is the last reported stage of a pending request and the user who
+// started it
+type generationProgress struct {
+	stage   string
+	ownerID string
+}
+
 // GenerationTracker manages pending generation requests
 type GenerationTracker struct {
 	mu      sync.RWMutex
 	pending map[string]chan GenerationResult
+	stages  map[string]generationProgress
 	timeout time.Duration
 }
 
@@ -30,20 +41,50 @@ type GenerationTracker struct {
 func NewGenerationTracker(timeout time.Duration) *GenerationTracker {
 	return &GenerationTracker{
 		pending: make(map[string]chan GenerationResult),
+		stages:  make(map[string]generationProgress),
 		timeout: timeout,
 	}
 }
 
-// Register creates a new pending generation request
-func (t *GenerationTracker) Register(requestID string) chan GenerationResult {
+// Register creates a new pending generation request started by ownerID
+func (t *GenerationTracker) Register(requestID string, ownerID string) chan GenerationResult {
 	t.mu.Lock()
 	defer t.mu.Unlock()
 
 	ch := make(chan GenerationResult, 1)
 	t.pending[requestID] = ch
+	t.stages[requestID] = generationProgress{stage: StageQueued, ownerID: ownerID}
 	return ch
 }
 
+// SetStage records the stage reported by the generator for a pending request
+func (t *GenerationTracker) SetStage(requestID string, stage string) error {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+
+	if _, exists := t.pending[requestID]; !exists {
+		return domain.ErrNotFound
+	}
+
+	progress := t.stages[requestID]
+	progress.stage = stage
+	t.stages[requestID] = progress
+	return nil
+}
+
+// Stage returns the last reported stage of a pending request started by ownerID. Requests
+// started by anyone else are reported as missing, so their IDs can't be probed.
+func (t *GenerationTracker) Stage(requestID string, ownerID string) (string, bool) {
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+
+	progress, exists := t.stages[requestID]
+	if !exists || progress.ownerID != ownerID {
+		return "", false
+	}
+	return progress.stage, true
+}
+
 // Complete marks a generation request as complete
 func (t *GenerationTracker) Complete(requestID string, result GenerationResult) error {
 	t.mu.Lock()
@@ -57,6 +98,7 @@ func (t *GenerationTracker) Complete(requestID string, result GenerationResult)
 	ch <- result
 	close(ch)
 	delete(t.pending, requestID)
+	delete(t.stages, requestID)
 
 	return nil
 }
@@ -76,12 +118,14 @@ func (t *GenerationTracker) Wait(ctx context.Context, requestID string, resultCh
 	case <-timeoutCtx.Done():
 		t.mu.Lock()
 		delete(t.pending, requestID)
+		delete(t.stages, requestID)
 		t.mu.Unlock()
 		return GenerationResult{}, domain.ErrTimeout
 
 	case <-ctx.Done():
 		t.mu.Lock()
 		delete(t.pending, requestID)
+		delete(t.stages, requestID)
 		t.mu.Unlock()
 		return GenerationResult{}, ctx.Err()
 	}
@@ -96,5 +140,6 @@ func (t *GenerationTracker) Cleanup() {
 	for id, ch := range t.pending {
 		close(ch)
 		delete(t.pending, id)
+		delete(t.stages, id)
 	}
 }
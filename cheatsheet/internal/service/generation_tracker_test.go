@@ -0,0 +1,24 @@
+package service
+
+import (
+	"testing"
+	"time"
+)
+
+func TestStageIsOnlyReportedToTheOwner(t *testing.T) {
+	tracker := NewGenerationTracker(time.Minute)
+	tracker.Register("req-1", "1")
+	if err := tracker.SetStage("req-1", "summarising"); err != nil {
+		t.Fatalf("SetStage: %v", err)
+	}
+
+	if stage, ok := tracker.Stage("req-1", "1"); !ok || stage != "summarising" {
+		t.Fatalf("owner got stage %q, %v; want summarising", stage, ok)
+	}
+	if stage, ok := tracker.Stage("req-1", "2"); ok {
+		t.Fatalf("another user got stage %q", stage)
+	}
+	if _, ok := tracker.Stage("req-2", "1"); ok {
+		t.Fatal("got a stage for an unknown request")
+	}
+}
@@ -2,6 +2,7 @@ package service
 
 import (
 	"context"
+	"time"
 
 	"storage/internal/domain"
 )
@@ -18,8 +19,12 @@ func (s *ShareServiceImpl) GetSharesOfFile(ctx context.Context, fileId, key stri
 	return s.metaRepo.GetSharesOfFile(ctx, fileId, key)
 }
 
-func (s *ShareServiceImpl) Share(ctx context.Context, userId, key, fileId string) error {
-	return s.metaRepo.ShareFile(ctx, userId, key, fileId)
+func (s *ShareServiceImpl) GetSharesOfUser(ctx context.Context, userId string) ([]domain.Share, error) {
+	return s.metaRepo.GetSharesOfUser(ctx, userId)
+}
+
+func (s *ShareServiceImpl) Share(ctx context.Context, userId, key, fileId string, role domain.Role, expiresAt *time.Time) error {
+	return s.metaRepo.ShareFile(ctx, userId, key, fileId, role, expiresAt)
 }
 
 func (s *ShareServiceImpl) Unshare(ctx context.Context, userId, key string) error {
@@ -0,0 +1,68 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"log"
+	"time"
+
+	"storage/internal/domain"
+)
+
+// ShareSweeper deletes time-limited shares once they expire. Until it runs, expired
+// shares still exist but no longer grant access.
+type ShareSweeper struct {
+	metaRepo domain.MetadataRepository
+	interval time.Duration
+}
+
+// NewShareSweeper creates a sweeper that runs every interval
+func NewShareSweeper(metaRepo domain.MetadataRepository, interval time.Duration) *ShareSweeper {
+	return &ShareSweeper{metaRepo: metaRepo, interval: interval}
+}
+
+// Start sweeps until ctx is cancelled
+func (s *ShareSweeper) Start(ctx context.Context) {
+	log.Printf("Starting share sweeper, every %s", s.interval)
+
+	ticker := time.NewTicker(s.interval)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-ctx.Done():
+			log.Println("Share sweeper shutting down")
+			return
+		case <-ticker.C:
+			s.sweep(ctx)
+		}
+	}
+}
+
+func (s *ShareSweeper) sweep(ctx context.Context) {
+	now := time.Now()
+	shares, err := s.metaRepo.GetExpiredShares(ctx, now)
+	if err != nil {
+		log.Printf("Error listing expired shares: %v", err)
+		return
+	}
+
+	removed, skipped := 0, 0
+	for _, share := range shares {
+		// The share may have been renewed since the scan; the delete only goes through if
+		// it is still expired
+		err := s.metaRepo.UnshareExpired(ctx, share.UserID, share.Key, now)
+		if errors.Is(err, domain.ErrNotExpired) {
+			skipped++
+			continue
+		}
+		if err != nil {
+			log.Printf("Error removing expired share of file %s for user %s: %v", share.FileID, share.UserID, err)
+			continue
+		}
+		removed++
+	}
+	if removed > 0 || skipped > 0 {
+		log.Printf("Removed %d expired shares, skipped %d renewed since listed", removed, skipped)
+	}
+}
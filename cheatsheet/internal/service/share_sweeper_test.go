@@ -0,0 +1,98 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+
+	"storage/internal/domain"
+)
+
+// fakeShares keeps shares in memory and implements the parts of the repository the
+// sweeper uses
+type fakeShares struct {
+	domain.MetadataRepository
+	shares  map[string]domain.Share
+	listErr error
+}
+
+func (f *fakeShares) GetExpiredShares(_ context.Context, now time.Time) ([]domain.Share, error) {
+	if f.listErr != nil {
+		return nil, f.listErr
+	}
+	var expired []domain.Share
+	for _, share := range f.shares {
+		if share.Expired(now) {
+			expired = append(expired, share)
+		}
+	}
+	return expired, nil
+}
+
+func (f *fakeShares) UnshareExpired(_ context.Context, userId, key string, now time.Time) error {
+	share, ok := f.shares[userId+"/"+key]
+	if !ok || !share.Expired(now) {
+		return domain.ErrNotExpired
+	}
+	delete(f.shares, userId+"/"+key)
+	return nil
+}
+
+func share(userId, key string, expiresAt *time.Time) domain.Share {
+	return domain.Share{UserID: userId, Key: key, FileID: key, Role: domain.RoleViewer, ExpiresAt: expiresAt}
+}
+
+func TestSweepRemovesOnlyExpiredShares(t *testing.T) {
+	past := time.Now().Add(-time.Hour)
+	future := time.Now().Add(time.Hour)
+	repo := &fakeShares{shares: map[string]domain.Share{
+		"1/a": share("1", "a", &past),
+		"2/a": share("2", "a", &future),
+		"3/a": share("3", "a", nil),
+	}}
+
+	NewShareSweeper(repo, time.Minute).sweep(context.Background())
+
+	if _, ok := repo.shares["1/a"]; ok {
+		t.Error("expired share was not removed")
+	}
+	if _, ok := repo.shares["2/a"]; !ok {
+		t.Error("share that has not expired was removed")
+	}
+	if _, ok := repo.shares["3/a"]; !ok {
+		t.Error("permanent share was removed")
+	}
+}
+
+// renewingShares makes a share permanent between the scan and the delete
+type renewingShares struct {
+	*fakeShares
+}
+
+func (r renewingShares) GetExpiredShares(ctx context.Context, now time.Time) ([]domain.Share, error) {
+	expired, err := r.fakeShares.GetExpiredShares(ctx, now)
+	for _, s := range expired {
+		r.shares[s.UserID+"/"+s.Key] = share(s.UserID, s.Key, nil)
+	}
+	return expired, err
+}
+
+func TestSweepKeepsShareRenewedAfterListing(t *testing.T) {
+	past := time.Now().Add(-time.Hour)
+	repo := renewingShares{&fakeShares{shares: map[string]domain.Share{
+		"1/a": share("1", "a", &past),
+	}}}
+
+	NewShareSweeper(repo, time.Minute).sweep(context.Background())
+
+	if _, ok := repo.shares["1/a"]; !ok {
+		t.Error("renewed share was removed")
+	}
+}
+
+func TestSweepToleratesListError(t *testing.T) {
+	repo := &fakeShares{listErr: errors.New("unavailable")}
+
+	NewShareSweeper(repo, time.Minute).sweep(context.Background())
+}
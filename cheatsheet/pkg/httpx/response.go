@@ -14,6 +14,9 @@ func Ok(c *fiber.Ctx, data any) error {
 func BadRequest(c *fiber.Ctx, msg string) error {
 	return c.Status(fiber.StatusBadRequest).JSON(fiber.Map{"success": false, "error": msg})
 }
+func Forbidden(c *fiber.Ctx, msg string) error {
+	return c.Status(fiber.StatusForbidden).JSON(fiber.Map{"success": false, "error": msg})
+}
 func NotFound(c *fiber.Ctx, msg string) error {
 	return c.Status(fiber.StatusNotFound).JSON(fiber.Map{"success": false, "error": msg})
 }
@@ -25,7 +28,7 @@ func FromDomainError(c *fiber.Ctx, err error) error {
 	switch err {
 	case domain.ErrNotFound:
 		return NotFound(c, err.Error())
-	case domain.ErrTooLarge, domain.ErrInvalidType:
+	case domain.ErrTooLarge, domain.ErrInvalidType, domain.ErrInvalidCursor:
 		return BadRequest(c, err.Error())
 	default:
 		return Internal(c, err)
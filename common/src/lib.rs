@@ -0,0 +1,5 @@
+//! Code shared by the gateway and the user service
+
+pub mod request_signer;
+pub mod shutdown;
+pub mod telemetry;
@@ -0,0 +1,198 @@
+use std::future::IntoFuture;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Phase {
+    Running,
+    /// Readiness fails so load balancers stop sending traffic, but connections are still
+    /// accepted
+    Draining,
+    /// The listener has stopped; in-flight requests have until the deadline to finish
+    Stopping,
+}
+
+/// Shared shutdown state, observed by the server, the drain deadline and health checks.
+/// Shutting down first fails readiness and only stops the listener `pre_stop_delay` later,
+/// so load balancers notice before connections are refused.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<Phase>>,
+    pre_stop_delay: Duration,
+}
+
+impl Shutdown {
+    pub fn new(pre_stop_delay: Duration) -> Self {
+        let (tx, _) = watch::channel(Phase::Running);
+        Self {
+            tx: Arc::new(tx),
+            pre_stop_delay,
+        }
+    }
+
+    /// Whether shutdown has begun, in which case readiness should fail
+    pub fn is_shutting_down(&self) -> bool {
+        *self.tx.borrow() >= Phase::Draining
+    }
+
+    /// Begins shutting down. Triggering again has no further effect.
+    pub fn trigger(&self) {
+        let started = self.tx.send_if_modified(|phase| {
+            let started = *phase == Phase::Running;
+            if started {
+                *phase = Phase::Draining;
+            }
+            started
+        });
+        if started {
+            let tx = self.tx.clone();
+            let delay = self.pre_stop_delay;
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                tx.send_replace(Phase::Stopping);
+            });
+        }
+    }
+
+    /// Resolves once shutdown has begun
+    pub async fn wait(&self) {
+        self.wait_for(Phase::Draining).await;
+    }
+
+    /// Resolves once the listener should stop accepting connections
+    pub async fn wait_for_stop(&self) {
+        self.wait_for(Phase::Stopping).await;
+    }
+
+    async fn wait_for(&self, phase: Phase) {
+        let mut rx = self.tx.subscribe();
+        // the sender lives in `self`, so this can't fail while we're borrowed
+        let _ = rx.wait_for(|current| *current >= phase).await;
+    }
+
+    /// Spawns a task that triggers shutdown on SIGTERM or ctrl-c
+    pub fn listen_for_signals(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            info!(
+                "Shutdown signal received, failing readiness for {:?} before draining in-flight requests",
+                shutdown.pre_stop_delay
+            );
+            shutdown.trigger();
+        });
+    }
+
+    /// Drives `server` to completion, giving up on stragglers once `timeout` has passed
+    /// since the listener stopped
+    pub async fn drain<F, E>(&self, server: F, timeout: Duration) -> Result<(), E>
+    where
+        F: IntoFuture<Output = Result<(), E>>,
+    {
+        let deadline = async {
+            self.wait_for_stop().await;
+            tokio::time::sleep(timeout).await;
+        };
+
+        tokio::select! {
+            result = server.into_future() => {
+                info!("All in-flight requests drained");
+                result
+            }
+            _ = deadline => {
+                warn!("Shutdown deadline of {:?} exceeded, dropping remaining requests", timeout);
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for ctrl-c: {:?}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_secs(5);
+
+    #[tokio::test(start_paused = true)]
+    async fn fails_readiness_before_stopping_the_listener() {
+        let shutdown = Shutdown::new(DELAY);
+        assert!(!shutdown.is_shutting_down());
+
+        let start = tokio::time::Instant::now();
+        shutdown.trigger();
+        assert!(shutdown.is_shutting_down());
+        shutdown.wait().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        shutdown.wait_for_stop().await;
+        assert_eq!(start.elapsed(), DELAY);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn triggering_again_does_not_restart_the_delay() {
+        let shutdown = Shutdown::new(DELAY);
+        let start = tokio::time::Instant::now();
+        shutdown.trigger();
+        tokio::time::sleep(DELAY / 2).await;
+        shutdown.trigger();
+
+        shutdown.wait_for_stop().await;
+        assert_eq!(start.elapsed(), DELAY);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drain_gives_up_after_the_deadline() {
+        let shutdown = Shutdown::new(DELAY);
+        let timeout = Duration::from_secs(10);
+        let start = tokio::time::Instant::now();
+        shutdown.trigger();
+
+        let result: Result<(), ()> = shutdown
+            .drain(std::future::pending::<Result<(), ()>>(), timeout)
+            .await;
+        assert_eq!(result, Ok(()));
+        assert_eq!(start.elapsed(), DELAY + timeout);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn drain_returns_the_server_result() {
+        let shutdown = Shutdown::new(DELAY);
+        let result = shutdown
+            .drain(async { Err::<(), _>("bind failed") }, DELAY)
+            .await;
+        assert_eq!(result, Err("bind failed"));
+    }
+}
@@ -0,0 +1,108 @@
+use opentelemetry::{global, trace::TracerProvider as _};
+use opentelemetry_http::HeaderExtractor;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{Resource, propagation::TraceContextPropagator, trace::SdkTracerProvider};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+/// Flushes pending spans when dropped at the end of `main`
+pub struct TelemetryGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("Failed to shut down tracer provider: {:?}", e);
+        }
+    }
+}
+
+/// Sets up structured logging and W3C trace context propagation, exporting spans over OTLP
+/// when `otlp_endpoint` is set. Spans are traced even without an endpoint, so a caller's
+/// trace still reaches the services this one calls.
+pub fn init(service_name: &str, otlp_endpoint: Option<&str>) -> anyhow::Result<TelemetryGuard> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let provider = tracer_provider(service_name, otlp_endpoint)?;
+    let otel_layer =
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(service_name.to_string()));
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel_layer)
+        .try_init()?;
+
+    global::set_tracer_provider(provider.clone());
+    Ok(TelemetryGuard { provider })
+}
+
+fn tracer_provider(
+    service_name: &str,
+    otlp_endpoint: Option<&str>,
+) -> anyhow::Result<SdkTracerProvider> {
+    let mut builder = SdkTracerProvider::builder().with_resource(
+        Resource::builder()
+            .with_service_name(service_name.to_string())
+            .build(),
+    );
+    if let Some(endpoint) = otlp_endpoint {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+            .build()?;
+        builder = builder.with_batch_exporter(exporter);
+    }
+    Ok(builder.build())
+}
+
+/// Makes `span` continue the trace the caller sent in `headers`, if any
+pub fn continue_trace(span: &Span, headers: &http::HeaderMap) {
+    let parent =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    // fails only when no OpenTelemetry layer is installed, as in code that skipped `init`
+    let _ = span.set_parent(parent);
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+    use opentelemetry_http::HeaderInjector;
+    use tracing::info_span;
+
+    use super::*;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    #[test]
+    fn continues_the_callers_trace_without_an_exporter() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = tracer_provider("test", None).unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let mut incoming = http::HeaderMap::new();
+            incoming.insert(
+                "traceparent",
+                HeaderValue::from_str(&format!("00-{}-00f067aa0ba902b7-01", TRACE_ID)).unwrap(),
+            );
+            let span = info_span!("request");
+            continue_trace(&span, &incoming);
+
+            let mut outgoing = http::HeaderMap::new();
+            let context = span.context();
+            global::get_text_map_propagator(|propagator| {
+                propagator.inject_context(&context, &mut HeaderInjector(&mut outgoing))
+            });
+            let traceparent = outgoing["traceparent"].to_str().unwrap();
+            assert!(
+                traceparent.starts_with(&format!("00-{}-", TRACE_ID)),
+                "trace was not continued: {}",
+                traceparent
+            );
+        });
+    }
+}
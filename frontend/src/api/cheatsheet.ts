@@ -1,4 +1,4 @@
-import { client } from "./client"
+import { client, publicClient } from "./client"
 import { components } from "./schema" // สมมติว่ามี schema export
 
 // ใช้ types จาก schema
@@ -15,6 +15,37 @@ export type GetPresignedUploadUrlResponse = components["schemas"]["GetPresignedU
 export type GetPresignedGetUrlResponse = components["schemas"]["GetPresignedGetUrlResponse"]
 export type GetFileResponse = components["schemas"]["GetFileResponse"]
 export type Share = components["schemas"]["Share"]
+export type PendingInvitation = components["schemas"]["PendingInvitation"]
+export type ShareRole = components["schemas"]["ShareRole"]
+export type BulkShareAction = components["schemas"]["BulkShareAction"]
+export type ShareRecipient = components["schemas"]["ShareRecipient"]
+export type BulkShareResponse = components["schemas"]["BulkShareResponse"]
+export type ShareLink = components["schemas"]["ShareLink"]
+export type CreateShareLinkResponse = components["schemas"]["CreateShareLinkResponse"]
+export type ResolveShareLinkResponse = components["schemas"]["ResolveShareLinkResponse"]
+
+// เรียงจากสิทธิ์น้อยไปมาก แต่ละ role มีสิทธิ์ของ role ก่อนหน้าด้วย
+export const SHARE_ROLES: ShareRole[] = ["viewer", "editor", "resharer", "co_owner", "owner"]
+
+export const SHARE_ROLE_LABELS: Record<ShareRole, string> = {
+  viewer: "Viewer",
+  editor: "Editor",
+  resharer: "Can reshare",
+  co_owner: "Co-owner",
+  owner: "Owner",
+}
+
+// ระยะเวลาของการแชร์ ไม่ระบุ seconds = แชร์ถาวร
+export const SHARE_EXPIRY_OPTIONS: { value: string; label: string; seconds?: number }[] = [
+  { value: "never", label: "No expiry" },
+  { value: "1d", label: "1 day", seconds: 24 * 60 * 60 },
+  { value: "7d", label: "7 days", seconds: 7 * 24 * 60 * 60 },
+  { value: "30d", label: "30 days", seconds: 30 * 24 * 60 * 60 },
+]
+
+/** Whether `role` includes everything `needed` allows */
+export const roleAtLeast = (role: ShareRole | undefined, needed: ShareRole): boolean =>
+  role !== undefined && SHARE_ROLES.indexOf(role) >= SHARE_ROLES.indexOf(needed)
 
 // Extended types สำหรับใช้งานใน frontend (ถ้าต้องการ shared info)
 export interface FileWithShares extends File {
@@ -26,7 +57,10 @@ export interface FileWithShares extends File {
  * GET /api/cheatsheet/files
  */
 export const getAllFiles = async (): Promise<File[]> => {
-  const response = await client.GET("/api/cheatsheet/files", {})
+  // newest first; without a sort the gateway returns storage order
+  const response = await client.GET("/api/cheatsheet/files", {
+    params: { query: { sort: "created_at" } },
+  })
   
   if (response.error) {
     throw new Error("Failed to fetch files")
@@ -75,12 +109,13 @@ export const getFileById = async (fileId: string): Promise<GetFileResponse> => {
  * @param fileType - "slides" or "cheatsheets"
  * @param filename - just the filename with random prefix, e.g. "9e25d9_cheatsheet.pdf"
  */
-export const removeFile = async (fileType: FileType, filename: string): Promise<void> => {
-  console.log("Deleting file:", { fileType, filename })
+export const removeFile = async (fileType: FileType, filename: string, fileId?: string): Promise<void> => {
+  console.log("Deleting file:", { fileType, filename, fileId })
 
   const response = await client.DELETE("/api/cheatsheet/files", {
     params: {
-      query: { file_type: fileType, file: filename },
+      // fileId ใช้เมื่อลบไฟล์ของคนอื่นในฐานะ co-owner
+      query: { file_type: fileType, file: filename, file_id: fileId },
     },
   })
 
@@ -237,11 +272,47 @@ export const downloadFile = async (key: string): Promise<void> => {
  * Share a cheatsheet file with another user
  * POST /api/cheatsheet/share
  */
-export const shareFile = async (fileId: string, userId: string): Promise<ShareResponse> => {
+export const shareFile = async (
+  fileId: string,
+  userId: string,
+  role: ShareRole = "viewer",
+  expiresInSecs?: number,
+): Promise<ShareResponse> => {
   const response = await client.POST("/api/cheatsheet/share", {
     body: {
       file_id: fileId,
       user_id: userId,
+      role,
+      expires_in_secs: expiresInSecs,
+    },
+  })
+
+  if (response.error) {
+    const errObj = response.error as Record<string, unknown>
+    const msg = typeof errObj?.message === 'string' ? (errObj.message as string) : undefined
+    throw new Error(msg || "Failed to share file")
+  }
+
+  return response.data as ShareResponse
+}
+
+/**
+ * Share a cheatsheet file by email. Emails without an account get a pending invitation
+ * (`pending: true`) that is granted when they first sign in.
+ * POST /api/cheatsheet/share
+ */
+export const shareFileByEmail = async (
+  fileId: string,
+  email: string,
+  role: ShareRole = "viewer",
+  expiresInSecs?: number,
+): Promise<ShareResponse> => {
+  const response = await client.POST("/api/cheatsheet/share", {
+    body: {
+      file_id: fileId,
+      email,
+      role,
+      expires_in_secs: expiresInSecs,
     },
   })
 
@@ -254,6 +325,58 @@ export const shareFile = async (fileId: string, userId: string): Promise<ShareRe
   return response.data as ShareResponse
 }
 
+/**
+ * Cancel a pending invitation
+ * POST /api/cheatsheet/unshare
+ */
+export const cancelInvitation = async (fileId: string, email: string): Promise<UnshareResponse> => {
+  const response = await client.POST("/api/cheatsheet/unshare", {
+    body: {
+      file_id: fileId,
+      email,
+    },
+  })
+
+  if (response.error) {
+    const errObj = response.error as Record<string, unknown>
+    const msg = typeof errObj?.message === 'string' ? (errObj.message as string) : undefined
+    throw new Error(msg || "Failed to cancel invitation")
+  }
+
+  return response.data as UnshareResponse
+}
+
+/**
+ * Share or unshare several files with several users at once. Each file and recipient pair
+ * succeeds or fails independently; check `results` for the outcome of each.
+ * POST /api/cheatsheet/share/bulk
+ */
+export const bulkShare = async (
+  action: BulkShareAction,
+  fileIds: string[],
+  recipients: ShareRecipient[],
+  role: ShareRole = "viewer",
+  expiresInSecs?: number,
+): Promise<BulkShareResponse> => {
+  const response = await client.POST("/api/cheatsheet/share/bulk", {
+    body: {
+      action,
+      file_ids: fileIds,
+      recipients,
+      role,
+      expires_in_secs: expiresInSecs,
+    },
+  })
+
+  if (response.error) {
+    const errObj = response.error as Record<string, unknown>
+    const msg = typeof errObj?.message === 'string' ? (errObj.message as string) : undefined
+    throw new Error(msg || "Failed to share files")
+  }
+
+  return response.data as BulkShareResponse
+}
+
 /**
  * Unshare a cheatsheet file (revoke access)
  * POST /api/cheatsheet/unshare
@@ -275,20 +398,148 @@ export const unshareFile = async (fileId: string, userId: string): Promise<Unsha
   return response.data as UnshareResponse
 }
 
+/**
+ * Get the current state of a generation job
+ * GET /api/cheatsheet/generate/:job_id
+ */
+export const getGenerationJob = async (jobId: string): Promise<GenerateResponse> => {
+  // Dynamic path, same workaround as getFileById
+  const clientDynamic = client as unknown as {
+    GET: (
+      path: string,
+      options?: Record<string, unknown>
+    ) => Promise<{
+      data?: GenerateResponse
+      error?: unknown
+    }>
+  }
+  const response = await clientDynamic.GET(`/api/cheatsheet/generate/${jobId}`, {})
+
+  if (response.error) {
+    throw new Error("Failed to fetch generation status")
+  }
+
+  return response.data as GenerateResponse
+}
+
 /**
  * Generate a PDF from files
- * POST /api/cheatsheet/generate
+ * POST /api/cheatsheet/generate, then polls the job until it finishes
  */
-export const generateCheatsheet = async (fileIds: string[]): Promise<GenerateResponse> => {
+export const generateCheatsheet = async (
+  fileIds: string[],
+  pollIntervalMs = 2000
+): Promise<{ file_id: string; key: string }> => {
   const response = await client.POST("/api/cheatsheet/generate", {
     body: {
       file_ids: fileIds,
     },
   })
 
-  if (response.error) {
+  if (response.response.status === 429) {
+    const retryAfter = Number(response.response.headers.get("Retry-After"))
+    const wait = retryAfter > 0 ? ` Try again in ${Math.ceil(retryAfter / 60)} min.` : ""
+    throw new Error(`Generation limit reached.${wait}`)
+  }
+
+  if (response.error || !response.data) {
     throw new Error("Failed to generate cheatsheet")
   }
 
-  return response.data as GenerateResponse
-}
\ No newline at end of file
+  let job = response.data as GenerateResponse
+  while (job.status === "queued" || job.status === "running") {
+    await new Promise(resolve => setTimeout(resolve, pollIntervalMs))
+    job = await getGenerationJob(job.job_id)
+  }
+
+  if (job.status !== "succeeded" || !job.file_id || !job.key) {
+    throw new Error(job.error?.message || "Failed to generate cheatsheet")
+  }
+
+  return { file_id: job.file_id, key: job.key }
+}
+
+/** URL of the page that opens a public link */
+export const shareLinkUrl = (token: string): string => `${window.location.origin}/s/${token}`
+
+/**
+ * Create a public link to a file. The token is only returned here; keep the URL now.
+ * POST /api/cheatsheet/files/{file_id}/links
+ */
+export const createShareLink = async (
+  fileId: string,
+  options: { password?: string; expiresInSecs?: number } = {},
+): Promise<CreateShareLinkResponse> => {
+  const response = await client.POST("/api/cheatsheet/files/{file_id}/links", {
+    params: { path: { file_id: fileId } },
+    body: {
+      password: options.password || undefined,
+      expires_in_secs: options.expiresInSecs,
+    },
+  })
+
+  if (response.error) {
+    const errObj = response.error as Record<string, unknown>
+    const msg = typeof errObj?.message === 'string' ? (errObj.message as string) : undefined
+    throw new Error(msg || "Failed to create link")
+  }
+
+  return response.data as CreateShareLinkResponse
+}
+
+/**
+ * List the public links to a file
+ * GET /api/cheatsheet/files/{file_id}/links
+ */
+export const getShareLinks = async (fileId: string): Promise<ShareLink[]> => {
+  const response = await client.GET("/api/cheatsheet/files/{file_id}/links", {
+    params: { path: { file_id: fileId } },
+  })
+
+  if (response.error) {
+    const errObj = response.error as Record<string, unknown>
+    const msg = typeof errObj?.message === 'string' ? (errObj.message as string) : undefined
+    throw new Error(msg || "Failed to load links")
+  }
+
+  return response.data?.links || []
+}
+
+/**
+ * Revoke a public link
+ * DELETE /api/cheatsheet/files/{file_id}/links/{link_id}
+ */
+export const revokeShareLink = async (fileId: string, linkId: string): Promise<void> => {
+  const response = await client.DELETE("/api/cheatsheet/files/{file_id}/links/{link_id}", {
+    params: { path: { file_id: fileId, link_id: linkId } },
+  })
+
+  if (response.error) {
+    const errObj = response.error as Record<string, unknown>
+    const msg = typeof errObj?.message === 'string' ? (errObj.message as string) : undefined
+    throw new Error(msg || "Failed to revoke link")
+  }
+}
+
+/**
+ * Open a public link without signing in. Throws with `status` 401 when the link needs a
+ * password or the password is wrong.
+ * POST /api/cheatsheet/links/{token}
+ */
+export const resolveShareLink = async (
+  token: string,
+  password?: string,
+): Promise<ResolveShareLinkResponse> => {
+  const response = await publicClient.POST("/api/cheatsheet/links/{token}", {
+    params: { path: { token } },
+    body: { password: password || undefined },
+  })
+
+  if (response.error) {
+    const errObj = response.error as Record<string, unknown>
+    const msg = typeof errObj?.message === 'string' ? (errObj.message as string) : undefined
+    throw Object.assign(new Error(msg || "Failed to open link"), { status: response.response.status })
+  }
+
+  return response.data as ResolveShareLinkResponse
+}
@@ -36,4 +36,15 @@ const client = createClient<paths>({
 
 client.use(middleware)
 
-export { client }
+// สำหรับหน้าที่เปิดได้โดยไม่ต้อง login เช่นลิงก์สาธารณะ: ไม่แนบ token และไม่ redirect เมื่อได้ 401
+const publicClient = createClient<paths>({
+  baseUrl: process.env.NEXT_PUBLIC_BACKEND_URL || "http://localhost:3001",
+  fetch: (url, init = {}) => {
+    return fetch(url, {
+      ...init,
+      cache: "no-store",
+    })
+  },
+})
+
+export { client, publicClient }
@@ -15,7 +15,7 @@ export interface paths {
         get: operations["get_all_files"];
         put?: never;
         post?: never;
-        /** @description Delete a file from S3 and remove all associated shares from DynamoDB. */
+        /** @description Delete a file from S3 and remove all associated shares from DynamoDB. Co-owners delete a file another user owns by also passing its `file_id`. */
         delete: operations["remove"];
         options?: never;
         head?: never;
@@ -39,6 +39,49 @@ export interface paths {
         patch?: never;
         trace?: never;
     };
+    "/api/cheatsheet/files/{file_id}/links": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description File to link to */
+                file_id: string;
+            };
+            cookie?: never;
+        };
+        /** @description List the public links to a file that have not expired. Needs the `resharer` role. */
+        get: operations["get_share_links"];
+        put?: never;
+        /** @description Create a public link to a file, which anyone holding it can open without an account. Links can be protected with a password and can expire. Needs the `resharer` role. The returned token cannot be retrieved again. */
+        post: operations["create_share_link"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/api/cheatsheet/files/{file_id}/links/{link_id}": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description File the link points to */
+                file_id: string;
+                /** @description Id of the link */
+                link_id: string;
+            };
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        post?: never;
+        /** @description Revoke a public link; it stops working immediately. Co-owners can revoke any link to the file, resharers only the links they created. */
+        delete: operations["revoke_share_link"];
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
     "/api/cheatsheet/generate": {
         parameters: {
             query?: never;
@@ -48,7 +91,7 @@ export interface paths {
         };
         get?: never;
         put?: never;
-        /** @description Generate a combined PDF from multiple files you have access to. Sends a request to SQS and waits for the Lambda to process and merge the PDFs. Returns the key of the generated file. */
+        /** @description Start generating a combined PDF from up to 20 files you hold at least the `editor` role on. Returns a job immediately; poll `GET /api/cheatsheet/generate/{job_id}` until it has succeeded to get the key of the generated file. */
         post: operations["generate"];
         delete?: never;
         options?: never;
@@ -56,6 +99,67 @@ export interface paths {
         patch?: never;
         trace?: never;
     };
+    "/api/cheatsheet/generate/{job_id}": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Job id returned when the generation was submitted */
+                job_id: string;
+            };
+            cookie?: never;
+        };
+        /** @description Get the status of a generation job. `file_id` and `key` are set once it has succeeded. */
+        get: operations["get_generation"];
+        put?: never;
+        post?: never;
+        /** @description Cancel a queued or running generation job. Finished jobs are returned unchanged. Cancelling stops the gateway tracking the job; a generation that was already running may still finish and store its file. */
+        delete: operations["cancel_generation"];
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/api/cheatsheet/generate/{job_id}/events": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Job id returned when the generation was submitted */
+                job_id: string;
+            };
+            cookie?: never;
+        };
+        /** @description Stream the progress of a generation job as server-sent events. Each event is named after the job's stage (`queued`, `downloading_sources`, `summarising`, `rendering`, `done`, `failed` or `cancelled`) and carries the job as JSON. The first event is the current state; the stream ends after a terminal stage, whose event holds the final `key` or `error`. */
+        get: operations["generation_events"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
+    "/api/cheatsheet/links/{token}": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Token from the link */
+                token: string;
+            };
+            cookie?: never;
+        };
+        get?: never;
+        put?: never;
+        /** @description Open a public link. Needs no authentication; returns a short-lived download URL for the file. Send `{}` as the body, or the password for a protected link. Limited per client IP; five wrong passwords in a row lock a link for 15 minutes. */
+        post: operations["resolve_share_link"];
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
     "/api/cheatsheet/presigned": {
         parameters: {
             query?: never;
@@ -99,7 +203,7 @@ export interface paths {
         };
         get?: never;
         put?: never;
-        /** @description Share your file with another user. Creates a share record in DynamoDB. Your user id is determined from the Auth header. */
+        /** @description Share a file with another user, by user id or by email, granting them a role. Creates a share record in DynamoDB. Owners can grant up to `co_owner`; other users need the `resharer` role and can grant up to their own role. Your user id is determined from the Auth header. */
         post: operations["share"];
         delete?: never;
         options?: never;
@@ -107,7 +211,7 @@ export interface paths {
         patch?: never;
         trace?: never;
     };
-    "/api/cheatsheet/unshare": {
+    "/api/cheatsheet/share/bulk": {
         parameters: {
             query?: never;
             header?: never;
@@ -116,24 +220,25 @@ export interface paths {
         };
         get?: never;
         put?: never;
-        /** @description Revoke a user's access to a file. Removes the share record from DynamoDB. Your user id is determined from the Auth header. */
-        post: operations["unshare"];
+        /** @description Share or unshare several files with several users in one call. Every file is paired with every recipient, and each pair succeeds or fails on its own; the response reports the outcome per pair. Recipients are given by user id or email, with the same rules as `/api/cheatsheet/share`. */
+        post: operations["bulk_share"];
         delete?: never;
         options?: never;
         head?: never;
         patch?: never;
         trace?: never;
     };
-    "/api/user/all": {
+    "/api/cheatsheet/unshare": {
         parameters: {
             query?: never;
             header?: never;
             path?: never;
             cookie?: never;
         };
-        get: operations["get_all_users"];
+        get?: never;
         put?: never;
-        post?: never;
+        /** @description Revoke a user's access to a file. Removes the share record from DynamoDB. Needs the `co_owner` role; the owner and users above the caller's role cannot be removed. Your user id is determined from the Auth header. */
+        post: operations["unshare"];
         delete?: never;
         options?: never;
         head?: never;
@@ -172,6 +277,23 @@ export interface paths {
         patch?: never;
         trace?: never;
     };
+    "/api/user/search": {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        /** @description Find users by name or email for typeahead, ordered by name, with cursor-based paging. */
+        get: operations["search_users"];
+        put?: never;
+        post?: never;
+        delete?: never;
+        options?: never;
+        head?: never;
+        patch?: never;
+        trace?: never;
+    };
     "/api/user/validate-token": {
         parameters: {
             query?: never;
@@ -192,8 +314,75 @@ export interface paths {
 export type webhooks = Record<string, never>;
 export interface components {
     schemas: {
+        /** @description Error body returned by every gateway endpoint */
+        ApiError: {
+            code: components["schemas"]["ErrorCode"];
+            details?: components["schemas"]["FieldError"][];
+            /** @description Human-readable description, safe to show to end users */
+            message: string;
+            request_id?: string | null;
+        };
+        /** @enum {string} */
+        BulkShareAction: "share" | "unshare";
+        /** @description Applies `action` to every pair of file and recipient */
+        BulkShareRequest: {
+            action: components["schemas"]["BulkShareAction"];
+            file_ids: string[];
+            recipients: components["schemas"]["ShareRecipient"][];
+            /** @description Makes the shares time-limited, as in `ShareRequest`; ignored when unsharing */
+            expires_at?: string | null;
+            /**
+             * Format: int64
+             * @description Alternative to `expires_at`, as in `ShareRequest`
+             */
+            expires_in_secs?: number | null;
+            /** @description Role to grant when sharing; ignored when unsharing */
+            role?: components["schemas"]["ShareRole"];
+        };
+        BulkShareResponse: {
+            failed: number;
+            /** @description One entry per file and recipient pair, files first, in request order */
+            results: components["schemas"]["BulkShareResult"][];
+            succeeded: number;
+        };
+        BulkShareResult: components["schemas"]["ShareRecipient"] & {
+            error?: components["schemas"]["ApiError"] | null;
+            file_id: string;
+            /** @description The recipient has no account yet and was invited instead */
+            pending: boolean;
+            success: boolean;
+        };
+        CreateShareLinkRequest: {
+            /** @description RFC 3339 time the link stops working. Without this or `expires_in_secs` the link
+             *     works until it is revoked. */
+            expires_at?: string | null;
+            /**
+             * Format: int64
+             * @description Seconds from now until the link stops working; alternative to `expires_at`
+             */
+            expires_in_secs?: number | null;
+            /** @description Protects the link with a password of at least 8 characters */
+            password?: string | null;
+        };
+        CreateShareLinkResponse: {
+            link: components["schemas"]["ShareLink"];
+            /** @description Secret part of the link, opened with `POST /api/cheatsheet/links/{token}`.
+             *     It is not stored and cannot be retrieved again. */
+            token: string;
+        };
+        /**
+         * @description Stable, machine-readable error codes. Clients should branch on these rather than on `message`.
+         * @enum {string}
+         */
+        ErrorCode: "invalid_argument" | "unauthenticated" | "permission_denied" | "not_found" | "conflict" | "rate_limited" | "service_unavailable" | "deadline_exceeded" | "upstream_error" | "internal_error";
+        FieldError: {
+            field: string;
+            message: string;
+        };
         File: {
             createdAt: string;
+            /** @description When the caller's time-limited share of this file ends, RFC 3339 */
+            expiresAt?: string | null;
             id: string;
             key: string;
             name: string;
@@ -204,18 +393,39 @@ export interface components {
         GenerateRequest: {
             file_ids: string[];
         };
+        /** @description A cheatsheet generation job. `file_id` and `key` are set once it has succeeded,
+         *     `error` once it has failed. */
         GenerateResponse: {
-            file_id: string;
-            key: string;
+            error?: null | {
+                code: string;
+                message: string;
+            };
+            file_id?: string | null;
+            job_id: string;
+            key?: string | null;
+            stage: components["schemas"]["GenerationStage"];
+            status: components["schemas"]["GenerationStatus"];
         };
+        /**
+         * @description Finer-grained progress of a generation job, as reported by the cheatsheet backend.
+         *     The last three are terminal and mirror the job's status.
+         * @enum {string}
+         */
+        GenerationStage: "queued" | "downloading_sources" | "summarising" | "rendering" | "done" | "failed" | "cancelled";
+        /** @enum {string} */
+        GenerationStatus: "queued" | "running" | "succeeded" | "failed" | "cancelled";
         GetAllFilesResponse: {
             files: components["schemas"]["File"][];
-        };
-        GetAllUsersResponse: {
-            users: components["schemas"]["UserProfile"][];
+            /** @description Pass as `cursor` to fetch the next page; absent on the last page */
+            next_cursor?: string | null;
+            /** @description Number of files matching the filters, across all pages; only counted when sorting,
+             *     since pages in storage order are read one at a time */
+            total?: number | null;
         };
         GetFileResponse: {
             file: components["schemas"]["File"];
+            /** @description Pending invitations; only listed for the owner and co-owners */
+            invitations?: components["schemas"]["PendingInvitation"][];
             shares: components["schemas"]["Share"][];
         };
         GetPresignedGetUrlResponse: {
@@ -227,6 +437,9 @@ export interface components {
             key: string;
             url: string;
         };
+        GetShareLinksResponse: {
+            links: components["schemas"]["ShareLink"][];
+        };
         LoginRequest: {
             code: string;
         };
@@ -236,24 +449,98 @@ export interface components {
             name: string;
             token: string;
         };
+        /** @description A share with an email that has no account yet; granted when the email first logs in */
+        PendingInvitation: {
+            email: string;
+            /** @description When the share granted on sign-up ends, RFC 3339 */
+            expires_at?: string | null;
+            /** @description RFC 3339 */
+            invited_at: string;
+            role: components["schemas"]["ShareRole"];
+        };
         RemoveFileQuery: {
             file: string;
+            /** @description Id of the file, to remove a file another user owns as its co-owner.
+             *     Without it, `file` names one of the caller's own files. */
+            file_id?: string | null;
             file_type: components["schemas"]["FileType"];
         };
+        ResolveShareLinkRequest: {
+            /** @description Required when the link has a password */
+            password?: string | null;
+        };
+        ResolveShareLinkResponse: {
+            expires_in: string;
+            name: string;
+            /** @description Presigned download URL */
+            url: string;
+        };
+        RevokeShareLinkResponse: {
+            revoked: boolean;
+        };
+        SearchUsersResponse: {
+            /** @description Pass as `cursor` to get the next page; absent on the last page */
+            next_cursor?: string | null;
+            users: components["schemas"]["UserProfile"][];
+        };
         Share: {
+            /** @description When this time-limited share ends, RFC 3339 */
+            expiresAt?: string | null;
             name: string;
+            role: components["schemas"]["ShareRole"];
             userId: string;
         };
+        /** @description Exactly one of `user_id` and `email` must be set */
+        /** @description A public link that opens a file without an account. The token in the link is only
+         *     returned when the link is created. */
+        ShareLink: {
+            /** @description RFC 3339 */
+            created_at: string;
+            created_by: string;
+            /** @description RFC 3339; absent for a link that never expires */
+            expires_at?: string | null;
+            file_id: string;
+            /** @description Whoever opens the link has to enter a password */
+            has_password: boolean;
+            id: string;
+        };
+        /** @description Someone to share with, by user id or by email; exactly one must be set */
+        ShareRecipient: {
+            email?: string | null;
+            user_id?: string | null;
+        };
         ShareRequest: {
+            /** @description Shares with the account using this email, or invites it if there is none yet */
+            email?: string | null;
+            /** @description RFC 3339 time the share stops granting access. Without this or `expires_in_secs`
+             *     the share is permanent. */
+            expires_at?: string | null;
+            /**
+             * Format: int64
+             * @description Seconds from now until the share stops granting access; alternative to `expires_at`
+             */
+            expires_in_secs?: number | null;
             file_id: string;
-            user_id: string;
+            /** @description Role to grant, at most the caller's own; `owner` cannot be granted */
+            role?: components["schemas"]["ShareRole"];
+            user_id?: string | null;
         };
         ShareResponse: {
+            /** @description True when the email has no account yet; the file is shared once it signs up */
+            pending?: boolean;
             shared: boolean;
         };
+        /**
+         * @description What a user may do with a file they can see. Each role includes the ones before it.
+         * @enum {string}
+         */
+        ShareRole: "viewer" | "editor" | "resharer" | "co_owner" | "owner";
+        /** @description Exactly one of `user_id` and `email` must be set */
         UnshareRequest: {
+            /** @description Cancels the pending invitation of this email */
+            email?: string | null;
             file_id: string;
-            user_id: string;
+            user_id?: string | null;
         };
         UnshareResponse: {
             unshared: boolean;
@@ -281,7 +568,26 @@ export type $defs = Record<string, never>;
 export interface operations {
     get_all_files: {
         parameters: {
-            query?: never;
+            query?: {
+                /** @description Only slides or only cheatsheets, by key prefix */
+                type?: "slides" | "cheatsheets";
+                /** @description Only files the user owns (`mine`) or that were shared with them (`shared`) */
+                ownership?: "mine" | "shared";
+                /** @description Case-insensitive substring of the file name */
+                name?: string;
+                /** @description Inclusive lower bound, RFC 3339 timestamp or YYYY-MM-DD */
+                created_after?: string;
+                /** @description Exclusive upper bound, RFC 3339 timestamp or YYYY-MM-DD */
+                created_before?: string;
+                /** @description Sort field; without it files come in storage order */
+                sort?: "created_at" | "name";
+                /** @description Sort order when sorting, defaults to desc */
+                order?: "asc" | "desc";
+                /** @description Page size, 1 to 100 */
+                limit?: number;
+                /** @description next_cursor from the previous page, with the same sort and order */
+                cursor?: string;
+            };
             header?: never;
             path?: never;
             cookie?: never;
@@ -313,6 +619,8 @@ export interface operations {
                 file_type: string;
                 /** @description Filename to delete e.g. 82a354_test.pdf */
                 file: string;
+                /** @description Id of a file owned by another user, which the caller co-owns */
+                file_id?: string;
             };
             header?: never;
             path?: never;
@@ -383,8 +691,8 @@ export interface operations {
             };
         };
         responses: {
-            /** @description Generation request accepted */
-            200: {
+            /** @description Generation job queued */
+            202: {
                 headers: {
                     [name: string]: unknown;
                 };
@@ -399,6 +707,21 @@ export interface operations {
                 };
                 content?: never;
             };
+            /** @description Rate limit, daily generation quota or cap on jobs in progress exceeded */
+            429: {
+                headers: {
+                    /** @description Seconds until the limit resets */
+                    "Retry-After"?: number;
+                    /** @description Requests allowed in the current window */
+                    "X-RateLimit-Limit"?: number;
+                    /** @description Requests left in the current window */
+                    "X-RateLimit-Remaining"?: number;
+                    /** @description Seconds until the current window ends */
+                    "X-RateLimit-Reset"?: number;
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
             /** @description Internal server error */
             500: {
                 headers: {
@@ -408,6 +731,117 @@ export interface operations {
             };
         };
     };
+    get_generation: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Job id returned when the generation was submitted */
+                job_id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Success */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["GenerateResponse"];
+                };
+            };
+            /** @description Unauthorized */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+            /** @description Job not found */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+        };
+    };
+    generation_events: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Job id returned when the generation was submitted */
+                job_id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Event stream of the job's state */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "text/event-stream": components["schemas"]["GenerateResponse"];
+                };
+            };
+            /** @description Unauthorized */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+            /** @description Job not found */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+        };
+    };
+    cancel_generation: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Job id returned when the generation was submitted */
+                job_id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Success */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["GenerateResponse"];
+                };
+            };
+            /** @description Unauthorized */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+            /** @description Job not found */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+        };
+    };
     get_presigned_get_url: {
         parameters: {
             query: {
@@ -513,6 +947,51 @@ export interface operations {
             };
         };
     };
+    bulk_share: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["BulkShareRequest"];
+            };
+        };
+        responses: {
+            /** @description Outcome of every file and recipient pair */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["BulkShareResponse"];
+                };
+            };
+            /** @description Bad request */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+            /** @description Unauthorized */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+            /** @description Too many requests */
+            429: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+        };
+    };
     unshare: {
         parameters: {
             query?: never;
@@ -544,22 +1023,43 @@ export interface operations {
             };
         };
     };
-    get_all_users: {
+    get_share_links: {
         parameters: {
             query?: never;
             header?: never;
-            path?: never;
+            path: {
+                /** @description File whose links to list */
+                file_id: string;
+            };
             cookie?: never;
         };
         requestBody?: never;
         responses: {
-            /** @description Success */
+            /** @description Links, oldest first */
             200: {
                 headers: {
                     [name: string]: unknown;
                 };
                 content: {
-                    "application/json": components["schemas"]["GetAllUsersResponse"];
+                    "application/json": components["schemas"]["GetShareLinksResponse"];
+                };
+            };
+            /** @description Unauthorized */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Missing the resharer role on the file */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
                 };
             };
             /** @description Internal server error */
@@ -567,7 +1067,196 @@ export interface operations {
                 headers: {
                     [name: string]: unknown;
                 };
-                content?: never;
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+        };
+    };
+    create_share_link: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description File to link to */
+                file_id: string;
+            };
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["CreateShareLinkRequest"];
+            };
+        };
+        responses: {
+            /** @description Link created */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["CreateShareLinkResponse"];
+                };
+            };
+            /** @description Bad request */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Unauthorized */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Missing the resharer role on the file */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Internal server error */
+            500: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+        };
+    };
+    revoke_share_link: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description File the link points to */
+                file_id: string;
+                /** @description Id of the link */
+                link_id: string;
+            };
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Link revoked */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["RevokeShareLinkResponse"];
+                };
+            };
+            /** @description Unauthorized */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Not allowed to revoke the link */
+            403: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description No such link to the file */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Internal server error */
+            500: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+        };
+    };
+    resolve_share_link: {
+        parameters: {
+            query?: never;
+            header?: never;
+            path: {
+                /** @description Token from the link */
+                token: string;
+            };
+            cookie?: never;
+        };
+        requestBody: {
+            content: {
+                "application/json": components["schemas"]["ResolveShareLinkRequest"];
+            };
+        };
+        responses: {
+            /** @description Download URL */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ResolveShareLinkResponse"];
+                };
+            };
+            /** @description The link needs a password, or the password is wrong */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Unknown, revoked or expired link */
+            404: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Rate limit exceeded, or the link is locked after too many wrong passwords */
+            429: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
+            };
+            /** @description Internal server error */
+            500: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["ApiError"];
+                };
             };
         };
     };
@@ -629,6 +1318,54 @@ export interface operations {
             };
         };
     };
+    search_users: {
+        parameters: {
+            query: {
+                /** @description At least 2 characters; every word must prefix a word of the name or email */
+                q: string;
+                /** @description Page size, defaults to 20, capped at 50 */
+                limit?: number;
+                /** @description next_cursor from the previous page, with the same q */
+                cursor?: string;
+            };
+            header?: never;
+            path?: never;
+            cookie?: never;
+        };
+        requestBody?: never;
+        responses: {
+            /** @description Matching users */
+            200: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content: {
+                    "application/json": components["schemas"]["SearchUsersResponse"];
+                };
+            };
+            /** @description Bad request */
+            400: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+            /** @description Unauthorized */
+            401: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+            /** @description Internal server error */
+            500: {
+                headers: {
+                    [name: string]: unknown;
+                };
+                content?: never;
+            };
+        };
+    };
     validate_token: {
         parameters: {
             query?: never;
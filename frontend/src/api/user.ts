@@ -11,3 +11,18 @@ export const getGoogleLoginUrl = async () => {
   console.log(response.data)
   return response.data
 }
+
+export interface UserSearchResult {
+  id: string
+  name: string
+}
+
+export const searchUsers = async (query: string, limit = 8): Promise<UserSearchResult[]> => {
+  const { data, error } = await client.GET("/api/user/search", {
+    params: { query: { q: query, limit } },
+  })
+  if (error || !data) {
+    return []
+  }
+  return data.users
+}
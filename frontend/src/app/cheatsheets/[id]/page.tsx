@@ -7,12 +7,26 @@ import { ArrowLeft, Download, Trash2, Link2, Presentation, FileCheck } from "luc
 import { AppSidebar } from "@/components/app-sidebar"
 import { SidebarToggle } from "@/components/sidebar-toggle"
 import { useRouter, useParams } from "next/navigation"
-import { getFileById, shareFile, unshareFile, removeFile, downloadFile, type File, type Share } from "@/api/cheatsheet"
+import {
+  getFileById,
+  shareFile,
+  shareFileByEmail,
+  cancelInvitation,
+  unshareFile,
+  removeFile,
+  downloadFile,
+  type File,
+  type Share,
+  type PendingInvitation,
+  type ShareRole,
+  roleAtLeast,
+} from "@/api/cheatsheet"
 import { useAuth } from "@/components/auth-provider"
 import { useSidebar } from "@/components/sidebar-provider"
 import { cn } from "@/lib/utils"
 import { CheatsheetPreview } from "@/components/cheatsheet-preview-wrapper"
 import { ShareDialog } from "@/components/share-dialog"
+import { ShareLinksDialog } from "@/components/share-links-dialog"
 import { useToast } from "@/hooks/use-toast"
 import {
   AlertDialog,
@@ -40,6 +54,7 @@ export default function CheatsheetViewPage() {
   const [file, setFile] = useState<File | null>(null)
   const [isLoading, setIsLoading] = useState(true)
   const [sharedUsers, setSharedUsers] = useState<SharedUserWithDate[]>([])
+  const [invitations, setInvitations] = useState<PendingInvitation[]>([])
   const [showDeleteDialog, setShowDeleteDialog] = useState(false)
   
   const isLoadingRef = useRef(false)
@@ -61,9 +76,12 @@ export default function CheatsheetViewPage() {
         data.shares.map((s) => ({
           userId: s.userId,
           name: s.name,
+          role: s.role,
+          expiresAt: s.expiresAt,
           sharedAt: new Date().toISOString(),
         })),
       )
+      setInvitations(data.invitations ?? [])
     } catch (error) {
       console.error("Error loading file:", error)
       toast({
@@ -112,11 +130,11 @@ export default function CheatsheetViewPage() {
     }
   }, [file, router, loadFile])
 
-  const handleShare = async (targetUserId: string) => {
+  const handleShare = async (targetUserId: string, role: ShareRole, expiresInSecs?: number) => {
     if (!file) return
 
     try {
-      await shareFile(fileId, targetUserId)
+      await shareFile(fileId, targetUserId, role, expiresInSecs)
       await loadFile()
 
       window.dispatchEvent(
@@ -137,6 +155,52 @@ export default function CheatsheetViewPage() {
     }
   }
 
+  const handleInvite = async (email: string, role: ShareRole, expiresInSecs?: number) => {
+    if (!file) return
+
+    try {
+      const result = await shareFileByEmail(fileId, email, role, expiresInSecs)
+      await loadFile()
+
+      window.dispatchEvent(
+        new CustomEvent("filesChanged", { 
+          detail: { action: "shared", files: [file.key] } 
+        })
+      )
+
+      toast({
+        title: "Success",
+        description: result.pending
+          ? `${email} doesn't have an account yet. They'll get access when they sign up.`
+          : "File shared successfully",
+      })
+    } catch (error) {
+      console.error("Error sharing file:", error)
+      toast({
+        title: "Error",
+        description: error instanceof Error ? error.message : "Failed to share file",
+        variant: "destructive",
+      })
+      throw error
+    }
+  }
+
+  const handleCancelInvitation = async (email: string) => {
+    try {
+      await cancelInvitation(fileId, email)
+      await loadFile()
+      toast({ title: "Success", description: "Invitation cancelled" })
+    } catch (error) {
+      console.error("Error cancelling invitation:", error)
+      toast({
+        title: "Error",
+        description: error instanceof Error ? error.message : "Failed to cancel invitation",
+        variant: "destructive",
+      })
+      throw error
+    }
+  }
+
   const handleUnshare = async (targetUserId: string) => {
     if (!file) return
 
@@ -174,7 +238,8 @@ export default function CheatsheetViewPage() {
     try {
       const fileType = file.key.startsWith("slides/") ? "slides" : "cheatsheets"
       const filename = file.key.split("/").pop() || file.key
-      await removeFile(fileType, filename)
+      // co-owner ลบไฟล์ของคนอื่นได้โดยส่ง file id ไปด้วย
+      await removeFile(fileType, filename, isOwner ? undefined : fileId)
 
       toast({
         title: "Success",
@@ -223,6 +288,11 @@ export default function CheatsheetViewPage() {
   const fileType = isSlide ? "Slide" : "Cheatsheet"
   const fileTypeName = isSlide ? "slide" : "cheatsheet"
   const isOwner = file?.userId === user.id
+  const myRole: ShareRole | undefined = isOwner
+    ? "owner"
+    : sharedUsers.find((u) => u.userId === user.id)?.role
+  const canShare = roleAtLeast(myRole, "resharer")
+  const canManage = roleAtLeast(myRole, "co_owner")
 
   if (isLoading) {
     return (
@@ -315,7 +385,7 @@ export default function CheatsheetViewPage() {
                       }).format(new Date(file.createdAt))}
                     </span>
                   </div>
-                  {isOwner && sharedUsers.filter((u) => u.userId !== user.id).length > 0 && (
+                  {canManage && sharedUsers.filter((u) => u.userId !== user.id).length > 0 && (
                     <Badge
                       variant="secondary"
                       className="mt-2 sm:mt-3 text-xs bg-primary/10 text-primary border-primary/20"
@@ -337,19 +407,36 @@ export default function CheatsheetViewPage() {
                     Download
                   </Button>
 
-                  {isOwner && (
-                    <>
-                      <div className="w-full sm:w-auto">
-                        <ShareDialog
-                          fileId={fileId}
-                          fileName={file.name}
-                          sharedUsers={sharedUsers}
-                          currentUserId={user.id}
-                          onShare={handleShare}
-                          onUnshare={handleUnshare}
-                        />
-                      </div>
+                  {canShare && (
+                    <div className="w-full sm:w-auto">
+                      <ShareDialog
+                        fileId={fileId}
+                        fileName={file.name}
+                        sharedUsers={sharedUsers}
+                        currentUserId={user.id}
+                        currentUserRole={myRole}
+                        onShare={handleShare}
+                        invitations={invitations}
+                        onInvite={handleInvite}
+                        onCancelInvitation={canManage ? handleCancelInvitation : undefined}
+                        onUnshare={canManage ? handleUnshare : undefined}
+                      />
+                    </div>
+                  )}
 
+                  {canShare && (
+                    <div className="w-full sm:w-auto">
+                      <ShareLinksDialog
+                        fileId={fileId}
+                        fileName={file.name}
+                        currentUserId={user.id}
+                        canRevokeAll={canManage}
+                      />
+                    </div>
+                  )}
+
+                  {canManage && (
+                    <>
                       <Button
                         variant="outline"
                         size="sm"
@@ -2,7 +2,7 @@
 
 import { useState, useEffect, useCallback, useRef, useMemo } from "react"
 import { Button } from "@/components/ui/button"
-import { Plus, FileCheck, Presentation, Loader2, Search, X, RefreshCw } from "lucide-react"
+import { Plus, FileCheck, Presentation, Loader2, Search, X, RefreshCw, Share2 } from "lucide-react"
 import { Checkbox } from "@/components/ui/checkbox"
 import { AppSidebar } from "@/components/app-sidebar"
 import { SidebarToggle } from "@/components/sidebar-toggle"
@@ -25,6 +25,7 @@ import {
   AlertDialogTitle,
 } from "@/components/ui/alert-dialog"
 import { UploadModal } from "@/components/upload-modal"
+import { BulkShareDialog } from "@/components/bulk-share-dialog"
 import { Input } from "@/components/ui/input"
 
 // Debounce utility
@@ -99,6 +100,7 @@ export default function DashboardPage() {
   const [searchQuery, setSearchQuery] = useState("")
   const [showSearch, setShowSearch] = useState(false)
   const [isRefreshing, setIsRefreshing] = useState(false)
+  const [showBulkShare, setShowBulkShare] = useState(false)
 
   const loadFiles = useCallback(async () => {
     console.log("Dashboard: loadFiles called")
@@ -413,6 +415,17 @@ export default function DashboardPage() {
               )}
             </div>
             <div className="flex items-center gap-1.5 sm:gap-2">
+              {selectedSlides.size > 0 && (
+                <Button
+                  variant="outline"
+                  onClick={() => setShowBulkShare(true)}
+                  className="hidden md:flex text-xs sm:text-sm h-9"
+                  size="sm"
+                >
+                  <Share2 className="h-3 w-3 sm:h-4 sm:w-4 mr-1 sm:mr-2" />
+                  Share ({selectedSlides.size})
+                </Button>
+              )}
               {selectedSlides.size > 0 && (
                 <Button
                   onClick={handleGenerateCheatsheets}
@@ -685,6 +698,16 @@ export default function DashboardPage() {
         )}
       </main>
 
+      <BulkShareDialog
+        open={showBulkShare}
+        onOpenChange={setShowBulkShare}
+        fileIds={Array.from(selectedSlides)}
+        fileNames={Object.fromEntries(files.map((f) => [f.id, f.name]))}
+        onComplete={() =>
+          window.dispatchEvent(new CustomEvent("filesChanged", { detail: { action: "shared" } }))
+        }
+      />
+
       <UploadModal open={showUploadModal} onOpenChange={setShowUploadModal} onUploadComplete={handleUploadComplete} />
 
       <Dialog open={showGenerateModal} onOpenChange={(open) => !isGenerating && setShowGenerateModal(open)}>
@@ -0,0 +1,107 @@
+"use client"
+
+import { useState, useEffect, useCallback } from "react"
+import { useParams } from "next/navigation"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { AlertCircle, Download, Lock } from "lucide-react"
+import { resolveShareLink, type ResolveShareLinkResponse } from "@/api/cheatsheet"
+
+// หน้าเปิดลิงก์สาธารณะ ใช้ได้โดยไม่ต้อง login
+export default function ShareLinkPage() {
+  const params = useParams()
+  const token = params.token as string
+
+  const [file, setFile] = useState<ResolveShareLinkResponse | null>(null)
+  const [needsPassword, setNeedsPassword] = useState(false)
+  const [password, setPassword] = useState("")
+  const [isLoading, setIsLoading] = useState(true)
+  const [error, setError] = useState<string | null>(null)
+
+  const open = useCallback(async (password?: string) => {
+    setIsLoading(true)
+    setError(null)
+    try {
+      setFile(await resolveShareLink(token, password))
+      setNeedsPassword(false)
+    } catch (err) {
+      const status = (err as { status?: number }).status
+      if (status === 401) {
+        setNeedsPassword(true)
+        // ครั้งแรกยังไม่ได้ใส่รหัสผ่าน จึงไม่ต้องแสดง error
+        if (password) {
+          setError(err instanceof Error ? err.message : "Incorrect password")
+        }
+      } else {
+        setError(err instanceof Error ? err.message : "Failed to open link")
+      }
+    } finally {
+      setIsLoading(false)
+    }
+  }, [token])
+
+  useEffect(() => {
+    open()
+  }, [open])
+
+  return (
+    <div className="bg-muted flex min-h-[100svh] flex-col items-center justify-center gap-10 p-6 md:p-10">
+      <div className="text-center space-y-2">
+        <h1 className="text-4xl font-extrabold tracking-tight">
+          OPEN EXAM
+        </h1>
+      </div>
+
+      <div className="flex w-full max-w-sm flex-col gap-4 rounded-2xl border bg-background p-6">
+        {file ? (
+          <>
+            <p className="text-sm text-muted-foreground">Shared file</p>
+            <p className="font-medium break-words">{file.name}</p>
+            <Button asChild>
+              <a href={file.url} target="_blank" rel="noopener noreferrer">
+                <Download className="h-4 w-4 mr-2" />
+                Download
+              </a>
+            </Button>
+          </>
+        ) : needsPassword ? (
+          <>
+            <p className="flex items-center gap-2 text-sm font-medium">
+              <Lock className="h-4 w-4" />
+              This link is password protected
+            </p>
+            <div className="flex gap-2">
+              <Input
+                type="password"
+                placeholder="Password"
+                value={password}
+                onChange={(e) => setPassword(e.target.value)}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter' && password) {
+                    open(password)
+                  }
+                }}
+                disabled={isLoading}
+                className="flex-1"
+              />
+              <Button onClick={() => open(password)} disabled={isLoading || !password}>
+                Open
+              </Button>
+            </div>
+          </>
+        ) : isLoading ? (
+          <div className="flex justify-center py-4">
+            <div className="h-6 w-6 border-2 border-primary border-t-transparent rounded-full animate-spin" />
+          </div>
+        ) : null}
+
+        {error && (
+          <div className="flex items-start gap-2 p-3 bg-destructive/10 border border-destructive/20 rounded-lg">
+            <AlertCircle className="h-4 w-4 text-destructive mt-0.5 flex-shrink-0" />
+            <p className="text-sm text-destructive">{error}</p>
+          </div>
+        )}
+      </div>
+    </div>
+  )
+}
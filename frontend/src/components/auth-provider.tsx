@@ -14,6 +14,11 @@ interface AuthContextType {
 const AuthContext = createContext<AuthContextType | undefined>(undefined)
 
 const PUBLIC_ROUTES = ["/signin"]
+// ลิงก์สาธารณะ /s/{token} เปิดได้โดยไม่ต้องมีบัญชี
+const PUBLIC_ROUTE_PREFIXES = ["/s/"]
+
+const isPublicRoute = (pathname: string) =>
+  PUBLIC_ROUTES.includes(pathname) || PUBLIC_ROUTE_PREFIXES.some((prefix) => pathname.startsWith(prefix))
 
 interface AuthProviderProps {
   children: ReactNode
@@ -31,7 +36,7 @@ export function AuthProvider({ children }: AuthProviderProps) {
 
       const userProfile = userService.getSavedUserProfile()
 
-      if (!userProfile && !PUBLIC_ROUTES.includes(pathname)) {
+      if (!userProfile && !isPublicRoute(pathname)) {
         router.replace("/signin")
         setIsLoading(false)
         return
@@ -0,0 +1,170 @@
+"use client"
+
+import { useState } from "react"
+import { Button } from "@/components/ui/button"
+import { Textarea } from "@/components/ui/textarea"
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter
+} from "@/components/ui/dialog"
+import { AlertCircle, CheckCircle2, Mail, XCircle } from "lucide-react"
+import {
+  bulkShare,
+  type BulkShareAction,
+  type BulkShareResponse,
+  type ShareRecipient,
+  type ShareRole,
+} from "@/api/cheatsheet"
+import { ShareRoleSelect } from "@/components/share-role-select"
+import { ShareExpirySelect } from "@/components/share-expiry-select"
+
+interface BulkShareDialogProps {
+  open: boolean
+  onOpenChange: (open: boolean) => void
+  fileIds: string[]
+  fileNames: Record<string, string>
+  onComplete?: () => void
+}
+
+// แยกผู้รับจากข้อความ: คั่นด้วย comma, เว้นวรรค หรือขึ้นบรรทัดใหม่
+const parseRecipients = (text: string): ShareRecipient[] =>
+  text
+    .split(/[\s,;]+/)
+    .map((value) => value.trim())
+    .filter(Boolean)
+    .map((value) => (/^\d+$/.test(value) ? { user_id: value } : { email: value }))
+
+export function BulkShareDialog({ open, onOpenChange, fileIds, fileNames, onComplete }: BulkShareDialogProps) {
+  const [recipientsInput, setRecipientsInput] = useState("")
+  const [role, setRole] = useState<ShareRole>("viewer")
+  const [expiresInSecs, setExpiresInSecs] = useState<number | undefined>(undefined)
+  const [isSubmitting, setIsSubmitting] = useState(false)
+  const [error, setError] = useState("")
+  const [result, setResult] = useState<BulkShareResponse | null>(null)
+
+  const recipients = parseRecipients(recipientsInput)
+
+  const handleSubmit = async (action: BulkShareAction) => {
+    if (recipients.length === 0) {
+      setError("Enter at least one email or User ID")
+      return
+    }
+
+    setIsSubmitting(true)
+    setError("")
+    setResult(null)
+
+    try {
+      const response = await bulkShare(action, fileIds, recipients, role, expiresInSecs)
+      setResult(response)
+      if (response.failed === 0) {
+        setRecipientsInput("")
+      }
+      onComplete?.()
+    } catch (err) {
+      console.error("Error sharing files:", err)
+      setError(err instanceof Error ? err.message : "Failed to share files")
+    } finally {
+      setIsSubmitting(false)
+    }
+  }
+
+  const handleOpenChange = (next: boolean) => {
+    if (isSubmitting) return
+    if (!next) {
+      setError("")
+      setResult(null)
+    }
+    onOpenChange(next)
+  }
+
+  return (
+    <Dialog open={open} onOpenChange={handleOpenChange}>
+      <DialogContent className="sm:max-w-lg">
+        <DialogHeader>
+          <DialogTitle>Share {fileIds.length} file{fileIds.length === 1 ? "" : "s"}</DialogTitle>
+          <DialogDescription>
+            Enter emails or User IDs separated by commas or new lines. Emails without an account yet get an invitation.
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="space-y-4 py-2">
+          <Textarea
+            placeholder={"alice@example.com, bob@example.com\n42"}
+            value={recipientsInput}
+            onChange={(e) => {
+              setRecipientsInput(e.target.value)
+              setError("")
+            }}
+            disabled={isSubmitting}
+            rows={4}
+          />
+
+          <div className="flex items-center justify-between gap-2">
+            <span className="text-sm text-muted-foreground">Share as</span>
+            <ShareRoleSelect value={role} onChange={setRole} disabled={isSubmitting} />
+          </div>
+
+          <div className="flex items-center justify-between gap-2">
+            <span className="text-sm text-muted-foreground">Access expires after</span>
+            <ShareExpirySelect value={expiresInSecs} onChange={setExpiresInSecs} disabled={isSubmitting} />
+          </div>
+
+          {error && (
+            <div className="flex items-start gap-2 p-3 bg-destructive/10 border border-destructive/20 rounded-lg">
+              <AlertCircle className="h-4 w-4 text-destructive mt-0.5 flex-shrink-0" />
+              <p className="text-sm text-destructive">{error}</p>
+            </div>
+          )}
+
+          {result && (
+            <div className="space-y-2">
+              <p className="text-sm font-medium">
+                {result.succeeded} succeeded, {result.failed} failed
+              </p>
+              <div className="border rounded-lg divide-y max-h-56 overflow-y-auto">
+                {result.results.map((r) => (
+                  <div key={`${r.file_id}-${r.user_id ?? r.email}`} className="flex items-start gap-2 px-3 py-2 text-sm">
+                    {!r.success ? (
+                      <XCircle className="h-4 w-4 text-destructive mt-0.5 flex-shrink-0" />
+                    ) : r.pending ? (
+                      <Mail className="h-4 w-4 text-muted-foreground mt-0.5 flex-shrink-0" />
+                    ) : (
+                      <CheckCircle2 className="h-4 w-4 text-green-600 mt-0.5 flex-shrink-0" />
+                    )}
+                    <div className="min-w-0">
+                      <p className="truncate">
+                        {fileNames[r.file_id] ?? r.file_id} → {r.email ?? `User ${r.user_id}`}
+                      </p>
+                      {r.error && <p className="text-xs text-destructive">{r.error.message}</p>}
+                      {r.success && r.pending && (
+                        <p className="text-xs text-muted-foreground">Invited, gets access when they sign up</p>
+                      )}
+                    </div>
+                  </div>
+                ))}
+              </div>
+            </div>
+          )}
+        </div>
+
+        <DialogFooter className="gap-2">
+          <Button
+            variant="outline"
+            onClick={() => handleSubmit("unshare")}
+            disabled={isSubmitting || recipients.length === 0}
+          >
+            Revoke access
+          </Button>
+          <Button onClick={() => handleSubmit("share")} disabled={isSubmitting || recipients.length === 0}>
+            {isSubmitting ? "Working..." : "Share"}
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  )
+}
@@ -22,12 +22,21 @@ import {
   AlertDialogHeader,
   AlertDialogTitle,
 } from "@/components/ui/alert-dialog"
-import { Share2, UserPlus, Trash2, AlertCircle } from "lucide-react"
+import { Share2, UserPlus, Trash2, AlertCircle, Mail, X } from "lucide-react"
+import { searchUsers, UserSearchResult } from "@/api/user"
+import { SHARE_ROLE_LABELS, type PendingInvitation, type ShareRole } from "@/api/cheatsheet"
+import { ShareRoleSelect } from "@/components/share-role-select"
+import { ShareExpirySelect } from "@/components/share-expiry-select"
+
+const SEARCH_DEBOUNCE_MS = 250
+const MIN_SEARCH_LENGTH = 2
 
 interface SharedUser {
   userId: string
   name?: string
   sharedAt?: string
+  role?: ShareRole
+  expiresAt?: string | null
 }
 
 interface ShareDialogProps {
@@ -35,24 +44,45 @@ interface ShareDialogProps {
   fileName: string
   sharedUsers: SharedUser[]
   currentUserId: string
-  onShare: (userId: string) => Promise<void>
-  onUnshare: (userId: string) => Promise<void>
+  /** Role of the current user; limits which roles they can grant and whether they can revoke */
+  currentUserRole?: ShareRole
+  onShare: (userId: string, role: ShareRole, expiresInSecs?: number) => Promise<void>
+  onUnshare?: (userId: string) => Promise<void>
+  invitations?: PendingInvitation[]
+  onInvite?: (email: string, role: ShareRole, expiresInSecs?: number) => Promise<void>
+  onCancelInvitation?: (email: string) => Promise<void>
 }
 
+const formatExpiry = (expiresAt: string) =>
+  new Date(expiresAt).toLocaleString('en-US', {
+    month: 'short',
+    day: 'numeric',
+    year: 'numeric',
+    hour: 'numeric',
+    minute: '2-digit'
+  })
+
 export function ShareDialog({
   fileName,
   sharedUsers,
   currentUserId,
+  currentUserRole = "owner",
   onShare,
-  onUnshare
+  onUnshare,
+  invitations = [],
+  onInvite,
+  onCancelInvitation
 }: ShareDialogProps) {
   const [open, setOpen] = useState(false)
   const [userIdInput, setUserIdInput] = useState("")
+  const [role, setRole] = useState<ShareRole>("viewer")
+  const [expiresInSecs, setExpiresInSecs] = useState<number | undefined>(undefined)
   const [isSharing, setIsSharing] = useState(false)
   const [isUnsharing, setIsUnsharing] = useState(false)
   const [error, setError] = useState("")
   const [localSharedUsers, setLocalSharedUsers] = useState<SharedUser[]>(sharedUsers)
   const [unshareConfirm, setUnshareConfirm] = useState<string | null>(null)
+  const [suggestions, setSuggestions] = useState<UserSearchResult[]>([])
 
   useEffect(() => {
     setLocalSharedUsers(sharedUsers)
@@ -72,17 +102,46 @@ export function ShareDialog({
     }
   }, [localSharedUsers, currentUserId])
 
-  const handleShare = async () => {
-    const targetUserId = userIdInput.trim()
+  useEffect(() => {
+    // ค้นหา user จากชื่อหรืออีเมล (ถ้าพิมพ์เป็นตัวเลขจะถือว่าเป็น User ID)
+    const query = userIdInput.trim()
+    if (query.length < MIN_SEARCH_LENGTH || /^\d+$/.test(query)) {
+      setSuggestions([])
+      return
+    }
+
+    let cancelled = false
+    const timer = setTimeout(async () => {
+      const users = await searchUsers(query)
+      if (!cancelled) {
+        setSuggestions(users.filter(u => u.id !== currentUserId))
+      }
+    }, SEARCH_DEBOUNCE_MS)
+
+    return () => {
+      cancelled = true
+      clearTimeout(timer)
+    }
+  }, [userIdInput, currentUserId])
+
+  const handleShare = async (userId?: string) => {
+    const targetUserId = (userId ?? userIdInput).trim()
     
     // Validation: ต้องเป็นตัวเลขเท่านั้น
     if (!targetUserId) {
-      setError("Please enter a User ID")
+      setError("Please enter a name, email or User ID")
       return
     }
     
     if (!/^\d+$/.test(targetUserId)) {
-      setError("User ID must contain only numbers")
+      if (suggestions.length === 1) {
+        return handleShare(suggestions[0].id)
+      }
+      // อีเมลที่ยังไม่มีบัญชีจะถูกบันทึกเป็นคำเชิญ
+      if (onInvite && targetUserId.includes("@")) {
+        return handleInvite(targetUserId)
+      }
+      setError("Pick a user from the list, or enter an email or numeric User ID")
       return
     }
     
@@ -100,8 +159,9 @@ export function ShareDialog({
     setError("")
 
     try {
-      await onShare(targetUserId)
+      await onShare(targetUserId, role, expiresInSecs)
       setUserIdInput("")
+      setSuggestions([])
       // รอ parent component update ผ่าน props
       // ถ้า user ไม่มีชื่อ useEffect ด้านบนจะจัดการให้
     } catch (error) {
@@ -119,12 +179,46 @@ export function ShareDialog({
     }
   }
 
+  const handleInvite = async (email: string) => {
+    if (!onInvite) return
+
+    if (invitations.some(i => i.email === email.toLowerCase())) {
+      setError("This email has already been invited")
+      return
+    }
+
+    setIsSharing(true)
+    setError("")
+
+    try {
+      await onInvite(email, role, expiresInSecs)
+      setUserIdInput("")
+      setSuggestions([])
+    } catch (error) {
+      console.error("Error inviting by email:", error)
+      setError(error instanceof Error ? error.message : "Failed to share file")
+    } finally {
+      setIsSharing(false)
+    }
+  }
+
+  const handleCancelInvitation = async (email: string) => {
+    if (!onCancelInvitation) return
+
+    try {
+      await onCancelInvitation(email)
+    } catch (error) {
+      console.error("Error cancelling invitation:", error)
+      setError(error instanceof Error ? error.message : "Failed to cancel invitation")
+    }
+  }
+
   const handleUnshareClick = (targetUserId: string) => {
     setUnshareConfirm(targetUserId)
   }
 
   const handleUnshareConfirm = async () => {
-    if (!unshareConfirm) return
+    if (!unshareConfirm || !onUnshare) return
     
     const targetId = unshareConfirm
     setIsUnsharing(true)
@@ -150,12 +244,8 @@ export function ShareDialog({
   }
 
   const handleInputChange = (e: React.ChangeEvent<HTMLInputElement>) => {
-    const value = e.target.value
-    // อนุญาตเฉพาะตัวเลข
-    if (value === "" || /^\d+$/.test(value)) {
-      setUserIdInput(value)
-      setError("")
-    }
+    setUserIdInput(e.target.value)
+    setError("")
   }
 
   const otherSharedUsers = localSharedUsers.filter(u => u.userId !== currentUserId)
@@ -177,14 +267,14 @@ export function ShareDialog({
           <DialogHeader>
             <DialogTitle>Share &quot;{fileName}&quot;</DialogTitle>
             <DialogDescription>
-              Enter the User ID (numbers only) of the person you want to share this file with
+              Search by name or email, or enter a User ID. Emails without an account yet get an invitation.
             </DialogDescription>
           </DialogHeader>
 
           <div className="space-y-4 py-4">
             <div className="flex gap-2">
               <Input
-                placeholder="Enter User ID (numbers only)"
+                placeholder="Name, email or User ID"
                 value={userIdInput}
                 onChange={handleInputChange}
                 onKeyDown={(e) => {
@@ -195,10 +285,15 @@ export function ShareDialog({
                 disabled={isSharing}
                 className="flex-1"
                 type="text"
-                inputMode="numeric"
+              />
+              <ShareRoleSelect
+                value={role}
+                onChange={setRole}
+                maxRole={currentUserRole}
+                disabled={isSharing}
               />
               <Button
-                onClick={handleShare}
+                onClick={() => handleShare()}
                 disabled={isSharing || !userIdInput.trim()}
                 size="icon"
               >
@@ -210,6 +305,33 @@ export function ShareDialog({
               </Button>
             </div>
 
+            <div className="flex items-center justify-between gap-2">
+              <span className="text-sm text-muted-foreground">Access expires after</span>
+              <ShareExpirySelect value={expiresInSecs} onChange={setExpiresInSecs} disabled={isSharing} />
+            </div>
+
+            {suggestions.length > 0 && (
+              <div className="border rounded-lg divide-y max-h-48 overflow-y-auto">
+                {suggestions.map((user) => {
+                  const alreadyShared = localSharedUsers.some(u => u.userId === user.id)
+                  return (
+                    <button
+                      key={user.id}
+                      type="button"
+                      onClick={() => handleShare(user.id)}
+                      disabled={isSharing || alreadyShared}
+                      className="w-full flex items-center justify-between px-3 py-2 text-left text-sm hover:bg-muted/70 disabled:opacity-50 disabled:cursor-not-allowed"
+                    >
+                      <span className="truncate">{user.name}</span>
+                      <span className="text-xs text-muted-foreground flex-shrink-0 ml-2">
+                        {alreadyShared ? "Already shared" : `ID ${user.id}`}
+                      </span>
+                    </button>
+                  )
+                })}
+              </div>
+            )}
+
             {error && (
               <div className="flex items-start gap-2 p-3 bg-destructive/10 border border-destructive/20 rounded-lg">
                 <AlertCircle className="h-4 w-4 text-destructive mt-0.5 flex-shrink-0" />
@@ -230,6 +352,14 @@ export function ShareDialog({
                         <p className="text-sm font-medium truncate">
                           {user.name || `User ${user.userId}`}
                         </p>
+                        {user.role && (
+                          <p className="text-xs text-muted-foreground">{SHARE_ROLE_LABELS[user.role]}</p>
+                        )}
+                        {user.expiresAt && (
+                          <p className="text-xs text-muted-foreground">
+                            Expires {formatExpiry(user.expiresAt)}
+                          </p>
+                        )}
                         {user.sharedAt && (
                           <p className="text-xs text-muted-foreground">
                             Shared {new Date(user.sharedAt).toLocaleDateString('en-US', {
@@ -240,22 +370,60 @@ export function ShareDialog({
                           </p>
                         )}
                       </div>
-                      <Button
-                        variant="ghost"
-                        size="sm"
-                        onClick={() => handleUnshareClick(user.userId)}
-                        disabled={isUnsharing}
-                        className="text-destructive hover:text-destructive hover:bg-destructive/10 flex-shrink-0"
-                      >
-                        <Trash2 className="h-4 w-4" />
-                      </Button>
+                      {onUnshare && user.role !== "owner" && (
+                        <Button
+                          variant="ghost"
+                          size="sm"
+                          onClick={() => handleUnshareClick(user.userId)}
+                          disabled={isUnsharing}
+                          className="text-destructive hover:text-destructive hover:bg-destructive/10 flex-shrink-0"
+                        >
+                          <Trash2 className="h-4 w-4" />
+                        </Button>
+                      )}
+                    </div>
+                  ))}
+                </div>
+              </div>
+            )}
+
+            {invitations.length > 0 && (
+              <div className="space-y-2">
+                <h4 className="text-sm font-medium">Pending invitations:</h4>
+                <div className="space-y-2 max-h-32 overflow-y-auto">
+                  {invitations.map((invitation) => (
+                    <div
+                      key={invitation.email}
+                      className="flex items-center justify-between p-3 border border-dashed rounded-lg"
+                    >
+                      <div className="flex items-center gap-2 flex-1 min-w-0 mr-2">
+                        <Mail className="h-4 w-4 text-muted-foreground flex-shrink-0" />
+                        <div className="min-w-0">
+                          <p className="text-sm truncate">{invitation.email}</p>
+                          <p className="text-xs text-muted-foreground">
+                            Gets {SHARE_ROLE_LABELS[invitation.role].toLowerCase()} access when they sign up
+                            {invitation.expires_at && `, until ${formatExpiry(invitation.expires_at)}`}
+                          </p>
+                        </div>
+                      </div>
+                      {onCancelInvitation && (
+                        <Button
+                          variant="ghost"
+                          size="sm"
+                          onClick={() => handleCancelInvitation(invitation.email)}
+                          className="flex-shrink-0"
+                          aria-label={`Cancel invitation for ${invitation.email}`}
+                        >
+                          <X className="h-4 w-4" />
+                        </Button>
+                      )}
                     </div>
                   ))}
                 </div>
               </div>
             )}
 
-            {otherSharedUsers.length === 0 && (
+            {otherSharedUsers.length === 0 && invitations.length === 0 && (
               <div className="text-center py-8 text-sm text-muted-foreground border rounded-lg bg-muted/20">
                 <Share2 className="h-8 w-8 mx-auto mb-2 opacity-50" />
                 <p>This file hasn&apos;t been shared with anyone yet</p>
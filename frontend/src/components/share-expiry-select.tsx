@@ -0,0 +1,40 @@
+"use client"
+
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select"
+import { SHARE_EXPIRY_OPTIONS } from "@/api/cheatsheet"
+
+interface ShareExpirySelectProps {
+  /** Seconds until the share expires; undefined for a share that never expires */
+  value?: number
+  onChange: (seconds?: number) => void
+  disabled?: boolean
+}
+
+export function ShareExpirySelect({ value, onChange, disabled }: ShareExpirySelectProps) {
+  const selected = SHARE_EXPIRY_OPTIONS.find((option) => option.seconds === value) ?? SHARE_EXPIRY_OPTIONS[0]
+
+  return (
+    <Select
+      value={selected.value}
+      onValueChange={(v) => onChange(SHARE_EXPIRY_OPTIONS.find((option) => option.value === v)?.seconds)}
+      disabled={disabled}
+    >
+      <SelectTrigger className="w-32" aria-label="Expires">
+        <SelectValue />
+      </SelectTrigger>
+      <SelectContent>
+        {SHARE_EXPIRY_OPTIONS.map((option) => (
+          <SelectItem key={option.value} value={option.value}>
+            {option.label}
+          </SelectItem>
+        ))}
+      </SelectContent>
+    </Select>
+  )
+}
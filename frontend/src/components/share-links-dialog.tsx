@@ -0,0 +1,237 @@
+"use client"
+
+import { useState, useEffect, useCallback } from "react"
+import { Button } from "@/components/ui/button"
+import { Input } from "@/components/ui/input"
+import { Badge } from "@/components/ui/badge"
+import {
+  Dialog,
+  DialogContent,
+  DialogHeader,
+  DialogTitle,
+  DialogDescription,
+  DialogFooter
+} from "@/components/ui/dialog"
+import { Link2, Trash2, AlertCircle, Copy, Check, Lock } from "lucide-react"
+import {
+  createShareLink,
+  getShareLinks,
+  revokeShareLink,
+  shareLinkUrl,
+  type ShareLink,
+} from "@/api/cheatsheet"
+import { ShareExpirySelect } from "@/components/share-expiry-select"
+
+const MIN_PASSWORD_LENGTH = 8
+
+interface ShareLinksDialogProps {
+  fileId: string
+  fileName: string
+  currentUserId: string
+  /** Co-owners can revoke links anyone created; others only their own */
+  canRevokeAll?: boolean
+}
+
+const formatDate = (value: string) =>
+  new Date(value).toLocaleString('en-US', {
+    month: 'short',
+    day: 'numeric',
+    year: 'numeric',
+    hour: 'numeric',
+    minute: '2-digit'
+  })
+
+export function ShareLinksDialog({
+  fileId,
+  fileName,
+  currentUserId,
+  canRevokeAll = false
+}: ShareLinksDialogProps) {
+  const [open, setOpen] = useState(false)
+  const [links, setLinks] = useState<ShareLink[]>([])
+  const [password, setPassword] = useState("")
+  const [expiresInSecs, setExpiresInSecs] = useState<number | undefined>(undefined)
+  const [isCreating, setIsCreating] = useState(false)
+  const [revokingId, setRevokingId] = useState<string | null>(null)
+  // token ถูกส่งกลับมาแค่ตอนสร้าง จึงแสดง URL ได้ครั้งเดียว
+  const [createdUrl, setCreatedUrl] = useState<string | null>(null)
+  const [copied, setCopied] = useState(false)
+  const [error, setError] = useState<string | null>(null)
+
+  const loadLinks = useCallback(async () => {
+    try {
+      setLinks(await getShareLinks(fileId))
+    } catch (err) {
+      setError(err instanceof Error ? err.message : "Failed to load links")
+    }
+  }, [fileId])
+
+  useEffect(() => {
+    if (open) {
+      loadLinks()
+    } else {
+      setCreatedUrl(null)
+      setCopied(false)
+      setPassword("")
+      setError(null)
+    }
+  }, [open, loadLinks])
+
+  const handleCreate = async () => {
+    if (password && password.length < MIN_PASSWORD_LENGTH) {
+      setError(`Password must be at least ${MIN_PASSWORD_LENGTH} characters`)
+      return
+    }
+
+    setIsCreating(true)
+    setError(null)
+    try {
+      const { token } = await createShareLink(fileId, { password, expiresInSecs })
+      setCreatedUrl(shareLinkUrl(token))
+      setCopied(false)
+      setPassword("")
+      await loadLinks()
+    } catch (err) {
+      setError(err instanceof Error ? err.message : "Failed to create link")
+    } finally {
+      setIsCreating(false)
+    }
+  }
+
+  const handleCopy = async () => {
+    if (!createdUrl) return
+    await navigator.clipboard.writeText(createdUrl)
+    setCopied(true)
+  }
+
+  const handleRevoke = async (linkId: string) => {
+    setRevokingId(linkId)
+    setError(null)
+    try {
+      await revokeShareLink(fileId, linkId)
+      setLinks((prev) => prev.filter((link) => link.id !== linkId))
+    } catch (err) {
+      setError(err instanceof Error ? err.message : "Failed to revoke link")
+    } finally {
+      setRevokingId(null)
+    }
+  }
+
+  return (
+    <>
+      <Button variant="outline" onClick={() => setOpen(true)}>
+        <Link2 className="h-4 w-4 mr-2" />
+        Links
+        {links.length > 0 && (
+          <Badge variant="secondary" className="ml-2">
+            {links.length}
+          </Badge>
+        )}
+      </Button>
+
+      <Dialog open={open} onOpenChange={setOpen}>
+        <DialogContent className="sm:max-w-md">
+          <DialogHeader>
+            <DialogTitle>Public links</DialogTitle>
+            <DialogDescription>
+              Anyone with a link can download &quot;{fileName}&quot; without signing in
+            </DialogDescription>
+          </DialogHeader>
+
+          <div className="space-y-4 py-4">
+            <div className="flex gap-2">
+              <Input
+                type="password"
+                placeholder="Password (optional)"
+                value={password}
+                onChange={(e) => setPassword(e.target.value)}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter') {
+                    handleCreate()
+                  }
+                }}
+                disabled={isCreating}
+                className="flex-1"
+              />
+              <Button onClick={handleCreate} disabled={isCreating} size="icon">
+                {isCreating ? (
+                  <div className="h-4 w-4 border-2 border-white border-t-transparent rounded-full animate-spin" />
+                ) : (
+                  <Link2 className="h-4 w-4" />
+                )}
+              </Button>
+            </div>
+
+            <div className="flex items-center justify-between gap-2">
+              <span className="text-sm text-muted-foreground">Link expires after</span>
+              <ShareExpirySelect value={expiresInSecs} onChange={setExpiresInSecs} disabled={isCreating} />
+            </div>
+
+            {createdUrl && (
+              <div className="space-y-2">
+                <p className="text-xs text-muted-foreground">
+                  Copy this link now; it will not be shown again.
+                </p>
+                <div className="flex gap-2">
+                  <Input value={createdUrl} readOnly className="flex-1 font-mono text-xs" />
+                  <Button variant="outline" size="icon" onClick={handleCopy}>
+                    {copied ? <Check className="h-4 w-4" /> : <Copy className="h-4 w-4" />}
+                  </Button>
+                </div>
+              </div>
+            )}
+
+            {error && (
+              <div className="flex items-start gap-2 p-3 bg-destructive/10 border border-destructive/20 rounded-lg">
+                <AlertCircle className="h-4 w-4 text-destructive mt-0.5 flex-shrink-0" />
+                <p className="text-sm text-destructive">{error}</p>
+              </div>
+            )}
+
+            {links.length > 0 && (
+              <div className="space-y-2">
+                <h4 className="text-sm font-medium">Active links:</h4>
+                <div className="space-y-2 max-h-48 overflow-y-auto">
+                  {links.map((link) => (
+                    <div
+                      key={link.id}
+                      className="flex items-center justify-between p-3 border rounded-lg bg-muted/50 hover:bg-muted/70 transition-colors"
+                    >
+                      <div className="flex-1 min-w-0 mr-2">
+                        <p className="text-sm font-medium truncate flex items-center gap-1">
+                          {link.has_password && <Lock className="h-3 w-3 flex-shrink-0" />}
+                          Created {formatDate(link.created_at)}
+                          {link.created_by === currentUserId && " by you"}
+                        </p>
+                        <p className="text-xs text-muted-foreground">
+                          {link.expires_at ? `Expires ${formatDate(link.expires_at)}` : "Never expires"}
+                        </p>
+                      </div>
+                      {(canRevokeAll || link.created_by === currentUserId) && (
+                        <Button
+                          variant="ghost"
+                          size="sm"
+                          onClick={() => handleRevoke(link.id)}
+                          disabled={revokingId === link.id}
+                          className="text-destructive hover:text-destructive hover:bg-destructive/10 flex-shrink-0"
+                        >
+                          <Trash2 className="h-4 w-4" />
+                        </Button>
+                      )}
+                    </div>
+                  ))}
+                </div>
+              </div>
+            )}
+          </div>
+
+          <DialogFooter>
+            <Button variant="outline" onClick={() => setOpen(false)}>
+              Close
+            </Button>
+          </DialogFooter>
+        </DialogContent>
+      </Dialog>
+    </>
+  )
+}
@@ -0,0 +1,38 @@
+"use client"
+
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select"
+import { SHARE_ROLES, SHARE_ROLE_LABELS, roleAtLeast, type ShareRole } from "@/api/cheatsheet"
+
+interface ShareRoleSelectProps {
+  value: ShareRole
+  onChange: (role: ShareRole) => void
+  /** Highest role the current user may grant */
+  maxRole?: ShareRole
+  disabled?: boolean
+}
+
+export function ShareRoleSelect({ value, onChange, maxRole = "co_owner", disabled }: ShareRoleSelectProps) {
+  // owner ให้กันไม่ได้ และให้ได้ไม่เกินสิทธิ์ของตัวเอง
+  const roles = SHARE_ROLES.filter((role) => role !== "owner" && roleAtLeast(maxRole, role))
+
+  return (
+    <Select value={value} onValueChange={(role) => onChange(role as ShareRole)} disabled={disabled}>
+      <SelectTrigger className="w-36" aria-label="Role">
+        <SelectValue />
+      </SelectTrigger>
+      <SelectContent>
+        {roles.map((role) => (
+          <SelectItem key={role} value={role}>
+            {SHARE_ROLE_LABELS[role]}
+          </SelectItem>
+        ))}
+      </SelectContent>
+    </Select>
+  )
+}
@@ -1,9 +1,19 @@
 use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+use axum::http::Method;
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub app: AppConfig,
     pub server: ServerConfig,
+    pub auth: AuthConfig,
+    pub generation: GenerationConfig,
+    pub share_links: ShareLinkConfig,
+    pub rate_limit: RateLimitConfig,
+    pub telemetry: TelemetryConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -15,8 +25,94 @@ pub struct AppConfig {
 pub struct ServerConfig {
     pub gateway_host: String,
     pub gateway_port: u16,
+    /// Serves `/metrics`, apart from the public listener so only the internal network can
+    /// scrape it
+    pub metrics_addr: String,
     pub user_grpc_url: String,
+    pub user_grpc_connect_timeout_secs: u64,
     pub cheatsheet_api_url: String,
+    pub cheatsheet_client: CheatsheetClientConfig,
+    /// Shared with the cheatsheet service, which only accepts requests signed with it
+    pub internal_signing_secret: String,
+    /// How long readiness fails before the listener stops, so load balancers stop routing here
+    pub shutdown_pre_stop_delay_secs: u64,
+    pub shutdown_timeout_secs: u64,
+}
+
+/// Timeouts, retry and circuit breaker policy for calls to the cheatsheet API
+#[derive(Debug, Clone)]
+pub struct CheatsheetClientConfig {
+    pub connect_timeout_ms: u64,
+    pub request_timeout_secs: u64,
+    /// Generation waits on the worker pipeline, so it gets a much longer budget
+    pub generate_timeout_secs: u64,
+    /// Extra attempts for idempotent calls; 0 disables retries
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    /// Consecutive failures that open the circuit; 0 disables the breaker
+    pub breaker_failure_threshold: u32,
+    pub breaker_open_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    pub token_cache_ttl_secs: u64,
+    pub token_cache_max_entries: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerationConfig {
+    /// Jobs allowed to wait on the cheatsheet API at the same time; the rest stay queued
+    pub max_concurrent: usize,
+    /// How long finished jobs can still be polled
+    pub job_ttl_secs: u64,
+    /// How often a running job asks the cheatsheet API which stage it has reached
+    pub progress_poll_ms: u64,
+    /// Generations each user may submit per UTC day; 0 disables the quota
+    pub daily_quota: u32,
+    /// Queued or running jobs each user may have at once; 0 disables the cap
+    pub max_active_per_user: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShareLinkConfig {
+    /// Lifetime of the download URL a public link resolves to; capped at 600 by the
+    /// cheatsheet API
+    pub url_ttl_secs: u64,
+}
+
+/// Per-client request limits. Authenticated routes are limited per user, public ones per
+/// client IP.
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Applies to every route without an entry in `routes`
+    pub default: RateLimit,
+    pub routes: Vec<RouteRateLimit>,
+    /// Take the client IP from `X-Forwarded-For`; only safe behind a proxy that sets it
+    pub trust_forwarded_for: bool,
+}
+
+/// `requests` per `window_secs`, written as `requests/window_secs`, e.g. `10/60`
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub window_secs: u64,
+}
+
+/// Limit for one route, written as `METHOD /path=requests/window_secs` where the path is
+/// the route template, e.g. `POST /api/cheatsheet/generate=5/60`
+#[derive(Debug, Clone)]
+pub struct RouteRateLimit {
+    pub method: Method,
+    pub path: String,
+    pub limit: RateLimit,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    pub service_name: String,
+    pub otlp_endpoint: Option<String>,
 }
 
 impl Config {
@@ -24,6 +120,11 @@ impl Config {
         Ok(Self {
             app: AppConfig::from_env()?,
             server: ServerConfig::from_env()?,
+            auth: AuthConfig::from_env()?,
+            generation: GenerationConfig::from_env()?,
+            share_links: ShareLinkConfig::from_env()?,
+            rate_limit: RateLimitConfig::from_env()?,
+            telemetry: TelemetryConfig::from_env()?,
         })
     }
 }
@@ -45,10 +146,180 @@ impl ServerConfig {
             gateway_port: env::var("GATEWAY_PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()?,
+            metrics_addr: env::var("METRICS_ADDR").unwrap_or_else(|_| "127.0.0.1:9101".to_string()),
             user_grpc_url: env::var("USER_GRPC_URL")
                 .unwrap_or_else(|_| "http://127.0.0.1:50051".to_string()),
+            user_grpc_connect_timeout_secs: env::var("USER_GRPC_CONNECT_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
             cheatsheet_api_url: env::var("CHEATSHEET_API_URL")
                 .unwrap_or_else(|_| "http://127.0.0.1:3002".to_string()),
+            cheatsheet_client: CheatsheetClientConfig::from_env()?,
+            internal_signing_secret: env::var("INTERNAL_SIGNING_SECRET")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .context("INTERNAL_SIGNING_SECRET must be set")?,
+            shutdown_pre_stop_delay_secs: env::var("SHUTDOWN_PRE_STOP_DELAY_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
+            shutdown_timeout_secs: env::var("SHUTDOWN_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+        })
+    }
+}
+
+impl CheatsheetClientConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            connect_timeout_ms: env::var("CHEATSHEET_CONNECT_TIMEOUT_MS")
+                .unwrap_or_else(|_| "2000".to_string())
+                .parse()?,
+            request_timeout_secs: env::var("CHEATSHEET_REQUEST_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()?,
+            generate_timeout_secs: env::var("CHEATSHEET_GENERATE_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "120".to_string())
+                .parse()?,
+            max_retries: env::var("CHEATSHEET_MAX_RETRIES")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()?,
+            retry_base_delay_ms: env::var("CHEATSHEET_RETRY_BASE_DELAY_MS")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()?,
+            breaker_failure_threshold: env::var("CHEATSHEET_BREAKER_FAILURE_THRESHOLD")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()?,
+            breaker_open_secs: env::var("CHEATSHEET_BREAKER_OPEN_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+        })
+    }
+}
+
+impl AuthConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            token_cache_ttl_secs: env::var("TOKEN_CACHE_TTL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            token_cache_max_entries: env::var("TOKEN_CACHE_MAX_ENTRIES")
+                .unwrap_or_else(|_| "10000".to_string())
+                .parse()?,
+        })
+    }
+}
+
+impl GenerationConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            max_concurrent: env::var("GENERATION_MAX_CONCURRENT")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()?,
+            job_ttl_secs: env::var("GENERATION_JOB_TTL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()?,
+            progress_poll_ms: env::var("GENERATION_PROGRESS_POLL_MS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()?,
+            daily_quota: env::var("GENERATION_DAILY_QUOTA")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()?,
+            max_active_per_user: env::var("GENERATION_MAX_ACTIVE_PER_USER")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()?,
+        })
+    }
+}
+
+impl ShareLinkConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            url_ttl_secs: env::var("SHARE_LINK_URL_TTL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()?,
+        })
+    }
+}
+
+impl RateLimitConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            enabled: env::var("RATE_LIMIT_ENABLED")
+                .unwrap_or_else(|_| "true".to_string())
+                .parse()?,
+            default: env::var("RATE_LIMIT_DEFAULT")
+                .unwrap_or_else(|_| "300/60".to_string())
+                .parse()?,
+            routes: env::var("RATE_LIMIT_ROUTES")
+                .unwrap_or_else(|_| {
+                    "POST /api/cheatsheet/generate=5/60,POST /api/cheatsheet/share/bulk=10/60,POST /api/cheatsheet/links/{token}=20/60,POST /api/user/google/callback=10/60"
+                        .to_string()
+                })
+                .split(',')
+                .map(str::trim)
+                .filter(|route| !route.is_empty())
+                .map(str::parse)
+                .collect::<anyhow::Result<_>>()?,
+            trust_forwarded_for: env::var("RATE_LIMIT_TRUST_FORWARDED_FOR")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+        })
+    }
+}
+
+impl RateLimit {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+}
+
+impl FromStr for RateLimit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (requests, window_secs) = s
+            .split_once('/')
+            .with_context(|| format!("rate limit `{s}` must look like requests/window_secs"))?;
+        let limit = Self {
+            requests: requests.trim().parse()?,
+            window_secs: window_secs.trim().parse()?,
+        };
+        anyhow::ensure!(
+            limit.window_secs > 0,
+            "rate limit `{s}` has an empty window"
+        );
+        Ok(limit)
+    }
+}
+
+impl FromStr for RouteRateLimit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (route, limit) = s
+            .split_once('=')
+            .with_context(|| format!("route limit `{s}` must look like METHOD /path=limit"))?;
+        let (method, path) = route
+            .trim()
+            .split_once(' ')
+            .with_context(|| format!("route limit `{s}` is missing a method or path"))?;
+        Ok(Self {
+            method: method.trim().to_uppercase().parse()?,
+            path: path.trim().to_string(),
+            limit: limit.parse()?,
+        })
+    }
+}
+
+impl TelemetryConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            service_name: env::var("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|_| "openexam-gateway".to_string()),
+            otlp_endpoint: env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .ok()
+                .filter(|v| !v.is_empty()),
         })
     }
 }
@@ -6,36 +6,78 @@ use utoipa::{
 #[derive(utoipa::OpenApi)]
 #[openapi(
     paths(
-        // crate::health_check,
+        crate::handlers::health::healthz,
+        crate::handlers::health::readyz,
         crate::handlers::user::get_google_login_url,
         crate::handlers::user::login,
         crate::handlers::user::validate_token,
-        crate::handlers::user::get_all_users,
+        crate::handlers::user::refresh_token,
+        crate::handlers::user::logout,
+        crate::handlers::user::search_users,
         crate::handlers::cheatsheet::get_presigned_upload_url,
         crate::handlers::cheatsheet::get_presigned_get_url,
         crate::handlers::cheatsheet::remove,
         crate::handlers::cheatsheet::get_all_files,
         crate::handlers::cheatsheet::get_file,
         crate::handlers::cheatsheet::share,
+        crate::handlers::cheatsheet::bulk_share,
         crate::handlers::cheatsheet::unshare,
+        crate::handlers::cheatsheet::create_share_link,
+        crate::handlers::cheatsheet::get_share_links,
+        crate::handlers::cheatsheet::revoke_share_link,
+        crate::handlers::cheatsheet::resolve_share_link,
         crate::handlers::cheatsheet::generate,
+        crate::handlers::cheatsheet::get_generation,
+        crate::handlers::cheatsheet::generation_events,
+        crate::handlers::cheatsheet::cancel_generation,
     ),
     components(schemas(
+        crate::dtos::ApiError,
+        crate::dtos::ErrorCode,
+        crate::dtos::FieldError,
+        crate::dtos::HealthResponse,
+        crate::dtos::ReadinessResponse,
+        crate::dtos::DependencyCheck,
         crate::dtos::ValidateTokenRequest,
         crate::dtos::ValidateTokenResponse,
         crate::dtos::LoginRequest,
         crate::dtos::LoginResponse,
+        crate::dtos::RefreshTokenRequest,
+        crate::dtos::RefreshTokenResponse,
+        crate::dtos::LogoutRequest,
+        crate::dtos::LogoutResponse,
         crate::dtos::GetPresignedUploadUrlResponse,
         crate::dtos::GetPresignedGetUrlResponse,
         crate::dtos::RemoveFileQuery,
+        crate::dtos::ListFilesQuery,
+        crate::dtos::FileType,
+        crate::dtos::FileOwnership,
+        crate::dtos::FileSort,
+        crate::dtos::SortOrder,
         crate::dtos::GetFileResponse,
+        crate::dtos::PendingInvitation,
+        crate::dtos::ShareRole,
         crate::dtos::ShareRequest,
         crate::dtos::ShareResponse,
         crate::dtos::UnshareRequest,
         crate::dtos::UnshareResponse,
+        crate::dtos::BulkShareAction,
+        crate::dtos::ShareRecipient,
+        crate::dtos::BulkShareRequest,
+        crate::dtos::BulkShareResult,
+        crate::dtos::BulkShareResponse,
+        crate::dtos::ShareLink,
+        crate::dtos::CreateShareLinkRequest,
+        crate::dtos::CreateShareLinkResponse,
+        crate::dtos::GetShareLinksResponse,
+        crate::dtos::RevokeShareLinkResponse,
+        crate::dtos::ResolveShareLinkRequest,
+        crate::dtos::ResolveShareLinkResponse,
         crate::dtos::GenerateRequest,
         crate::dtos::GenerateResponse,
-        crate::dtos::GetAllUsersResponse,
+        crate::dtos::GenerationStatus,
+        crate::dtos::GenerationStage,
+        crate::dtos::SearchUsersResponse,
         crate::dtos::UserProfile,
     )),
     info(
@@ -48,6 +90,7 @@ use utoipa::{
         (url = "https://openexam.bookpanda.dev", description = "production server")
     ),
     tags(
+        (name = "Health"),
         (name = "User"),
         (name = "Cheatsheet")
     )
@@ -3,6 +3,8 @@ use core::fmt;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::dtos::ApiError;
+
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct GetPresignedUploadUrlResponse {
     pub expires_in: String,
@@ -26,7 +28,7 @@ pub struct PresignGetQuery {
     pub key: String,
 }
 
-#[derive(Deserialize, Serialize, ToSchema)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum FileType {
     Slides,
@@ -47,6 +49,9 @@ impl fmt::Display for FileType {
 pub struct RemoveFileQuery {
     pub file_type: FileType,
     pub file: String,
+    /// Id of the file, to remove a file another user owns as its co-owner.
+    /// Without it, `file` names one of the caller's own files.
+    pub file_id: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
@@ -58,6 +63,63 @@ pub struct File {
     pub created_at: String,
     pub name: String,
     pub key: String,
+    /// When the caller's time-limited share of this file ends, RFC 3339
+    #[serde(rename = "expiresAt", default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+/// What a user may do with a file they can see. Each role includes the ones before it.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Default,
+    Deserialize,
+    Serialize,
+    ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareRole {
+    /// Read and download the file
+    #[default]
+    Viewer,
+    /// Generate cheatsheets from it
+    Editor,
+    /// Share it with others, up to their own role
+    Resharer,
+    /// Unshare and remove it
+    CoOwner,
+    /// The file's creator; never granted by a share
+    Owner,
+}
+
+impl ShareRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShareRole::Viewer => "viewer",
+            ShareRole::Editor => "editor",
+            ShareRole::Resharer => "resharer",
+            ShareRole::CoOwner => "co_owner",
+            ShareRole::Owner => "owner",
+        }
+    }
+
+    /// Reads a role stored by the cheatsheet or user service. Shares made before roles
+    /// existed have none and count as viewer.
+    pub fn parse(role: &str) -> Self {
+        match role {
+            "editor" => ShareRole::Editor,
+            "resharer" => ShareRole::Resharer,
+            "co_owner" => ShareRole::CoOwner,
+            "owner" => ShareRole::Owner,
+            _ => ShareRole::Viewer,
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
@@ -65,33 +127,119 @@ pub struct Share {
     #[serde(rename = "userId")]
     pub user_id: String,
     pub name: String,
+    pub role: ShareRole,
+    /// When this time-limited share ends, RFC 3339
+    #[serde(rename = "expiresAt", default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+/// Whose files to list: the caller's own, or files other users shared with them
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FileOwnership {
+    Mine,
+    Shared,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileSort {
+    CreatedAt,
+    Name,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Filters, sort and page for the file listing. Without `limit` every matching file is
+/// returned in one page; without `sort` files come in storage order.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ListFilesQuery {
+    #[serde(rename = "type")]
+    pub file_type: Option<FileType>,
+    pub ownership: Option<FileOwnership>,
+    /// Case-insensitive substring of the file name
+    pub name: Option<String>,
+    /// RFC 3339 timestamp or `YYYY-MM-DD`, inclusive
+    pub created_after: Option<String>,
+    /// RFC 3339 timestamp or `YYYY-MM-DD`, exclusive
+    pub created_before: Option<String>,
+    pub sort: Option<FileSort>,
+    /// Only applies together with `sort`
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<usize>,
+    /// `next_cursor` of the previous page
+    pub cursor: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct GetAllFilesResponse {
     pub files: Vec<File>,
+    /// Number of files matching the filters, across all pages
+    pub total: usize,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// A share with an email that has no account yet; granted when the email first logs in
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct PendingInvitation {
+    pub email: String,
+    /// RFC 3339
+    pub invited_at: String,
+    pub role: ShareRole,
+    /// When the share granted on sign-up ends, RFC 3339
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct GetFileResponse {
     pub file: File,
     pub shares: Vec<Share>,
+    /// Pending invitations; only listed for the owner and co-owners
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invitations: Vec<PendingInvitation>,
 }
 
+/// Exactly one of `user_id` and `email` must be set
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct ShareRequest {
-    pub user_id: String,
+    pub user_id: Option<String>,
+    /// Shares with the account using this email, or invites it if there is none yet
+    pub email: Option<String>,
     pub file_id: String,
+    /// Role to grant, at most the caller's own; `owner` cannot be granted
+    #[serde(default)]
+    pub role: ShareRole,
+    /// RFC 3339 time the share stops granting access. Without this or `expires_in_secs`
+    /// the share is permanent.
+    pub expires_at: Option<String>,
+    /// Seconds from now until the share stops granting access; alternative to `expires_at`
+    pub expires_in_secs: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct ShareResponse {
     pub shared: bool,
+    /// True when the email has no account yet; the file is shared once it signs up
+    #[serde(default)]
+    pub pending: bool,
 }
 
+/// Exactly one of `user_id` and `email` must be set
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct UnshareRequest {
-    pub user_id: String,
+    pub user_id: Option<String>,
+    /// Cancels the pending invitation of this email
+    pub email: Option<String>,
     pub file_id: String,
 }
 
@@ -100,13 +248,187 @@ pub struct UnshareResponse {
     pub unshared: bool,
 }
 
+/// A public link that opens a file without an account. The token in the link is only
+/// returned when the link is created.
 #[derive(Deserialize, Serialize, ToSchema)]
-pub struct GenerateRequest {
+pub struct ShareLink {
+    pub id: String,
+    pub file_id: String,
+    pub created_by: String,
+    /// RFC 3339
+    pub created_at: String,
+    /// RFC 3339; absent for a link that never expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// Whoever opens the link has to enter a password
+    pub has_password: bool,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct CreateShareLinkRequest {
+    /// Protects the link with a password of at least 8 characters
+    pub password: Option<String>,
+    /// RFC 3339 time the link stops working. Without this or `expires_in_secs` the link
+    /// works until it is revoked.
+    pub expires_at: Option<String>,
+    /// Seconds from now until the link stops working; alternative to `expires_at`
+    pub expires_in_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct CreateShareLinkResponse {
+    pub link: ShareLink,
+    /// Secret part of the link, opened with `POST /api/cheatsheet/links/{token}`.
+    /// It is not stored and cannot be retrieved again.
+    pub token: String,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct GetShareLinksResponse {
+    pub links: Vec<ShareLink>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct RevokeShareLinkResponse {
+    pub revoked: bool,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ResolveShareLinkRequest {
+    /// Required when the link has a password
+    pub password: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ResolveShareLinkResponse {
+    pub name: String,
+    /// Presigned download URL
+    pub url: String,
+    pub expires_in: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkShareAction {
+    Share,
+    Unshare,
+}
+
+/// Someone to share with, by user id or by email; exactly one must be set
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, ToSchema)]
+pub struct ShareRecipient {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+/// Applies `action` to every pair of file and recipient
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct BulkShareRequest {
+    pub action: BulkShareAction,
     pub file_ids: Vec<String>,
+    pub recipients: Vec<ShareRecipient>,
+    /// Role to grant when sharing; ignored when unsharing
+    #[serde(default)]
+    pub role: ShareRole,
+    /// Makes the shares time-limited, as in `ShareRequest`; ignored when unsharing
+    pub expires_at: Option<String>,
+    /// Alternative to `expires_at`, as in `ShareRequest`
+    pub expires_in_secs: Option<u64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BulkShareResult {
+    pub file_id: String,
+    #[serde(flatten)]
+    pub recipient: ShareRecipient,
+    pub success: bool,
+    /// The recipient has no account yet and was invited instead
+    pub pending: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BulkShareResponse {
+    /// One entry per file and recipient pair, files first, in request order
+    pub results: Vec<BulkShareResult>,
+    pub succeeded: usize,
+    pub failed: usize,
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
+pub struct GenerateRequest {
+    pub file_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl GenerationStatus {
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            GenerationStatus::Succeeded | GenerationStatus::Failed | GenerationStatus::Cancelled
+        )
+    }
+}
+
+/// Finer-grained progress of a generation job, as reported by the cheatsheet backend.
+/// The last three are terminal and mirror the job's status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationStage {
+    Queued,
+    DownloadingSources,
+    Summarising,
+    Rendering,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl GenerationStage {
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            GenerationStage::Done | GenerationStage::Failed | GenerationStage::Cancelled
+        )
+    }
+
+    /// Name of the server-sent event announcing this stage
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GenerationStage::Queued => "queued",
+            GenerationStage::DownloadingSources => "downloading_sources",
+            GenerationStage::Summarising => "summarising",
+            GenerationStage::Rendering => "rendering",
+            GenerationStage::Done => "done",
+            GenerationStage::Failed => "failed",
+            GenerationStage::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A cheatsheet generation job. `file_id` and `key` are set once it has succeeded,
+/// `error` once it has failed.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct GenerateResponse {
-    pub file_id: String,
-    pub key: String,
+    pub job_id: String,
+    pub status: GenerationStatus,
+    pub stage: GenerationStage,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
 }
@@ -0,0 +1,22 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// `ready` when every dependency check passed, otherwise `not_ready`
+    pub status: String,
+    pub checks: Vec<DependencyCheck>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DependencyCheck {
+    pub name: String,
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -1,5 +1,9 @@
 pub mod cheatsheet;
+pub mod error;
+pub mod health;
 pub mod user;
 
 pub use cheatsheet::*;
+pub use error::*;
+pub use health::*;
 pub use user::*;
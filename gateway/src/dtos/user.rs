@@ -12,6 +12,7 @@ pub struct LoginResponse {
     pub email: String,
     pub name: String,
     pub token: String,
+    pub refresh_token: String,
 }
 
 #[derive(Deserialize, Serialize, ToSchema)]
@@ -19,20 +20,56 @@ pub struct ValidateTokenRequest {
     pub token: String,
 }
 
-#[derive(Deserialize, Serialize, ToSchema)]
+#[derive(Clone, Deserialize, Serialize, ToSchema)]
 pub struct ValidateTokenResponse {
     pub id: String,
     pub email: String,
     pub name: String,
 }
 
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct RefreshTokenResponse {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct LogoutRequest {
+    pub token: String,
+    /// Revoke every session of the user instead of only the current one
+    #[serde(default)]
+    pub all_sessions: bool,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct LogoutResponse {
+    pub success: bool,
+}
+
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct UserProfile {
     pub id: String,
     pub name: String,
 }
 
+/// User search for typeahead. Every word of `q` must prefix a word of the user's name or
+/// email, or `q` must prefix their email.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SearchUsersQuery {
+    pub q: String,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
 #[derive(Deserialize, Serialize, ToSchema)]
-pub struct GetAllUsersResponse {
+pub struct SearchUsersResponse {
     pub users: Vec<UserProfile>,
+    /// Pass as `cursor` to get the next page; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
@@ -0,0 +1,25 @@
+use axum::extract::{FromRequest, Request};
+use serde::de::DeserializeOwned;
+
+use crate::dtos::ApiError;
+
+/// `axum::Json` that rejects malformed bodies with an [`ApiError`]
+pub struct Json<T>(pub T);
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match axum::Json::<T>::from_request(req, state).await {
+            Ok(axum::Json(value)) => Ok(Json(value)),
+            Err(rejection) => Err(ApiError::from_status(
+                rejection.status().as_u16(),
+                &rejection.body_text(),
+            )),
+        }
+    }
+}
@@ -1,3 +1,9 @@
+pub mod json;
+pub mod path;
+pub mod query;
 pub mod user_id;
 
+pub use json::Json;
+pub use path::Path;
+pub use query::Query;
 pub use user_id::UserId;
@@ -1,7 +1,6 @@
-use axum::{
-    extract::FromRequestParts,
-    http::{StatusCode, request::Parts},
-};
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use crate::dtos::ApiError;
 
 /// Extractor for user ID from X-User-Id header
 pub struct UserId(pub String);
@@ -10,7 +9,7 @@ impl<S> FromRequestParts<S> for UserId
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = ApiError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         let user_id = parts
@@ -21,9 +20,8 @@ where
             .to_string();
 
         if user_id.is_empty() {
-            return Err((
-                StatusCode::UNAUTHORIZED,
-                "Missing or invalid X-User-Id header".to_string(),
+            return Err(ApiError::unauthenticated(
+                "Missing or invalid X-User-Id header",
             ));
         }
 
@@ -1,9 +1,16 @@
-use crate::dtos::{self, PresignGetQuery, PresignUploadQuery, RemoveFileQuery};
-use crate::extractors::UserId;
-use crate::services::cheatsheet::CheatsheetService;
-use axum::Json;
-use axum::extract::{Path, Query, State};
+use crate::dtos::{
+    self, ApiError, FieldError, ListFilesQuery, PresignGetQuery, PresignUploadQuery,
+    RemoveFileQuery,
+};
+use crate::extractors::{Json, Path, Query, UserId};
+use crate::services::cheatsheet::{CheatsheetService, MAX_GENERATE_FILES};
+use crate::services::response::ApiResponse;
+use axum::extract::State;
 use axum::response::IntoResponse;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::{Stream, stream};
+use hyper::StatusCode;
+use tokio::sync::watch;
 
 #[derive(Debug, Clone)]
 pub struct CheatsheetHandler {
@@ -26,8 +33,9 @@ impl CheatsheetHandler {
     ),
     responses(
         (status = 200, description = "Success", body = dtos::GetPresignedUploadUrlResponse),
-        (status = 400, description = "Bad request"),
-        (status = 500, description = "Internal server error"),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn get_presigned_upload_url(
@@ -46,14 +54,17 @@ pub async fn get_presigned_upload_url(
     get,
     path = "/api/cheatsheet/presigned",
     tag = "Cheatsheet",
-    description = "Generate a presigned URL for downloading a file from S3. The URL expires after a set time.",
+    description = "Generate a presigned URL for downloading a file from S3. The URL expires after a set time. \
+        The key must belong to a file the user owns or has an active share of.",
     params(
         ("key" = String, Query, description = "Full key in S3 e.g. slides/1/4e8d92_test.pdf"),
     ),
     responses(
         (status = 200, description = "Success", body = dtos::GetPresignedGetUrlResponse),
-        (status = 400, description = "Bad request"),
-        (status = 500, description = "Internal server error"),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 403, description = "The key is not a file the user owns or has an active share of", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn get_presigned_get_url(
@@ -72,15 +83,19 @@ pub async fn get_presigned_get_url(
     delete,
     path = "/api/cheatsheet/files",
     tag = "Cheatsheet",
-    description = "Delete a file from S3 and remove all associated shares from DynamoDB.",
+    description = "Delete a file from S3 and remove all associated shares from DynamoDB. \
+        Co-owners delete a file another user owns by also passing its `file_id`.",
     params(
         ("file_type" = String, Query, description = "File type (`slides` or `cheatsheets`)"),
         ("file" = String, Query, description = "Filename to delete e.g. 82a354_test.pdf"),
+        ("file_id" = Option<String>, Query, description = "Id of a file owned by another user, which the caller co-owns"),
     ),
     responses(
         (status = 200, description = "File deleted successfully"),
-        (status = 404, description = "File not found"),
-        (status = 500, description = "Internal server error"),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 403, description = "Not the owner or a co-owner of the file, or no such file exists", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn remove(
@@ -90,7 +105,12 @@ pub async fn remove(
 ) -> impl IntoResponse {
     handler
         .cheatsheet_service
-        .remove_file(query.file_type.to_string(), query.file, user_id)
+        .remove_file(
+            query.file_type.to_string(),
+            query.file,
+            query.file_id,
+            user_id,
+        )
         .await
         .into_axum_response()
 }
@@ -99,19 +119,36 @@ pub async fn remove(
     get,
     path = "/api/cheatsheet/files",
     tag = "Cheatsheet",
-    description = "Get all files that are shared with the current user, including files they own.",
+    description = "Get the files that are shared with the current user, including files they own. \
+        Files whose share has expired are left out. \
+        Supports filtering, sorting and cursor-based paging; without `limit` every matching file is returned. \
+        Without `sort`, files come in storage order.",
+    params(
+        ("type" = Option<dtos::FileType>, Query, description = "Only slides or only cheatsheets, by key prefix"),
+        ("ownership" = Option<dtos::FileOwnership>, Query, description = "Only files the user owns (`mine`) or that were shared with them (`shared`)"),
+        ("name" = Option<String>, Query, description = "Case-insensitive substring of the file name"),
+        ("created_after" = Option<String>, Query, description = "Inclusive lower bound, RFC 3339 timestamp or YYYY-MM-DD"),
+        ("created_before" = Option<String>, Query, description = "Exclusive upper bound, RFC 3339 timestamp or YYYY-MM-DD"),
+        ("sort" = Option<dtos::FileSort>, Query, description = "Sort field; without it files come in storage order"),
+        ("order" = Option<dtos::SortOrder>, Query, description = "Sort order when sorting, defaults to desc"),
+        ("limit" = Option<usize>, Query, description = "Page size, 1 to 100"),
+        ("cursor" = Option<String>, Query, description = "next_cursor from the previous page, with the same sort and order"),
+    ),
     responses(
         (status = 200, description = "List of all user files", body = dtos::GetAllFilesResponse),
-        (status = 500, description = "Internal server error"),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn get_all_files(
     State(handler): State<CheatsheetHandler>,
     UserId(user_id): UserId,
+    Query(query): Query<ListFilesQuery>,
 ) -> impl IntoResponse {
     handler
         .cheatsheet_service
-        .get_all_files(user_id)
+        .get_all_files(user_id, query)
         .await
         .into_axum_response()
 }
@@ -123,7 +160,9 @@ pub async fn get_all_files(
     description = "Get a file by its ID.",
     responses(
         (status = 200, description = "File", body = dtos::GetFileResponse),
-        (status = 500, description = "Internal server error"),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 403, description = "The user's share of the file has expired", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn get_file(
@@ -142,21 +181,56 @@ pub async fn get_file(
     post,
     path = "/api/cheatsheet/share",
     tag = "Cheatsheet",
-    description = "Share your file with another user. Creates a share record in DynamoDB. Your user id is determined from the Auth header.",
+    description = "Share a file with another user, by user id or by email, granting them a role. Creates a share record in DynamoDB. \
+        Owners can grant up to `co_owner`; other users need the `resharer` role and can grant up to their own role. \
+        An email without an account gets a pending invitation instead, granted when it first logs in. \
+        Set `expires_at` or `expires_in_secs` for a share that is revoked automatically; an invitation that is \
+        not claimed before then lapses. \
+        Your user id is determined from the Auth header.",
     request_body = dtos::ShareRequest,
     responses(
-        (status = 200, description = "File shared successfully", body = dtos::ShareResponse),
-        (status = 500, description = "Internal server error"),
+        (status = 200, description = "File shared, or invitation recorded when `pending` is true", body = dtos::ShareResponse),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 403, description = "Not allowed to share the file, or to grant the role", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn share(
     State(handler): State<CheatsheetHandler>,
-    UserId(owner_id): UserId,
+    UserId(user_id): UserId,
     Json(body): Json<dtos::ShareRequest>,
 ) -> impl IntoResponse {
     handler
         .cheatsheet_service
-        .share(owner_id, body.user_id, body.file_id)
+        .share(user_id, body)
+        .await
+        .into_axum_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/cheatsheet/share/bulk",
+    tag = "Cheatsheet",
+    description = "Share or unshare several files with several users in one call. Every file is paired with every recipient, \
+        and each pair succeeds or fails on its own; the response reports the outcome per pair. \
+        Recipients are given by user id or email, with the same rules as `/api/cheatsheet/share`.",
+    request_body = dtos::BulkShareRequest,
+    responses(
+        (status = 200, description = "Outcome of every file and recipient pair", body = dtos::BulkShareResponse),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 429, description = "Too many requests", body = dtos::ApiError),
+    ),
+)]
+pub async fn bulk_share(
+    State(handler): State<CheatsheetHandler>,
+    UserId(user_id): UserId,
+    Json(body): Json<dtos::BulkShareRequest>,
+) -> impl IntoResponse {
+    handler
+        .cheatsheet_service
+        .bulk_share(user_id, body)
         .await
         .into_axum_response()
 }
@@ -165,21 +239,147 @@ pub async fn share(
     post,
     path = "/api/cheatsheet/unshare",
     tag = "Cheatsheet",
-    description = "Revoke a user's access to a file. Removes the share record from DynamoDB. Your user id is determined from the Auth header.",
+    description = "Revoke a user's access to a file, or cancel a pending invitation by email. Removes the share record from DynamoDB. \
+        Needs the `co_owner` role; the owner and users above the caller's role cannot be removed. \
+        Your user id is determined from the Auth header.",
     request_body = dtos::UnshareRequest,
     responses(
         (status = 200, description = "File unshared successfully", body = dtos::UnshareResponse),
-        (status = 500, description = "Internal server error"),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 403, description = "Not a co-owner of the file", body = dtos::ApiError),
+        (status = 404, description = "No pending invitation for the email", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn unshare(
     State(handler): State<CheatsheetHandler>,
-    UserId(owner_id): UserId,
+    UserId(user_id): UserId,
     Json(body): Json<dtos::UnshareRequest>,
 ) -> impl IntoResponse {
     handler
         .cheatsheet_service
-        .unshare(owner_id, body.user_id, body.file_id)
+        .unshare(user_id, body)
+        .await
+        .into_axum_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/cheatsheet/files/{file_id}/links",
+    tag = "Cheatsheet",
+    description = "Create a public link to a file, which anyone holding it can open without an account. \
+        Links can be protected with a password and can expire. Needs the `resharer` role. \
+        The returned token cannot be retrieved again.",
+    params(
+        ("file_id" = String, Path, description = "File to link to")
+    ),
+    request_body = dtos::CreateShareLinkRequest,
+    responses(
+        (status = 200, description = "Link created", body = dtos::CreateShareLinkResponse),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 403, description = "Missing the resharer role on the file", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
+    ),
+)]
+pub async fn create_share_link(
+    State(handler): State<CheatsheetHandler>,
+    UserId(user_id): UserId,
+    Path(file_id): Path<String>,
+    Json(body): Json<dtos::CreateShareLinkRequest>,
+) -> impl IntoResponse {
+    handler
+        .cheatsheet_service
+        .create_share_link(user_id, file_id, body)
+        .await
+        .into_axum_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/cheatsheet/files/{file_id}/links",
+    tag = "Cheatsheet",
+    description = "List the public links to a file that have not expired. Needs the `resharer` role.",
+    params(
+        ("file_id" = String, Path, description = "File whose links to list")
+    ),
+    responses(
+        (status = 200, description = "Links, oldest first", body = dtos::GetShareLinksResponse),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 403, description = "Missing the resharer role on the file", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
+    ),
+)]
+pub async fn get_share_links(
+    State(handler): State<CheatsheetHandler>,
+    UserId(user_id): UserId,
+    Path(file_id): Path<String>,
+) -> impl IntoResponse {
+    handler
+        .cheatsheet_service
+        .get_share_links(user_id, file_id)
+        .await
+        .into_axum_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/cheatsheet/files/{file_id}/links/{link_id}",
+    tag = "Cheatsheet",
+    description = "Revoke a public link; it stops working immediately. \
+        Co-owners can revoke any link to the file, resharers only the links they created.",
+    params(
+        ("file_id" = String, Path, description = "File the link points to"),
+        ("link_id" = String, Path, description = "Id of the link"),
+    ),
+    responses(
+        (status = 200, description = "Link revoked", body = dtos::RevokeShareLinkResponse),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 403, description = "Not allowed to revoke the link", body = dtos::ApiError),
+        (status = 404, description = "No such link to the file", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
+    ),
+)]
+pub async fn revoke_share_link(
+    State(handler): State<CheatsheetHandler>,
+    UserId(user_id): UserId,
+    Path((file_id, link_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    handler
+        .cheatsheet_service
+        .revoke_share_link(user_id, file_id, link_id)
+        .await
+        .into_axum_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/cheatsheet/links/{token}",
+    tag = "Cheatsheet",
+    description = "Open a public link. Needs no authentication; returns a short-lived download URL for the file. \
+        Send `{}` as the body, or the password for a protected link. Limited per client IP; \
+        five wrong passwords in a row lock a link for 15 minutes.",
+    params(
+        ("token" = String, Path, description = "Token from the link")
+    ),
+    request_body = dtos::ResolveShareLinkRequest,
+    responses(
+        (status = 200, description = "Download URL", body = dtos::ResolveShareLinkResponse),
+        (status = 401, description = "The link needs a password, or the password is wrong", body = dtos::ApiError),
+        (status = 404, description = "Unknown, revoked or expired link", body = dtos::ApiError),
+        (status = 429, description = "Rate limit exceeded, or the link is locked after too many wrong passwords", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
+    ),
+)]
+pub async fn resolve_share_link(
+    State(handler): State<CheatsheetHandler>,
+    Path(token): Path<String>,
+    Json(body): Json<dtos::ResolveShareLinkRequest>,
+) -> impl IntoResponse {
+    handler
+        .cheatsheet_service
+        .resolve_share_link(token, body)
         .await
         .into_axum_response()
 }
@@ -188,22 +388,177 @@ pub async fn unshare(
     post,
     path = "/api/cheatsheet/generate",
     tag = "Cheatsheet",
-    description = "Generate a combined PDF from multiple files you have access to. Sends a request to SQS and waits for the Lambda to process and merge the PDFs. Returns the key of the generated file.",
+    description = "Start generating a combined PDF from up to 20 files you hold at least the `editor` role on. Returns a job immediately; poll `GET /api/cheatsheet/generate/{job_id}` until it has succeeded to get the key of the generated file. Each user has a daily generation quota.",
     request_body = dtos::GenerateRequest,
     responses(
-        (status = 200, description = "Generation request accepted", body = dtos::GenerateResponse),
-        (status = 400, description = "Bad request"),
-        (status = 500, description = "Internal server error"),
+        (status = 202, description = "Generation job queued", body = dtos::GenerateResponse),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 403, description = "Missing the editor role on one of the files, or one of them does not exist", body = dtos::ApiError),
+        (status = 429, description = "Rate limit, daily generation quota or cap on jobs in progress exceeded", body = dtos::ApiError,
+            headers(
+                ("Retry-After" = u64, description = "Seconds until the limit resets"),
+                ("X-RateLimit-Limit" = u32, description = "Requests allowed in the current window"),
+                ("X-RateLimit-Remaining" = u32, description = "Requests left in the current window"),
+                ("X-RateLimit-Reset" = u64, description = "Seconds until the current window ends"),
+            )
+        ),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn generate(
     State(handler): State<CheatsheetHandler>,
     UserId(user_id): UserId,
     Json(body): Json<dtos::GenerateRequest>,
+) -> impl IntoResponse {
+    if body.file_ids.is_empty() {
+        return ApiError::bad_request("Invalid generate request")
+            .with_details(vec![FieldError::new(
+                "file_ids",
+                "at least one file id is required",
+            )])
+            .into_response();
+    }
+    if body.file_ids.len() > MAX_GENERATE_FILES {
+        return ApiError::bad_request("Invalid generate request")
+            .with_details(vec![FieldError::new(
+                "file_ids",
+                &format!("at most {} file ids are allowed", MAX_GENERATE_FILES),
+            )])
+            .into_response();
+    }
+
+    if let Err(e) = handler
+        .cheatsheet_service
+        .authorize_generation(&user_id, &body.file_ids)
+        .await
+    {
+        return e.into_response();
+    }
+
+    // the quota is only counted once the request is known to be valid and authorized, and
+    // given back if the job cannot be queued
+    if let Some(quota) = handler.cheatsheet_service.reserve_generation(&user_id)
+        && !quota.allowed
+    {
+        let mut response =
+            ApiError::rate_limited("Daily generation quota exceeded").into_response();
+        quota.apply_headers(response.headers_mut());
+        return response;
+    }
+
+    match handler
+        .cheatsheet_service
+        .submit_generation(body.file_ids, user_id.clone())
+        .await
+    {
+        ApiResponse::Success(job) => (StatusCode::ACCEPTED, axum::Json(job)).into_response(),
+        error => {
+            handler.cheatsheet_service.refund_generation(&user_id);
+            error.into_axum_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/cheatsheet/generate/{job_id}",
+    tag = "Cheatsheet",
+    description = "Get the status of a generation job. `file_id` and `key` are set once it has succeeded.",
+    params(
+        ("job_id" = String, Path, description = "Job id returned when the generation was submitted")
+    ),
+    responses(
+        (status = 200, description = "Success", body = dtos::GenerateResponse),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 404, description = "Job not found", body = dtos::ApiError),
+    ),
+)]
+pub async fn get_generation(
+    State(handler): State<CheatsheetHandler>,
+    UserId(user_id): UserId,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    handler
+        .cheatsheet_service
+        .get_generation(job_id, user_id)
+        .await
+        .into_axum_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/cheatsheet/generate/{job_id}/events",
+    tag = "Cheatsheet",
+    description = "Stream the progress of a generation job as server-sent events. Each event is named after the job's stage \
+        (`queued`, `downloading_sources`, `summarising`, `rendering`, `done`, `failed` or `cancelled`) and carries the job as JSON. \
+        The first event is the current state; the stream ends after a terminal stage, whose event holds the final `key` or `error`.",
+    params(
+        ("job_id" = String, Path, description = "Job id returned when the generation was submitted")
+    ),
+    responses(
+        (status = 200, description = "Event stream of the job's state", content_type = "text/event-stream", body = dtos::GenerateResponse),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 404, description = "Job not found", body = dtos::ApiError),
+    ),
+)]
+pub async fn generation_events(
+    State(handler): State<CheatsheetHandler>,
+    UserId(user_id): UserId,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    match handler
+        .cheatsheet_service
+        .subscribe_generation(job_id, user_id)
+    {
+        ApiResponse::Success(receiver) => Sse::new(job_events(receiver))
+            .keep_alive(KeepAlive::default())
+            .into_response(),
+        ApiResponse::Error(e) => e.into_response(),
+    }
+}
+
+/// One event per change of the job, starting with its current state and ending after it
+/// reaches a terminal stage
+fn job_events(
+    receiver: watch::Receiver<dtos::GenerateResponse>,
+) -> impl Stream<Item = Result<Event, axum::Error>> {
+    stream::unfold(
+        (receiver, true, false),
+        |(mut receiver, first, finished)| async move {
+            if finished || (!first && receiver.changed().await.is_err()) {
+                return None;
+            }
+            let job = receiver.borrow_and_update().clone();
+            let event = Event::default().event(job.stage.as_str()).json_data(&job);
+            Some((event, (receiver, false, job.stage.is_terminal())))
+        },
+    )
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/cheatsheet/generate/{job_id}",
+    tag = "Cheatsheet",
+    description = "Cancel a queued or running generation job. Finished jobs are returned unchanged. \
+        Cancelling stops the gateway tracking the job; a generation that was already running may still finish and store its file.",
+    params(
+        ("job_id" = String, Path, description = "Job id returned when the generation was submitted")
+    ),
+    responses(
+        (status = 200, description = "Success", body = dtos::GenerateResponse),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 404, description = "Job not found", body = dtos::ApiError),
+    ),
+)]
+pub async fn cancel_generation(
+    State(handler): State<CheatsheetHandler>,
+    UserId(user_id): UserId,
+    Path(job_id): Path<String>,
 ) -> impl IntoResponse {
     handler
         .cheatsheet_service
-        .generate(body.file_ids, user_id)
+        .cancel_generation(job_id, user_id)
         .await
         .into_axum_response()
 }
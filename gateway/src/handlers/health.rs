@@ -0,0 +1,50 @@
+use crate::dtos;
+use crate::services::health::HealthService;
+use axum::Json;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use hyper::StatusCode;
+
+#[derive(Debug, Clone)]
+pub struct HealthHandler {
+    health_service: HealthService,
+}
+
+impl HealthHandler {
+    pub fn new(health_service: HealthService) -> Self {
+        Self { health_service }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "Health",
+    responses(
+        (status = 200, description = "The gateway process is up", body = dtos::HealthResponse),
+    ),
+)]
+pub async fn healthz() -> impl IntoResponse {
+    Json(dtos::HealthResponse {
+        status: "ok".to_string(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "Health",
+    responses(
+        (status = 200, description = "All dependencies are reachable", body = dtos::ReadinessResponse),
+        (status = 503, description = "At least one dependency is unavailable", body = dtos::ReadinessResponse),
+    ),
+)]
+pub async fn readyz(State(handler): State<HealthHandler>) -> impl IntoResponse {
+    let readiness = handler.health_service.readiness().await;
+    let status = if readiness.checks.iter().all(|check| check.healthy) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
@@ -1,2 +1,3 @@
 pub mod cheatsheet;
+pub mod health;
 pub mod user;
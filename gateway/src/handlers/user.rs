@@ -1,7 +1,8 @@
 use crate::dtos;
+use crate::extractors::{Json, Query};
 use crate::services::user::UserService;
 use axum::extract::State;
-use axum::{Json, response::IntoResponse};
+use axum::response::IntoResponse;
 
 #[derive(Debug, Clone)]
 pub struct UserHandler {
@@ -20,7 +21,7 @@ impl UserHandler {
     tag = "User",
     responses(
         (status = 200, description = "Success", body = String),
-        (status = 500, description = "Internal server error"),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn get_google_login_url(State(handler): State<UserHandler>) -> impl IntoResponse {
@@ -38,7 +39,10 @@ pub async fn get_google_login_url(State(handler): State<UserHandler>) -> impl In
     request_body = dtos::LoginRequest,
     responses(
         (status = 200, description = "Success", body = dtos::LoginResponse),
-        (status = 500, description = "Internal server error"),
+        (status = 429, description = "Too many login attempts from this client", body = dtos::ApiError,
+            headers(("Retry-After" = u64, description = "Seconds until the limit resets"))
+        ),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn login(
@@ -59,8 +63,8 @@ pub async fn login(
     request_body = dtos::ValidateTokenRequest,
     responses(
         (status = 200, description = "Success", body = dtos::ValidateTokenResponse),
-        (status = 401, description = "Unauthorized"),
-        (status = 500, description = "Internal server error"),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
 pub async fn validate_token(
@@ -74,19 +78,76 @@ pub async fn validate_token(
         .into_axum_response()
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/user/refresh",
+    tag = "User",
+    description = "Exchange a refresh token for a new access token. The refresh token is rotated and the old one stops working.",
+    request_body = dtos::RefreshTokenRequest,
+    responses(
+        (status = 200, description = "Success", body = dtos::RefreshTokenResponse),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
+    ),
+)]
+pub async fn refresh_token(
+    State(handler): State<UserHandler>,
+    Json(request): Json<dtos::RefreshTokenRequest>,
+) -> impl IntoResponse {
+    handler
+        .user_service
+        .refresh_token(request)
+        .await
+        .into_axum_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/user/logout",
+    tag = "User",
+    description = "Revoke the given access token and its session. Set `all_sessions` to sign out on every device.",
+    request_body = dtos::LogoutRequest,
+    responses(
+        (status = 200, description = "Success", body = dtos::LogoutResponse),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
+    ),
+)]
+pub async fn logout(
+    State(handler): State<UserHandler>,
+    Json(request): Json<dtos::LogoutRequest>,
+) -> impl IntoResponse {
+    handler
+        .user_service
+        .logout(request)
+        .await
+        .into_axum_response()
+}
+
 #[utoipa::path(
     get,
-    path = "/api/user/all",
+    path = "/api/user/search",
     tag = "User",
+    description = "Find users by name or email for typeahead, ordered by name, with cursor-based paging.",
+    params(
+        ("q" = String, Query, description = "At least 2 characters; every word must prefix a word of the name or email"),
+        ("limit" = Option<u32>, Query, description = "Page size, defaults to 20, capped at 50"),
+        ("cursor" = Option<String>, Query, description = "next_cursor from the previous page, with the same q"),
+    ),
     responses(
-        (status = 200, description = "Success", body = dtos::GetAllUsersResponse),
-        (status = 500, description = "Internal server error"),
+        (status = 200, description = "Matching users", body = dtos::SearchUsersResponse),
+        (status = 400, description = "Bad request", body = dtos::ApiError),
+        (status = 401, description = "Unauthorized", body = dtos::ApiError),
+        (status = 500, description = "Internal server error", body = dtos::ApiError),
     ),
 )]
-pub async fn get_all_users(State(handler): State<UserHandler>) -> impl IntoResponse {
+pub async fn search_users(
+    State(handler): State<UserHandler>,
+    Query(query): Query<dtos::SearchUsersQuery>,
+) -> impl IntoResponse {
     handler
         .user_service
-        .get_all_users()
+        .search_users(query)
         .await
         .into_axum_response()
 }
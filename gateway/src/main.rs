@@ -1,13 +1,26 @@
 use crate::handlers::cheatsheet::CheatsheetHandler;
+use crate::handlers::health::HealthHandler;
 use crate::handlers::user::UserHandler;
 use crate::proto::user::user_client::UserClient;
 use crate::routes::auth::auth_routes;
-use crate::routes::cheatsheet::cheatsheet_routes;
+use crate::routes::cheatsheet::{cheatsheet_routes, public_cheatsheet_routes};
+use crate::routes::health::health_routes;
+use crate::routes::user::user_routes;
 use crate::services::cheatsheet::CheatsheetService;
+use crate::services::health::HealthService;
+use crate::services::rate_limiter::RateLimiter;
+use crate::services::token_cache::TokenCache;
 use crate::services::user::UserService;
-use axum::{Router, middleware as axum_middleware};
+use axum::{Router, middleware as axum_middleware, response::Response};
+use openexam_common::request_signer::RequestSigner;
+use openexam_common::shutdown::Shutdown;
+use openexam_common::telemetry as common_telemetry;
 use std::net::SocketAddr;
+use std::time::Duration;
+use tonic::transport::Channel;
 use tower_http::cors::Any;
+use tower_http::trace::TraceLayer;
+use tracing::{Span, info};
 use utoipa_swagger_ui::SwaggerUi;
 
 mod config;
@@ -15,57 +28,154 @@ mod docs;
 mod dtos;
 mod extractors;
 mod handlers;
+mod metrics;
 mod middleware;
 mod proto;
 mod routes;
 mod services;
+mod telemetry;
+
+/// Part of the shutdown deadline left for cancelled generations to reach clients following
+/// them, whose event streams keep the server from draining until their job ends
+const GENERATION_CANCEL_MARGIN: Duration = Duration::from_secs(2);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    env_logger::init();
     dotenvy::dotenv().ok();
     let config = config::config::Config::from_env()?;
+    let _telemetry = common_telemetry::init(
+        &config.telemetry.service_name,
+        config.telemetry.otlp_endpoint.as_deref(),
+    )?;
+    let metrics_addr: SocketAddr = config.server.metrics_addr.parse()?;
+    metrics::init(metrics_addr)?;
+    info!("Metrics available on http://{}/metrics", metrics_addr);
+
+    let shutdown = Shutdown::new(Duration::from_secs(
+        config.server.shutdown_pre_stop_delay_secs,
+    ));
+    shutdown.listen_for_signals();
 
-    let user_client = UserClient::connect(config.server.user_grpc_url).await?;
-    let user_service = UserService::new(user_client);
+    // connect lazily so the gateway starts (and answers 503s) even while the user service is
+    // down; the channel re-establishes dropped connections on its own
+    let user_channel = Channel::from_shared(config.server.user_grpc_url)?
+        .connect_timeout(Duration::from_secs(
+            config.server.user_grpc_connect_timeout_secs,
+        ))
+        .http2_keep_alive_interval(Duration::from_secs(30))
+        .keep_alive_timeout(Duration::from_secs(10))
+        .connect_lazy();
+    let user_client = UserClient::new(user_channel.clone());
+    let token_cache = TokenCache::new(
+        Duration::from_secs(config.auth.token_cache_ttl_secs),
+        config.auth.token_cache_max_entries,
+    );
+    let signer = RequestSigner::new(&config.server.internal_signing_secret);
+    let user_service = UserService::new(user_client, token_cache, signer.clone());
     let user_handler = UserHandler::new(user_service.clone());
 
-    let cheatsheet_service =
-        CheatsheetService::new(config.server.cheatsheet_api_url, user_service.clone());
-    let cheatsheet_handler = CheatsheetHandler::new(cheatsheet_service);
+    let health_service = HealthService::new(
+        user_channel,
+        config.server.cheatsheet_api_url.clone(),
+        shutdown.clone(),
+    );
+    let health_handler = HealthHandler::new(health_service);
+
+    let cheatsheet_service = CheatsheetService::new(
+        config.server.cheatsheet_api_url,
+        signer,
+        config.server.cheatsheet_client,
+        config.generation,
+        config.share_links,
+        user_service.clone(),
+    )?;
+    let cheatsheet_handler = CheatsheetHandler::new(cheatsheet_service.clone());
 
     let cors = tower_http::cors::CorsLayer::new()
         .allow_headers(Any)
         .allow_methods(Any)
         .allow_origin(Any);
 
-    // routes that don't require authentication
-    let public_routes = Router::new().nest("/api", auth_routes().with_state(user_handler));
+    let rate_limiter = RateLimiter::new(config.rate_limit);
+
+    // routes that don't require authentication, limited per client IP; probes are exempt
+    let public_routes = Router::new()
+        .nest("/api", auth_routes().with_state(user_handler.clone()))
+        .nest(
+            "/api",
+            public_cheatsheet_routes().with_state(cheatsheet_handler.clone()),
+        )
+        .layer(axum_middleware::from_fn_with_state(
+            rate_limiter.clone(),
+            middleware::rate_limit_by_ip,
+        ))
+        .merge(health_routes().with_state(health_handler));
 
-    // routes that require authentication
+    // routes that require authentication, limited per user once auth has identified them
     let protected_routes = Router::new()
         .nest("/api", cheatsheet_routes().with_state(cheatsheet_handler))
+        .nest("/api", user_routes().with_state(user_handler))
+        .layer(axum_middleware::from_fn_with_state(
+            rate_limiter,
+            middleware::rate_limit_by_user,
+        ))
         .layer(axum_middleware::from_fn_with_state(
             user_service.clone(),
             middleware::auth_middleware,
         ));
 
-    let mut app = Router::new()
-        .merge(public_routes)
-        .merge(protected_routes)
-        .layer(cors);
+    let mut app = Router::new().merge(public_routes).merge(protected_routes);
 
     if config.app.debug {
         app = app.merge(SwaggerUi::new("/swagger").url("/api-docs/openapi.json", docs::get_doc()));
     }
 
+    let trace = TraceLayer::new_for_http()
+        .make_span_with(telemetry::make_http_span)
+        .on_response(|response: &Response, latency: Duration, span: &Span| {
+            span.record("http.status_code", response.status().as_u16());
+            info!(
+                status = response.status().as_u16(),
+                latency_ms = latency.as_millis() as u64,
+                "finished request"
+            );
+        });
+
+    // request id is outermost so every span, response and log line carries it
+    let app = app
+        .layer(axum_middleware::from_fn(middleware::metrics_middleware))
+        .layer(cors)
+        .layer(trace)
+        .layer(axum_middleware::from_fn(middleware::request_id_middleware));
+
     let addr = SocketAddr::new(
         config.server.gateway_host.parse()?,
         config.server.gateway_port,
     );
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    println!("Server running on http://{}", addr);
-    axum::serve(listener, app).await.unwrap();
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Server running on http://{}", addr);
+
+    let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout_secs);
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown.wait_for_stop().await;
+            cheatsheet_service
+                .drain_generations(shutdown_timeout.saturating_sub(GENERATION_CANCEL_MARGIN))
+                .await;
+        }
+    });
+
+    // peer addresses are needed to rate limit public routes per client IP
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown({
+        let shutdown = shutdown.clone();
+        async move { shutdown.wait_for_stop().await }
+    });
+    shutdown.drain(server, shutdown_timeout).await?;
 
     Ok(())
 }
@@ -0,0 +1,67 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use metrics::{counter, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+
+/// Latency buckets in seconds shared by every duration histogram
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Installs the global Prometheus recorder and serves `/metrics` on its own port, apart
+/// from the public API
+pub fn init(addr: SocketAddr) -> anyhow::Result<()> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("_duration_seconds".to_string()),
+            LATENCY_BUCKETS,
+        )?
+        .with_http_listener(addr)
+        .install()?;
+    Ok(())
+}
+
+pub fn record_http_request(method: &str, route: &str, status: u16, latency: Duration) {
+    let labels = [
+        ("method", method.to_string()),
+        ("route", route.to_string()),
+        ("status", status.to_string()),
+    ];
+    counter!("http_requests_total", &labels).increment(1);
+    histogram!("http_request_duration_seconds", &labels).record(latency.as_secs_f64());
+}
+
+/// Records one call to a backend service. `outcome` is an HTTP status or gRPC code name,
+/// or `error` when no response was received at all
+pub fn record_upstream_call(
+    upstream: &'static str,
+    operation: &'static str,
+    outcome: &str,
+    latency: Duration,
+) {
+    counter!(
+        "upstream_requests_total",
+        "upstream" => upstream,
+        "operation" => operation,
+        "outcome" => outcome.to_string(),
+    )
+    .increment(1);
+    histogram!(
+        "upstream_request_duration_seconds",
+        "upstream" => upstream,
+        "operation" => operation,
+    )
+    .record(latency.as_secs_f64());
+}
+
+pub fn record_token_cache_lookup(hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    counter!("token_cache_lookups_total", "result" => result).increment(1);
+}
+
+/// Counts a request rejected with 429. `limit` is the route template of the limit that was
+/// hit, `default`, or `generation_quota`
+pub fn record_rate_limited(limit: &str) {
+    counter!("rate_limited_requests_total", "limit" => limit.to_string()).increment(1);
+}
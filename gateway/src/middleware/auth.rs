@@ -1,34 +1,46 @@
 use axum::{
-    body::Body,
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, HeaderName},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
-use serde_json::json;
+
+use tracing::instrument;
 
 use crate::{
-    dtos,
+    dtos::{self, ApiError},
     services::{response::ApiResponse, user::UserService},
 };
 
 /// Middleware that validates JWT token from Authorization header
+#[instrument(name = "auth_middleware", skip_all)]
 pub async fn auth_middleware(
     State(user_service): State<UserService>,
     headers: HeaderMap,
     mut request: Request,
     next: Next,
 ) -> Result<Response, Response> {
+    // Only the identity set below may reach handlers; drop any the client sent itself
+    let forged: Vec<HeaderName> = request
+        .headers()
+        .keys()
+        .filter(|name| name.as_str().starts_with("x-user-"))
+        .cloned()
+        .collect();
+    for name in forged {
+        request.headers_mut().remove(name);
+    }
+
     // Extract token from Authorization header
     let token = headers
         .get("Authorization")
         .and_then(|v| v.to_str().ok())
-        .and_then(|v| {
+        .map(|v| {
             // Support both "Bearer TOKEN" and just "TOKEN"
             if v.starts_with("Bearer ") {
-                Some(v.trim_start_matches("Bearer ").to_string())
+                v.trim_start_matches("Bearer ").to_string()
             } else {
-                Some(v.to_string())
+                v.to_string()
             }
         });
 
@@ -53,29 +65,13 @@ pub async fn auth_middleware(
 
                     Ok(next.run(request).await)
                 }
-                ApiResponse::Error { message, .. } => {
-                    // Token validation failed
-                    Err(unauthorized_response(&message))
-                }
+                // 401 for a bad token; 5xx if the user service itself is failing
+                ApiResponse::Error(e) => Err(e.into_response()),
             }
         }
         None => {
             // No token provided
-            Err(unauthorized_response("Missing authorization token"))
+            Err(ApiError::unauthenticated("Missing authorization token").into_response())
         }
     }
 }
-
-/// Helper function to create unauthorized response
-fn unauthorized_response(message: &str) -> Response {
-    let body = json!({
-        "success": false,
-        "error": message
-    });
-
-    Response::builder()
-        .status(StatusCode::UNAUTHORIZED)
-        .header("Content-Type", "application/json")
-        .body(Body::from(body.to_string()))
-        .unwrap()
-}
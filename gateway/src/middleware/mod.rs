@@ -1,3 +1,9 @@
 pub mod auth;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
 
 pub use auth::auth_middleware;
+pub use metrics::metrics_middleware;
+pub use rate_limit::{rate_limit_by_ip, rate_limit_by_user};
+pub use request_id::request_id_middleware;
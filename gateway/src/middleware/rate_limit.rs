@@ -0,0 +1,72 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{dtos::ApiError, metrics, services::rate_limiter::RateLimiter};
+
+/// Limits authenticated routes per user. Must run after `auth_middleware`, which sets the
+/// `X-User-Id` header this keys on.
+pub async fn rate_limit_by_user(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let user_id = request
+        .headers()
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    enforce(&limiter, format!("user:{user_id}"), request, next).await
+}
+
+/// Limits public routes per client IP
+pub async fn rate_limit_by_ip(
+    State(limiter): State<RateLimiter>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let forwarded_for = limiter
+        .trust_forwarded_for()
+        .then(|| {
+            request
+                .headers()
+                .get("X-Forwarded-For")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .map(|ip| ip.trim().to_string())
+        })
+        .flatten()
+        .filter(|ip| !ip.is_empty());
+    let ip = forwarded_for.unwrap_or_else(|| peer.ip().to_string());
+
+    enforce(&limiter, format!("ip:{ip}"), request, next).await
+}
+
+async fn enforce(limiter: &RateLimiter, client: String, request: Request, next: Next) -> Response {
+    if !limiter.enabled() {
+        return next.run(request).await;
+    }
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let (limit, decision) = limiter.check(request.method(), &route, &client);
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        metrics::record_rate_limited(limit);
+        ApiError::rate_limited("Too many requests, please slow down").into_response()
+    };
+    decision.apply_headers(response.headers_mut());
+    response
+}
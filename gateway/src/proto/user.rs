@@ -28,6 +28,8 @@ pub struct LoginReply {
     pub name: ::prost::alloc::string::String,
     #[prost(string, tag = "4")]
     pub token: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub refresh_token: ::prost::alloc::string::String,
 }
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct ValidateTokenRequest {
@@ -42,13 +44,206 @@ pub struct ValidateTokenReply {
     pub email: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
     pub name: ::prost::alloc::string::String,
+    /// Unix seconds when the token expires
+    #[prost(uint64, tag = "4")]
+    pub expires_at: u64,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RefreshTokenRequest {
+    #[prost(string, tag = "1")]
+    pub refresh_token: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RefreshTokenReply {
+    #[prost(string, tag = "1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub refresh_token: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct LogoutRequest {
+    #[prost(string, tag = "1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub all_sessions: bool,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct LogoutReply {
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+}
+/// Unknown and non-numeric ids are left out of the reply rather than failing the call
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetUsersByIdsRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetUsersByIdsReply {
+    #[prost(message, repeated, tag = "1")]
+    pub users: ::prost::alloc::vec::Vec<UserProfile>,
+}
+/// Finds users with a word in their name or email starting with every term of `query`,
+/// ordered by name
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct SearchUsersRequest {
+    #[prost(string, tag = "1")]
+    pub query: ::prost::alloc::string::String,
+    /// Defaults to 20, capped at 50
+    #[prost(uint32, tag = "2")]
+    pub limit: u32,
+    /// `next_cursor` of the previous page
+    #[prost(string, tag = "3")]
+    pub cursor: ::prost::alloc::string::String,
 }
-#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct GetAllUsersRequest {}
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetAllUsersReply {
+pub struct SearchUsersReply {
     #[prost(message, repeated, tag = "1")]
     pub users: ::prost::alloc::vec::Vec<UserProfile>,
+    /// Empty on the last page
+    #[prost(string, tag = "2")]
+    pub next_cursor: ::prost::alloc::string::String,
+}
+/// Resolves an email to a user, or records a pending invitation to `file_id` that is
+/// granted when the email first logs in. The caller checks that `invited_by` owns the file.
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct InviteByEmailRequest {
+    #[prost(string, tag = "1")]
+    pub email: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub file_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub invited_by: ::prost::alloc::string::String,
+    /// Share role granted once the invitation is claimed; empty means viewer
+    #[prost(string, tag = "4")]
+    pub role: ::prost::alloc::string::String,
+    /// RFC 3339; the share granted on claim expires then. Empty for a permanent share
+    #[prost(string, tag = "5")]
+    pub expires_at: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct InviteByEmailReply {
+    /// Set when the email belongs to a registered user; share the file with them directly
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    /// True when the email has no account yet and an invitation was recorded
+    #[prost(bool, tag = "2")]
+    pub pending: bool,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RevokeInvitationRequest {
+    #[prost(string, tag = "1")]
+    pub email: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub file_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RevokeInvitationReply {
+    #[prost(bool, tag = "1")]
+    pub revoked: bool,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetFileInvitationsRequest {
+    #[prost(string, tag = "1")]
+    pub file_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Invitation {
+    #[prost(string, tag = "1")]
+    pub email: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub invited_by: ::prost::alloc::string::String,
+    /// Unix seconds
+    #[prost(int64, tag = "3")]
+    pub created_at: i64,
+    #[prost(string, tag = "4")]
+    pub role: ::prost::alloc::string::String,
+    /// RFC 3339, empty for a permanent share
+    #[prost(string, tag = "5")]
+    pub expires_at: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetFileInvitationsReply {
+    #[prost(message, repeated, tag = "1")]
+    pub invitations: ::prost::alloc::vec::Vec<Invitation>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ShareLink {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub file_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub created_by: ::prost::alloc::string::String,
+    /// Unix seconds
+    #[prost(int64, tag = "4")]
+    pub created_at: i64,
+    /// RFC 3339, empty for a link that never expires
+    #[prost(string, tag = "5")]
+    pub expires_at: ::prost::alloc::string::String,
+    #[prost(bool, tag = "6")]
+    pub has_password: bool,
+}
+/// Creates a public link to `file_id`. The caller checks that `created_by` may share the file.
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CreateShareLinkRequest {
+    #[prost(string, tag = "1")]
+    pub file_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub created_by: ::prost::alloc::string::String,
+    /// Empty for a link that anyone holding it can open
+    #[prost(string, tag = "3")]
+    pub password: ::prost::alloc::string::String,
+    /// RFC 3339; empty for a link that never expires
+    #[prost(string, tag = "4")]
+    pub expires_at: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CreateShareLinkReply {
+    #[prost(message, optional, tag = "1")]
+    pub link: ::core::option::Option<ShareLink>,
+    /// The secret part of the link; only its hash is stored, so it cannot be retrieved again
+    #[prost(string, tag = "2")]
+    pub token: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetFileShareLinksRequest {
+    #[prost(string, tag = "1")]
+    pub file_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetFileShareLinksReply {
+    /// Links that have not expired, oldest first
+    #[prost(message, repeated, tag = "1")]
+    pub links: ::prost::alloc::vec::Vec<ShareLink>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RevokeShareLinkRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub file_id: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RevokeShareLinkReply {
+    #[prost(bool, tag = "1")]
+    pub revoked: bool,
+}
+/// Fails with NOT_FOUND for an unknown, revoked or expired token, and with UNAUTHENTICATED
+/// when the link has a password and `password` is missing or wrong
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ResolveShareLinkRequest {
+    #[prost(string, tag = "1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub password: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ResolveShareLinkReply {
+    #[prost(message, optional, tag = "1")]
+    pub link: ::core::option::Option<ShareLink>,
 }
 /// Generated client implementations.
 pub mod user_client {
@@ -204,11 +399,11 @@ pub mod user_client {
             req.extensions_mut().insert(GrpcMethod::new("user.User", "ValidateToken"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_all_users(
+        pub async fn refresh_token(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetAllUsersRequest>,
+            request: impl tonic::IntoRequest<super::RefreshTokenRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetAllUsersReply>,
+            tonic::Response<super::RefreshTokenReply>,
             tonic::Status,
         > {
             self.inner
@@ -220,97 +415,387 @@ pub mod user_client {
                     )
                 })?;
             let codec = tonic_prost::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static("/user.User/GetAllUsers");
+            let path = http::uri::PathAndQuery::from_static("/user.User/RefreshToken");
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("user.User", "GetAllUsers"));
+            req.extensions_mut().insert(GrpcMethod::new("user.User", "RefreshToken"));
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod user_server {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with UserServer.
-    #[async_trait]
-    pub trait User: std::marker::Send + std::marker::Sync + 'static {
-        async fn get_google_login_url(
-            &self,
-            request: tonic::Request<super::GetGoogleLoginUrlRequest>,
+        pub async fn logout(
+            &mut self,
+            request: impl tonic::IntoRequest<super::LogoutRequest>,
+        ) -> std::result::Result<tonic::Response<super::LogoutReply>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/user.User/Logout");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("user.User", "Logout"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_users_by_ids(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetUsersByIdsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetGoogleLoginUrlReply>,
+            tonic::Response<super::GetUsersByIdsReply>,
             tonic::Status,
-        >;
-        async fn login(
-            &self,
-            request: tonic::Request<super::LoginRequest>,
-        ) -> std::result::Result<tonic::Response<super::LoginReply>, tonic::Status>;
-        async fn validate_token(
-            &self,
-            request: tonic::Request<super::ValidateTokenRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/user.User/GetUsersByIds");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("user.User", "GetUsersByIds"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn search_users(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SearchUsersRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::ValidateTokenReply>,
+            tonic::Response<super::SearchUsersReply>,
             tonic::Status,
-        >;
-        async fn get_all_users(
-            &self,
-            request: tonic::Request<super::GetAllUsersRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/user.User/SearchUsers");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("user.User", "SearchUsers"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn invite_by_email(
+            &mut self,
+            request: impl tonic::IntoRequest<super::InviteByEmailRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetAllUsersReply>,
+            tonic::Response<super::InviteByEmailReply>,
             tonic::Status,
-        >;
-    }
-    #[derive(Debug)]
-    pub struct UserServer<T> {
-        inner: Arc<T>,
-        accept_compression_encodings: EnabledCompressionEncodings,
-        send_compression_encodings: EnabledCompressionEncodings,
-        max_decoding_message_size: Option<usize>,
-        max_encoding_message_size: Option<usize>,
-    }
-    impl<T> UserServer<T> {
-        pub fn new(inner: T) -> Self {
-            Self::from_arc(Arc::new(inner))
-        }
-        pub fn from_arc(inner: Arc<T>) -> Self {
-            Self {
-                inner,
-                accept_compression_encodings: Default::default(),
-                send_compression_encodings: Default::default(),
-                max_decoding_message_size: None,
-                max_encoding_message_size: None,
-            }
-        }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
-        where
-            F: tonic::service::Interceptor,
-        {
-            InterceptedService::new(Self::new(inner), interceptor)
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/user.User/InviteByEmail");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("user.User", "InviteByEmail"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Enable decompressing requests with the given encoding.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.accept_compression_encodings.enable(encoding);
-            self
+        pub async fn revoke_invitation(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevokeInvitationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RevokeInvitationReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/user.User/RevokeInvitation",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("user.User", "RevokeInvitation"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Compress responses with the given encoding, if the client supports it.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.send_compression_encodings.enable(encoding);
-            self
+        pub async fn get_file_invitations(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetFileInvitationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetFileInvitationsReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/user.User/GetFileInvitations",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("user.User", "GetFileInvitations"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
+        pub async fn create_share_link(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateShareLinkRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateShareLinkReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/user.User/CreateShareLink",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("user.User", "CreateShareLink"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_file_share_links(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetFileShareLinksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetFileShareLinksReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/user.User/GetFileShareLinks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("user.User", "GetFileShareLinks"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn revoke_share_link(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevokeShareLinkRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RevokeShareLinkReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/user.User/RevokeShareLink",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("user.User", "RevokeShareLink"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn resolve_share_link(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResolveShareLinkRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ResolveShareLinkReply>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/user.User/ResolveShareLink",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("user.User", "ResolveShareLink"));
+            self.inner.unary(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod user_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with UserServer.
+    #[async_trait]
+    pub trait User: std::marker::Send + std::marker::Sync + 'static {
+        async fn get_google_login_url(
+            &self,
+            request: tonic::Request<super::GetGoogleLoginUrlRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetGoogleLoginUrlReply>,
+            tonic::Status,
+        >;
+        async fn login(
+            &self,
+            request: tonic::Request<super::LoginRequest>,
+        ) -> std::result::Result<tonic::Response<super::LoginReply>, tonic::Status>;
+        async fn validate_token(
+            &self,
+            request: tonic::Request<super::ValidateTokenRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ValidateTokenReply>,
+            tonic::Status,
+        >;
+        async fn refresh_token(
+            &self,
+            request: tonic::Request<super::RefreshTokenRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RefreshTokenReply>,
+            tonic::Status,
+        >;
+        async fn logout(
+            &self,
+            request: tonic::Request<super::LogoutRequest>,
+        ) -> std::result::Result<tonic::Response<super::LogoutReply>, tonic::Status>;
+        async fn get_users_by_ids(
+            &self,
+            request: tonic::Request<super::GetUsersByIdsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetUsersByIdsReply>,
+            tonic::Status,
+        >;
+        async fn search_users(
+            &self,
+            request: tonic::Request<super::SearchUsersRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SearchUsersReply>,
+            tonic::Status,
+        >;
+        async fn invite_by_email(
+            &self,
+            request: tonic::Request<super::InviteByEmailRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::InviteByEmailReply>,
+            tonic::Status,
+        >;
+        async fn revoke_invitation(
+            &self,
+            request: tonic::Request<super::RevokeInvitationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RevokeInvitationReply>,
+            tonic::Status,
+        >;
+        async fn get_file_invitations(
+            &self,
+            request: tonic::Request<super::GetFileInvitationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetFileInvitationsReply>,
+            tonic::Status,
+        >;
+        async fn create_share_link(
+            &self,
+            request: tonic::Request<super::CreateShareLinkRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateShareLinkReply>,
+            tonic::Status,
+        >;
+        async fn get_file_share_links(
+            &self,
+            request: tonic::Request<super::GetFileShareLinksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetFileShareLinksReply>,
+            tonic::Status,
+        >;
+        async fn revoke_share_link(
+            &self,
+            request: tonic::Request<super::RevokeShareLinkRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RevokeShareLinkReply>,
+            tonic::Status,
+        >;
+        async fn resolve_share_link(
+            &self,
+            request: tonic::Request<super::ResolveShareLinkRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ResolveShareLinkReply>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct UserServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> UserServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
         #[must_use]
         pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
             self.max_decoding_message_size = Some(limit);
@@ -475,23 +960,469 @@ pub mod user_server {
                     };
                     Box::pin(fut)
                 }
-                "/user.User/GetAllUsers" => {
+                "/user.User/RefreshToken" => {
+                    #[allow(non_camel_case_types)]
+                    struct RefreshTokenSvc<T: User>(pub Arc<T>);
+                    impl<T: User> tonic::server::UnaryService<super::RefreshTokenRequest>
+                    for RefreshTokenSvc<T> {
+                        type Response = super::RefreshTokenReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RefreshTokenRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::refresh_token(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RefreshTokenSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/Logout" => {
                     #[allow(non_camel_case_types)]
-                    struct GetAllUsersSvc<T: User>(pub Arc<T>);
-                    impl<T: User> tonic::server::UnaryService<super::GetAllUsersRequest>
-                    for GetAllUsersSvc<T> {
-                        type Response = super::GetAllUsersReply;
+                    struct LogoutSvc<T: User>(pub Arc<T>);
+                    impl<T: User> tonic::server::UnaryService<super::LogoutRequest>
+                    for LogoutSvc<T> {
+                        type Response = super::LogoutReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LogoutRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::logout(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = LogoutSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/GetUsersByIds" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetUsersByIdsSvc<T: User>(pub Arc<T>);
+                    impl<
+                        T: User,
+                    > tonic::server::UnaryService<super::GetUsersByIdsRequest>
+                    for GetUsersByIdsSvc<T> {
+                        type Response = super::GetUsersByIdsReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetUsersByIdsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::get_users_by_ids(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetUsersByIdsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/SearchUsers" => {
+                    #[allow(non_camel_case_types)]
+                    struct SearchUsersSvc<T: User>(pub Arc<T>);
+                    impl<T: User> tonic::server::UnaryService<super::SearchUsersRequest>
+                    for SearchUsersSvc<T> {
+                        type Response = super::SearchUsersReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SearchUsersRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::search_users(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = SearchUsersSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/InviteByEmail" => {
+                    #[allow(non_camel_case_types)]
+                    struct InviteByEmailSvc<T: User>(pub Arc<T>);
+                    impl<
+                        T: User,
+                    > tonic::server::UnaryService<super::InviteByEmailRequest>
+                    for InviteByEmailSvc<T> {
+                        type Response = super::InviteByEmailReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::InviteByEmailRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::invite_by_email(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = InviteByEmailSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/RevokeInvitation" => {
+                    #[allow(non_camel_case_types)]
+                    struct RevokeInvitationSvc<T: User>(pub Arc<T>);
+                    impl<
+                        T: User,
+                    > tonic::server::UnaryService<super::RevokeInvitationRequest>
+                    for RevokeInvitationSvc<T> {
+                        type Response = super::RevokeInvitationReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevokeInvitationRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::revoke_invitation(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RevokeInvitationSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/GetFileInvitations" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetFileInvitationsSvc<T: User>(pub Arc<T>);
+                    impl<
+                        T: User,
+                    > tonic::server::UnaryService<super::GetFileInvitationsRequest>
+                    for GetFileInvitationsSvc<T> {
+                        type Response = super::GetFileInvitationsReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetFileInvitationsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::get_file_invitations(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetFileInvitationsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/CreateShareLink" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateShareLinkSvc<T: User>(pub Arc<T>);
+                    impl<
+                        T: User,
+                    > tonic::server::UnaryService<super::CreateShareLinkRequest>
+                    for CreateShareLinkSvc<T> {
+                        type Response = super::CreateShareLinkReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateShareLinkRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::create_share_link(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateShareLinkSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/GetFileShareLinks" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetFileShareLinksSvc<T: User>(pub Arc<T>);
+                    impl<
+                        T: User,
+                    > tonic::server::UnaryService<super::GetFileShareLinksRequest>
+                    for GetFileShareLinksSvc<T> {
+                        type Response = super::GetFileShareLinksReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetFileShareLinksRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::get_file_share_links(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetFileShareLinksSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/RevokeShareLink" => {
+                    #[allow(non_camel_case_types)]
+                    struct RevokeShareLinkSvc<T: User>(pub Arc<T>);
+                    impl<
+                        T: User,
+                    > tonic::server::UnaryService<super::RevokeShareLinkRequest>
+                    for RevokeShareLinkSvc<T> {
+                        type Response = super::RevokeShareLinkReply;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevokeShareLinkRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as User>::revoke_share_link(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RevokeShareLinkSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/user.User/ResolveShareLink" => {
+                    #[allow(non_camel_case_types)]
+                    struct ResolveShareLinkSvc<T: User>(pub Arc<T>);
+                    impl<
+                        T: User,
+                    > tonic::server::UnaryService<super::ResolveShareLinkRequest>
+                    for ResolveShareLinkSvc<T> {
+                        type Response = super::ResolveShareLinkReply;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetAllUsersRequest>,
+                            request: tonic::Request<super::ResolveShareLinkRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as User>::get_all_users(&inner, request).await
+                                <T as User>::resolve_share_link(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -502,7 +1433,7 @@ pub mod user_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetAllUsersSvc(inner);
+                        let method = ResolveShareLinkSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
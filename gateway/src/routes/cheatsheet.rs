@@ -25,6 +25,36 @@ pub fn cheatsheet_routes() -> Router<CheatsheetHandler> {
             get(handlers::cheatsheet::get_file),
         )
         .route("/cheatsheet/share", post(handlers::cheatsheet::share))
+        .route(
+            "/cheatsheet/share/bulk",
+            post(handlers::cheatsheet::bulk_share),
+        )
         .route("/cheatsheet/unshare", post(handlers::cheatsheet::unshare))
+        .route(
+            "/cheatsheet/files/{file_id}/links",
+            get(handlers::cheatsheet::get_share_links)
+                .post(handlers::cheatsheet::create_share_link),
+        )
+        .route(
+            "/cheatsheet/files/{file_id}/links/{link_id}",
+            delete(handlers::cheatsheet::revoke_share_link),
+        )
         .route("/cheatsheet/generate", post(handlers::cheatsheet::generate))
+        .route(
+            "/cheatsheet/generate/{job_id}",
+            get(handlers::cheatsheet::get_generation)
+                .delete(handlers::cheatsheet::cancel_generation),
+        )
+        .route(
+            "/cheatsheet/generate/{job_id}/events",
+            get(handlers::cheatsheet::generation_events),
+        )
+}
+
+/// Cheatsheet routes that need no authentication
+pub fn public_cheatsheet_routes() -> Router<CheatsheetHandler> {
+    Router::new().route(
+        "/cheatsheet/links/{token}",
+        post(handlers::cheatsheet::resolve_share_link),
+    )
 }
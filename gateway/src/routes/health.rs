@@ -0,0 +1,9 @@
+use crate::handlers;
+use crate::handlers::health::HealthHandler;
+use axum::{Router, routing::get};
+
+pub fn health_routes() -> Router<HealthHandler> {
+    Router::new()
+        .route("/healthz", get(handlers::health::healthz))
+        .route("/readyz", get(handlers::health::readyz))
+}
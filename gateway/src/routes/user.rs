@@ -0,0 +1,7 @@
+use crate::handlers;
+use crate::handlers::user::UserHandler;
+use axum::{Router, routing::get};
+
+pub fn user_routes() -> Router<UserHandler> {
+    Router::new().route("/user/search", get(handlers::user::search_users))
+}
@@ -1,61 +1,280 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-use log::error;
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use futures_util::{StreamExt, TryStreamExt, future, stream};
+use hyper::{HeaderMap, StatusCode};
+use openexam_common::request_signer::RequestSigner;
+use rand::Rng;
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+use tracing::{Instrument, debug, error, info_span, instrument, warn};
 
 use crate::{
-    dtos,
-    services::{response::ApiResponse, types, user::UserService},
+    config::config::{CheatsheetClientConfig, GenerationConfig, ShareLinkConfig},
+    dtos::{self, ApiError, ErrorCode, FieldError, ShareRole},
+    metrics,
+    middleware::request_id::{self, REQUEST_ID_HEADER},
+    services::{
+        circuit_breaker::CircuitBreaker,
+        file_listing,
+        generation_jobs::GenerationJobs,
+        rate_limiter::{FixedWindowLimiter, RateLimitDecision},
+        response::ApiResponse,
+        types,
+        user::UserService,
+    },
+    telemetry,
 };
 
-#[derive(Debug, Clone)]
+/// Most file and recipient pairs one bulk share request may cover
+const MAX_BULK_SHARE_PAIRS: usize = 500;
+/// Shares a bulk request has in flight against the cheatsheet API at once
+const BULK_SHARE_CONCURRENCY: usize = 8;
+/// Most files one generation may combine
+pub const MAX_GENERATE_FILES: usize = 20;
+/// Role checks a generation request has in flight against the cheatsheet API at once
+const GENERATE_AUTHORIZE_CONCURRENCY: usize = 8;
+/// Shortest password a public link may be protected with
+const MIN_LINK_PASSWORD_CHARS: usize = 8;
+
+#[derive(Clone)]
 pub struct CheatsheetService {
     cheatsheet_api_url: String,
     client: reqwest::Client,
+    signer: RequestSigner,
+    policy: CheatsheetClientConfig,
+    breaker: Arc<CircuitBreaker>,
+    jobs: Arc<GenerationJobs>,
+    progress_poll: Duration,
+    /// Generations per user per UTC day, when a quota is configured
+    daily_quota: Option<Arc<FixedWindowLimiter>>,
+    /// Lifetime of the download URLs public links resolve to
+    share_link_url_ttl_secs: u64,
     user_service: UserService,
 }
 
+impl std::fmt::Debug for CheatsheetService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CheatsheetService")
+            .field("cheatsheet_api_url", &self.cheatsheet_api_url)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
 impl CheatsheetService {
-    pub fn new(cheatsheet_api_url: String, user_service: UserService) -> Self {
-        Self {
+    pub fn new(
+        cheatsheet_api_url: String,
+        signer: RequestSigner,
+        policy: CheatsheetClientConfig,
+        generation: GenerationConfig,
+        share_links: ShareLinkConfig,
+        user_service: UserService,
+    ) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_millis(policy.connect_timeout_ms))
+            .timeout(Duration::from_secs(policy.request_timeout_secs))
+            .build()?;
+        let jobs = Arc::new(GenerationJobs::new(
+            generation.max_concurrent,
+            Duration::from_secs(generation.job_ttl_secs),
+            generation.max_active_per_user,
+        ));
+        jobs.spawn_purger();
+        let breaker = CircuitBreaker::new(
+            "cheatsheet",
+            policy.breaker_failure_threshold,
+            Duration::from_secs(policy.breaker_open_secs),
+        );
+
+        Ok(Self {
             cheatsheet_api_url,
-            client: reqwest::Client::new(),
+            client,
+            signer,
+            policy,
+            breaker: Arc::new(breaker),
+            jobs,
+            progress_poll: Duration::from_millis(generation.progress_poll_ms),
+            daily_quota: (generation.daily_quota > 0).then(|| {
+                Arc::new(FixedWindowLimiter::new(
+                    generation.daily_quota,
+                    Duration::from_secs(24 * 60 * 60),
+                ))
+            }),
+            share_link_url_ttl_secs: share_links.url_ttl_secs,
             user_service,
-        }
+        })
     }
 
+    /// Sends a request once. Used for calls that must not be repeated blindly
     async fn send_request(
         &self,
+        operation: &'static str,
         request: reqwest::RequestBuilder,
-    ) -> Result<reqwest::Response, String> {
-        request.send().await.map_err(|e| {
-            error!("HTTP request error: {:?}", e);
-            format!("Request failed: {:?}", e)
-        })
+    ) -> Result<reqwest::Response, ApiError> {
+        let result = self.attempt(operation, request).await?;
+        result.map_err(|e| self.transport_error(e))
     }
 
-    async fn parse_json<T: serde::de::DeserializeOwned>(
+    /// Sends a request that is safe to repeat, retrying connection failures, timeouts and
+    /// 502/503/504 responses with jittered exponential backoff
+    async fn send_idempotent(
         &self,
-        response: reqwest::Response,
-    ) -> Result<T, (u16, String)> {
-        let status_code = response.status().as_u16();
+        operation: &'static str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, ApiError> {
+        let mut retries = 0;
+        loop {
+            let Some(this_try) = request.try_clone() else {
+                return self.send_request(operation, request).await;
+            };
 
-        if !response.status().is_success() {
-            // Try to get error message from response body
-            let error_body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
+            let result = self.attempt(operation, this_try).await?;
+            let retryable = match &result {
+                Ok(response) => matches!(
+                    response.status(),
+                    StatusCode::BAD_GATEWAY
+                        | StatusCode::SERVICE_UNAVAILABLE
+                        | StatusCode::GATEWAY_TIMEOUT
+                ),
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if !retryable || retries >= self.policy.max_retries {
+                return result.map_err(|e| self.transport_error(e));
+            }
+
+            retries += 1;
+            let delay = self.backoff(retries);
+            warn!(
+                "Cheatsheet {} failed, retry {}/{} in {:?}",
+                operation, retries, self.policy.max_retries, delay
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// A single attempt guarded by the circuit breaker. The outer error means the call was
+    /// rejected without being sent; the inner result is what the transport returned
+    async fn attempt(
+        &self,
+        operation: &'static str,
+        request: reqwest::RequestBuilder,
+    ) -> Result<Result<reqwest::Response, reqwest::Error>, ApiError> {
+        if !self.breaker.allow() {
+            metrics::record_upstream_call("cheatsheet", operation, "circuit_open", Duration::ZERO);
+            return Err(ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::ServiceUnavailable,
+                "Cheatsheet service is temporarily unavailable",
+            ));
+        }
 
+        let mut headers = HeaderMap::new();
+        if let Some(id) = request_id::current()
+            && let Ok(value) = id.parse()
+        {
+            headers.insert(REQUEST_ID_HEADER, value);
+        }
+        telemetry::inject_http_headers(&mut headers);
+
+        // signed per attempt, so retries carry a fresh timestamp
+        let started = Instant::now();
+        let result = match request.headers(headers).build() {
+            Ok(mut request) => {
+                self.signer.sign(&mut request);
+                self.client.execute(request).await
+            }
+            Err(e) => Err(e),
+        };
+        let outcome = match &result {
+            Ok(response) => response.status().as_u16().to_string(),
+            Err(e) if e.is_timeout() => "timeout".to_string(),
+            Err(_) => "error".to_string(),
+        };
+        metrics::record_upstream_call("cheatsheet", operation, &outcome, started.elapsed());
+
+        match &result {
+            Ok(response) if !response.status().is_server_error() => self.breaker.record_success(),
+            _ => self.breaker.record_failure(),
+        }
+        Ok(result)
+    }
+
+    fn transport_error(&self, e: reqwest::Error) -> ApiError {
+        error!("HTTP request error: {:?}", e);
+        if e.is_timeout() {
+            ApiError::new(
+                StatusCode::GATEWAY_TIMEOUT,
+                ErrorCode::DeadlineExceeded,
+                "Cheatsheet service timed out",
+            )
+        } else {
+            ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::ServiceUnavailable,
+                "Cheatsheet service is unavailable",
+            )
+        }
+    }
+
+    /// Full jitter: a random delay up to `base * 2^(retry - 1)`
+    fn backoff(&self, retry: u32) -> Duration {
+        let cap = self
+            .policy
+            .retry_base_delay_ms
+            .saturating_mul(1 << (retry - 1).min(10));
+        Duration::from_millis(rand::thread_rng().gen_range(0..=cap))
+    }
+
+    /// Turns a non-2xx response from the cheatsheet API into an [`ApiError`].
+    /// Client errors keep the backend's message; server errors are not exposed.
+    async fn upstream_error(&self, response: reqwest::Response) -> ApiError {
+        let status_code = response.status();
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        if status_code.is_client_error() {
+            warn!(
+                "Request rejected with status {}: {}",
+                status_code, error_body
+            );
+            let message = serde_json::from_str::<types::ErrorResponse>(&error_body)
+                .map(|body| body.error)
+                .unwrap_or_else(|_| "Request rejected by cheatsheet service".to_string());
+            ApiError::from_status(status_code.as_u16(), &message)
+        } else {
             error!("Request failed with status {}: {}", status_code, error_body);
-            return Err((status_code, error_body));
+            ApiError::new(
+                StatusCode::BAD_GATEWAY,
+                ErrorCode::UpstreamError,
+                "Cheatsheet service failed to handle the request",
+            )
+        }
+    }
+
+    async fn parse_json<T: serde::de::DeserializeOwned>(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<T, ApiError> {
+        if !response.status().is_success() {
+            return Err(self.upstream_error(response).await);
         }
 
         response.json::<T>().await.map_err(|e| {
             error!("Failed to parse response: {:?}", e);
-            (status_code, format!("Failed to parse response: {:?}", e))
+            ApiError::new(
+                StatusCode::BAD_GATEWAY,
+                ErrorCode::UpstreamError,
+                "Unexpected response from cheatsheet service",
+            )
         })
     }
 
+    #[instrument(name = "cheatsheet.get_presigned_upload_url", skip_all)]
     pub async fn get_presigned_upload_url(
         &self,
         filename: String,
@@ -68,15 +287,15 @@ impl CheatsheetService {
         );
 
         let request = self.client.get(&url).header("X-User-Id", user_id);
-        let response = match self.send_request(request).await {
+        let response = match self.send_request("get_presigned_upload_url", request).await {
             Ok(r) => r,
-            Err(e) => return ApiResponse::internal_error(&e),
+            Err(e) => return e.into(),
         };
 
         let data: types::ServiceResponse<types::GetPresignedUploadUrlData> =
             match self.parse_json(response).await {
                 Ok(d) => d,
-                Err((status, msg)) => return ApiResponse::error(status, &msg),
+                Err(e) => return e.into(),
             };
 
         let result = dtos::GetPresignedUploadUrlResponse {
@@ -87,42 +306,95 @@ impl CheatsheetService {
         ApiResponse::ok(result)
     }
 
+    #[instrument(name = "cheatsheet.get_presigned_get_url", skip_all)]
     pub async fn get_presigned_get_url(
         &self,
         key: String,
         user_id: String,
     ) -> ApiResponse<dtos::GetPresignedGetUrlResponse> {
-        let url = format!(
+        if let Err(e) = self
+            .require_role_for_key(&user_id, &key, ShareRole::Viewer, "download it")
+            .await
+        {
+            return e.into();
+        }
+
+        match self.presign_get(&key, &user_id, None).await {
+            Ok(data) => ApiResponse::ok(dtos::GetPresignedGetUrlResponse {
+                expires_in: data.expiresIn.to_string(),
+                url: data.url,
+            }),
+            Err(e) => e.into(),
+        }
+    }
+
+    /// Download URL for `key`, valid for `ttl_secs` or the cheatsheet API's default
+    async fn presign_get(
+        &self,
+        key: &str,
+        user_id: &str,
+        ttl_secs: Option<u64>,
+    ) -> Result<types::GetPresignedGetUrlData, ApiError> {
+        let mut url = format!(
             "{}/files/presign?key={}",
             self.cheatsheet_api_url,
-            urlencoding::encode(&key)
+            urlencoding::encode(key)
         );
+        if let Some(ttl_secs) = ttl_secs {
+            url.push_str(&format!("&ttl={}", ttl_secs));
+        }
 
         let request = self.client.get(&url).header("X-User-Id", user_id);
-        let response = match self.send_request(request).await {
-            Ok(r) => r,
-            Err(e) => return ApiResponse::internal_error(&e),
-        };
-
+        let response = self
+            .send_idempotent("get_presigned_get_url", request)
+            .await?;
         let data: types::ServiceResponse<types::GetPresignedGetUrlData> =
-            match self.parse_json(response).await {
-                Ok(d) => d,
-                Err((status, msg)) => return ApiResponse::error(status, &msg),
-            };
-
-        let result = dtos::GetPresignedGetUrlResponse {
-            expires_in: data.data.expiresIn.to_string(),
-            url: data.data.url,
-        };
-        ApiResponse::ok(result)
+            self.parse_json(response).await?;
+        Ok(data.data)
     }
 
+    /// Removes one of the caller's files, or with `file_id` a file the caller co-owns
+    #[instrument(name = "cheatsheet.remove_file", skip_all)]
     pub async fn remove_file(
         &self,
         file_type: String,
         file: String,
+        file_id: Option<String>,
         user_id: String,
     ) -> ApiResponse<types::EmptyResponse> {
+        // keys are `{file_type}/{owner id}/{file}`
+        let owner_id = match file_id {
+            Some(file_id) => {
+                let data = match self
+                    .require_role(&user_id, &file_id, ShareRole::CoOwner, "remove it")
+                    .await
+                {
+                    Ok((data, _)) => data,
+                    Err(e) => return e.into(),
+                };
+                let record = data.data.file;
+                if record.Key != format!("{}/{}/{}", file_type, record.UserID, file) {
+                    return ApiError::bad_request("Invalid remove request")
+                        .with_details(vec![FieldError::new(
+                            "file",
+                            "file_type and file do not match the file with this file_id",
+                        )])
+                        .into();
+                }
+                record.UserID
+            }
+            None => {
+                let key = format!("{}/{}/{}", file_type, user_id, file);
+                if let Err(e) = self
+                    .require_role_for_key(&user_id, &key, ShareRole::CoOwner, "remove it")
+                    .await
+                {
+                    return e.into();
+                }
+                user_id.clone()
+            }
+        };
+
         let url = format!("{}/files", self.cheatsheet_api_url);
 
         let request = self
@@ -131,76 +403,121 @@ impl CheatsheetService {
             .header("X-User-Id", &user_id)
             .query(&[
                 ("file_type", file_type),
-                ("user_id", user_id),
+                ("user_id", owner_id),
                 ("file", file),
             ]);
 
-        let response = match self.send_request(request).await {
+        let response = match self.send_request("remove_file", request).await {
             Ok(r) => r,
-            Err(e) => return ApiResponse::internal_error(&e),
+            Err(e) => return e.into(),
         };
 
         if !response.status().is_success() {
-            let status = response.status();
-            error!("Failed to delete file: status {}", status);
-            return ApiResponse::internal_error(&format!(
-                "Failed to delete file: status {}",
-                status
-            ));
+            return self.upstream_error(response).await.into();
         }
 
         ApiResponse::ok(types::EmptyResponse {})
     }
 
-    pub async fn get_all_files(&self, user_id: String) -> ApiResponse<dtos::GetAllFilesResponse> {
-        let url = format!("{}/files", self.cheatsheet_api_url);
-
-        let request = self.client.get(&url).header("X-User-Id", user_id);
-        let response = match self.send_request(request).await {
-            Ok(r) => r,
-            Err(e) => return ApiResponse::internal_error(&e),
+    /// Lists the files the user owns or can see. The cheatsheet API filters, sorts and pages
+    /// them; see [`file_listing::upstream_query`].
+    #[instrument(name = "cheatsheet.get_all_files", skip_all)]
+    pub async fn get_all_files(
+        &self,
+        user_id: String,
+        query: dtos::ListFilesQuery,
+    ) -> ApiResponse<dtos::GetAllFilesResponse> {
+        let upstream = match file_listing::upstream_query(&query) {
+            Ok(upstream) => upstream,
+            Err(e) => return e.into(),
         };
-
-        let data: types::ServiceResponse<types::FilesData> = match self.parse_json(response).await {
+        let data = match self.fetch_files(&user_id, &upstream).await {
             Ok(d) => d,
-            Err((status, msg)) => return ApiResponse::error(status, &msg),
+            Err(e) => return e.into(),
         };
 
+        // files shared with the caller through a share that has expired are hidden
+        let now = now();
+        let shares: HashMap<String, types::Share> = data
+            .shares
+            .into_iter()
+            .map(|share| (share.FileID.clone(), share))
+            .collect();
         let files = data
-            .data
             .files
             .into_iter()
-            .map(|f| dtos::File {
-                id: f.ID,
-                user_id: f.UserID,
-                created_at: f.CreatedAt,
-                name: f.Name,
-                key: f.Key,
+            .filter_map(|f| {
+                let share = shares.get(&f.ID).filter(|_| f.UserID != user_id);
+                if share.is_some_and(|share| share_expired(share, now)) {
+                    return None;
+                }
+                Some(dtos::File {
+                    expires_at: share.and_then(|share| share.ExpiresAt.clone()),
+                    id: f.ID,
+                    user_id: f.UserID,
+                    created_at: f.CreatedAt,
+                    name: f.Name,
+                    key: f.Key,
+                })
             })
             .collect();
 
-        ApiResponse::ok(dtos::GetAllFilesResponse { files })
+        ApiResponse::ok(dtos::GetAllFilesResponse {
+            files,
+            total: data.total,
+            next_cursor: data.next_cursor,
+        })
     }
 
+    #[instrument(name = "cheatsheet.get_file", skip_all)]
     pub async fn get_file(
         &self,
         user_id: String,
         file_id: String,
     ) -> ApiResponse<dtos::GetFileResponse> {
-        let url = format!("{}/files/{}", self.cheatsheet_api_url, file_id);
-
-        let request = self.client.get(&url).header("X-User-Id", user_id);
-        let response = match self.send_request(request).await {
-            Ok(r) => r,
-            Err(e) => return ApiResponse::internal_error(&e),
+        let data = match self.fetch_file(&user_id, &file_id).await {
+            Ok(d) => d,
+            Err(e) => return e.into(),
         };
 
-        let data: types::ServiceResponse<types::FileData> = match self.parse_json(response).await {
-            Ok(d) => d,
-            Err((status, msg)) => return ApiResponse::error(status, &msg),
+        let caller_role = role_of(&data.data, &user_id);
+        if caller_role.is_none() {
+            return ApiError::new(
+                StatusCode::FORBIDDEN,
+                ErrorCode::PermissionDenied,
+                "Your access to this file has expired",
+            )
+            .into();
+        }
+        let invitations = if caller_role >= Some(ShareRole::CoOwner) {
+            match self
+                .user_service
+                .get_file_invitations(user_id.clone(), file_id)
+                .await
+            {
+                ApiResponse::Success(invitations) => invitations,
+                e => return e.cast_error(),
+            }
+        } else {
+            Vec::new()
         };
 
+        let now = now();
+        let caller_share_expires_at = data
+            .data
+            .shares
+            .iter()
+            .find(|share| share.UserID == user_id && data.data.file.UserID != user_id)
+            .and_then(|share| share.ExpiresAt.clone());
+        let shares: Vec<types::Share> = data
+            .data
+            .shares
+            .into_iter()
+            .filter(|share| !share_expired(share, now))
+            .collect();
+
         let file = dtos::File {
+            expires_at: caller_share_expires_at,
             id: data.data.file.ID,
             user_id: data.data.file.UserID,
             created_at: data.data.file.CreatedAt,
@@ -208,81 +525,372 @@ impl CheatsheetService {
             key: data.data.file.Key,
         };
 
-        let users = match self.user_service.get_all_users().await {
-            ApiResponse::Success(u) => u.users,
-            ApiResponse::Error { status, message } => return ApiResponse::error(status, &message),
+        let share_user_ids = shares.iter().map(|share| share.UserID.clone()).collect();
+        let user_id_to_name = match self.user_service.get_user_names(share_user_ids).await {
+            ApiResponse::Success(names) => names,
+            e => return e.cast_error(),
         };
-        let user_id_to_name = users
-            .iter()
-            .map(|u| (u.id.clone(), u.name.clone()))
-            .collect::<HashMap<String, String>>();
 
-        let shares = data
-            .data
-            .shares
+        let shares = shares
             .into_iter()
             .map(|f| dtos::Share {
-                user_id: f.UserID.clone(),
-                name: user_id_to_name
-                    .get(&f.UserID.clone())
-                    .unwrap_or(&"".to_string())
-                    .clone(),
+                name: user_id_to_name.get(&f.UserID).cloned().unwrap_or_default(),
+                role: if f.UserID == file.user_id {
+                    ShareRole::Owner
+                } else {
+                    ShareRole::parse(&f.Role)
+                },
+                expires_at: f.ExpiresAt,
+                user_id: f.UserID,
             })
             .collect();
 
-        ApiResponse::ok(dtos::GetFileResponse { file, shares })
+        ApiResponse::ok(dtos::GetFileResponse {
+            file,
+            shares,
+            invitations,
+        })
+    }
+
+    /// Files the user can see, along with the user's own shares
+    async fn fetch_files(
+        &self,
+        user_id: &str,
+        query: &[(&str, String)],
+    ) -> Result<types::FilesData, ApiError> {
+        let url = format!("{}/files", self.cheatsheet_api_url);
+
+        let request = self
+            .client
+            .get(&url)
+            .query(query)
+            .header("X-User-Id", user_id);
+        let response = self.send_idempotent("get_all_files", request).await?;
+        let data: types::ServiceResponse<types::FilesData> = self.parse_json(response).await?;
+        Ok(data.data)
+    }
+
+    /// Looks up the file stored under `key` and fails unless the user owns it or holds at
+    /// least the `needed` role through an active share. The cheatsheet API acts on any key it
+    /// is given, so unknown keys are rejected the same way as other users' files and cannot
+    /// be used to probe for them.
+    async fn require_role_for_key(
+        &self,
+        user_id: &str,
+        key: &str,
+        needed: ShareRole,
+        action: &str,
+    ) -> Result<types::File, ApiError> {
+        let denied = || {
+            ApiError::new(
+                StatusCode::FORBIDDEN,
+                ErrorCode::PermissionDenied,
+                &format!(
+                    "You need the {} role on this file to {}",
+                    needed.as_str(),
+                    action
+                ),
+            )
+        };
+
+        let data = match self.fetch_file_by_key(user_id, key).await {
+            Ok(data) => data,
+            Err(e) if e.status_code().is_client_error() => return Err(denied()),
+            Err(e) => return Err(e),
+        };
+        match role_of(&data.data, user_id) {
+            Some(role) if role >= needed => Ok(data.data.file),
+            _ => Err(denied()),
+        }
+    }
+
+    async fn fetch_file_by_key(
+        &self,
+        user_id: &str,
+        key: &str,
+    ) -> Result<types::ServiceResponse<types::FileData>, ApiError> {
+        let url = format!("{}/files/by-key", self.cheatsheet_api_url);
+
+        let request = self
+            .client
+            .get(&url)
+            .header("X-User-Id", user_id)
+            .query(&[("key", key)]);
+        let response = self.send_idempotent("get_file_by_key", request).await?;
+        self.parse_json(response).await
+    }
+
+    async fn fetch_file(
+        &self,
+        user_id: &str,
+        file_id: &str,
+    ) -> Result<types::ServiceResponse<types::FileData>, ApiError> {
+        let url = format!("{}/files/{}", self.cheatsheet_api_url, file_id);
+
+        let request = self.client.get(&url).header("X-User-Id", user_id);
+        let response = self.send_idempotent("get_file", request).await?;
+        self.parse_json(response).await
+    }
+
+    /// Fails unless `user_id` holds at least the `needed` role on the file. Returns the file
+    /// and the role the user holds, which owners see as [`ShareRole::Owner`]. Files that do
+    /// not exist are rejected like files the user cannot access.
+    async fn require_role(
+        &self,
+        user_id: &str,
+        file_id: &str,
+        needed: ShareRole,
+        action: &str,
+    ) -> Result<FileAccess, ApiError> {
+        let denied = || {
+            ApiError::new(
+                StatusCode::FORBIDDEN,
+                ErrorCode::PermissionDenied,
+                &format!(
+                    "You need the {} role on file {} to {}",
+                    needed.as_str(),
+                    file_id,
+                    action
+                ),
+            )
+        };
+
+        let data = match self.fetch_file(user_id, file_id).await {
+            Ok(data) => data,
+            Err(e) if e.status_code().is_client_error() => return Err(denied()),
+            Err(e) => return Err(e),
+        };
+        match role_of(&data.data, user_id) {
+            Some(role) if role >= needed => Ok((data, role)),
+            _ => Err(denied()),
+        }
+    }
+
+    /// Checks that the user may generate from every file, i.e. holds the editor role on it
+    pub async fn authorize_generation(
+        &self,
+        user_id: &str,
+        file_ids: &[String],
+    ) -> Result<(), ApiError> {
+        stream::iter(unique(file_ids.to_vec()))
+            .map(|file_id| async move {
+                self.require_role(user_id, &file_id, ShareRole::Editor, "generate from it")
+                    .await
+            })
+            .buffer_unordered(GENERATE_AUTHORIZE_CONCURRENCY)
+            .try_for_each(|_| future::ok(()))
+            .await
     }
 
+    /// Shares a file as its owner or as a user holding at least the resharer role, who may
+    /// grant up to their own role
+    #[instrument(name = "cheatsheet.share", skip_all)]
     pub async fn share(
         &self,
-        owner_id: String,
-        user_id: String,
+        sharer_id: String,
+        request: dtos::ShareRequest,
+    ) -> ApiResponse<dtos::ShareResponse> {
+        let file_id = request.file_id;
+        let role = request.role;
+        let target = match share_target(request.user_id, request.email) {
+            Ok(target) => target,
+            Err(e) => return e.into(),
+        };
+        let expires_at = match share_expiry(request.expires_at, request.expires_in_secs) {
+            Ok(expires_at) => expires_at,
+            Err(e) => return e.into(),
+        };
+        if role == ShareRole::Owner {
+            return ApiError::bad_request("Invalid share request")
+                .with_details(vec![FieldError::new("role", "owner cannot be granted")])
+                .into();
+        }
+
+        let access = match self
+            .require_role(&sharer_id, &file_id, ShareRole::Resharer, "share it")
+            .await
+        {
+            Ok(access) => access,
+            Err(e) => return e.into(),
+        };
+        self.share_with_access(sharer_id, file_id, target, role, expires_at, &access)
+            .await
+    }
+
+    /// Shares a file the sharer already holds at least the resharer role on
+    async fn share_with_access(
+        &self,
+        sharer_id: String,
         file_id: String,
+        target: ShareTarget,
+        role: ShareRole,
+        expires_at: Option<String>,
+        (data, sharer_role): &FileAccess,
     ) -> ApiResponse<dtos::ShareResponse> {
+        let sharer_role = *sharer_role;
+        if role > sharer_role {
+            return ApiError::new(
+                StatusCode::FORBIDDEN,
+                ErrorCode::PermissionDenied,
+                "You cannot grant a role above your own",
+            )
+            .into();
+        }
+
+        let user_id = match target {
+            ShareTarget::User(user_id) => user_id,
+            ShareTarget::Email(email) => {
+                match self
+                    .user_service
+                    .invite_by_email(
+                        email,
+                        file_id.clone(),
+                        sharer_id.clone(),
+                        role,
+                        expires_at.clone(),
+                    )
+                    .await
+                {
+                    ApiResponse::Success(Some(user_id)) => user_id,
+                    ApiResponse::Success(None) => {
+                        return ApiResponse::ok(dtos::ShareResponse {
+                            shared: false,
+                            pending: true,
+                        });
+                    }
+                    e => return e.cast_error(),
+                }
+            }
+        };
+
+        if user_id == sharer_id {
+            return ApiError::bad_request("You cannot share a file with yourself").into();
+        }
+        match role_of(&data.data, &user_id) {
+            Some(ShareRole::Owner) => {
+                return ApiError::bad_request("The owner already has access to this file").into();
+            }
+            Some(current) if current > sharer_role => {
+                return ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    ErrorCode::PermissionDenied,
+                    "You cannot change the role of a user above you",
+                )
+                .into();
+            }
+            _ => {}
+        }
+
         let url = format!("{}/share", self.cheatsheet_api_url);
 
         let request = self
             .client
             .post(&url)
-            .header("X-User-Id", owner_id)
-            .form(&[("user_id", user_id), ("file_id", file_id)]);
-        let response = match self.send_request(request).await {
+            .header("X-User-Id", sharer_id)
+            .form(&[
+                ("user_id", user_id),
+                ("file_id", file_id),
+                ("role", role.as_str().to_string()),
+                ("expires_at", expires_at.unwrap_or_default()),
+            ]);
+        let response = match self.send_request("share", request).await {
             Ok(r) => r,
-            Err(e) => return ApiResponse::internal_error(&e),
+            Err(e) => return e.into(),
         };
 
         let data: types::ServiceResponse<types::ShareData> = match self.parse_json(response).await {
             Ok(d) => d,
-            Err((status, msg)) => return ApiResponse::error(status, &msg),
+            Err(e) => return e.into(),
         };
 
         ApiResponse::ok(dtos::ShareResponse {
             shared: data.data.shared,
+            pending: false,
         })
     }
 
+    /// Unshares a file as its owner or a co-owner
+    #[instrument(name = "cheatsheet.unshare", skip_all)]
     pub async fn unshare(
         &self,
-        owner_id: String,
-        user_id: String,
+        sharer_id: String,
+        request: dtos::UnshareRequest,
+    ) -> ApiResponse<dtos::UnshareResponse> {
+        let file_id = request.file_id;
+        let target = match share_target(request.user_id, request.email) {
+            Ok(target) => target,
+            Err(e) => return e.into(),
+        };
+        let access = match self
+            .require_role(&sharer_id, &file_id, ShareRole::CoOwner, "unshare it")
+            .await
+        {
+            Ok(access) => access,
+            Err(e) => return e.into(),
+        };
+        self.unshare_with_access(sharer_id, file_id, target, &access)
+            .await
+    }
+
+    /// Unshares a file the sharer already holds at least the co-owner role on
+    async fn unshare_with_access(
+        &self,
+        sharer_id: String,
         file_id: String,
+        target: ShareTarget,
+        (data, sharer_role): &FileAccess,
     ) -> ApiResponse<dtos::UnshareResponse> {
+        let sharer_role = *sharer_role;
+        let user_id = match target {
+            ShareTarget::User(user_id) => user_id,
+            ShareTarget::Email(email) => {
+                return match self
+                    .user_service
+                    .revoke_invitation(sharer_id, email, file_id)
+                    .await
+                {
+                    ApiResponse::Success(true) => {
+                        ApiResponse::ok(dtos::UnshareResponse { unshared: true })
+                    }
+                    ApiResponse::Success(false) => {
+                        ApiResponse::not_found("No pending invitation for this email")
+                    }
+                    e => e.cast_error(),
+                };
+            }
+        };
+
+        match role_of(&data.data, &user_id) {
+            Some(ShareRole::Owner) => {
+                return ApiError::bad_request("The owner cannot be removed from their file").into();
+            }
+            Some(current) if current > sharer_role => {
+                return ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    ErrorCode::PermissionDenied,
+                    "You cannot remove a user above you",
+                )
+                .into();
+            }
+            _ => {}
+        }
+
         let url = format!("{}/unshare", self.cheatsheet_api_url);
 
         let request = self
             .client
             .post(&url)
-            .header("X-User-Id", owner_id)
+            .header("X-User-Id", sharer_id)
             .query(&[("user_id", user_id), ("file_id", file_id)]);
-        let response = match self.send_request(request).await {
+        let response = match self.send_request("unshare", request).await {
             Ok(r) => r,
-            Err(e) => return ApiResponse::internal_error(&e),
+            Err(e) => return e.into(),
         };
 
         let data: types::ServiceResponse<types::UnshareData> = match self.parse_json(response).await
         {
             Ok(d) => d,
-            Err((status, msg)) => return ApiResponse::error(status, &msg),
+            Err(e) => return e.into(),
         };
 
         ApiResponse::ok(dtos::UnshareResponse {
@@ -290,33 +898,895 @@ impl CheatsheetService {
         })
     }
 
-    pub async fn generate(
+    /// Shares or unshares every file with every recipient. Pairs are independent: a pair that
+    /// fails is reported in its result and does not stop the others.
+    #[instrument(name = "cheatsheet.bulk_share", skip_all)]
+    pub async fn bulk_share(
+        &self,
+        sharer_id: String,
+        request: dtos::BulkShareRequest,
+    ) -> ApiResponse<dtos::BulkShareResponse> {
+        let file_ids = unique(request.file_ids);
+        let recipients = unique(request.recipients);
+
+        // resolved once, so a duration means the same end time for every pair
+        let expires_at = match request.action {
+            dtos::BulkShareAction::Share => {
+                match share_expiry(request.expires_at, request.expires_in_secs) {
+                    Ok(expires_at) => expires_at,
+                    Err(e) => return e.into(),
+                }
+            }
+            dtos::BulkShareAction::Unshare => None,
+        };
+
+        let mut errors = Vec::new();
+        if file_ids.is_empty() {
+            errors.push(FieldError::new("file_ids", "must not be empty"));
+        }
+        if recipients.is_empty() {
+            errors.push(FieldError::new("recipients", "must not be empty"));
+        }
+        for (i, recipient) in recipients.iter().enumerate() {
+            if recipient.user_id.is_some() == recipient.email.is_some() {
+                errors.push(FieldError::new(
+                    &format!("recipients[{}]", i),
+                    "exactly one of user_id and email must be set",
+                ));
+            }
+        }
+        if matches!(request.action, dtos::BulkShareAction::Share)
+            && request.role == ShareRole::Owner
+        {
+            errors.push(FieldError::new("role", "owner cannot be granted"));
+        }
+        if file_ids.len() * recipients.len() > MAX_BULK_SHARE_PAIRS {
+            errors.push(FieldError::new(
+                "recipients",
+                &format!(
+                    "at most {} file and recipient pairs per request",
+                    MAX_BULK_SHARE_PAIRS
+                ),
+            ));
+        }
+        if !errors.is_empty() {
+            return ApiError::bad_request("Invalid bulk share request")
+                .with_details(errors)
+                .into();
+        }
+
+        // each file is authorized once, however many recipients it goes to
+        let (needed, action) = match request.action {
+            dtos::BulkShareAction::Share => (ShareRole::Resharer, "share it"),
+            dtos::BulkShareAction::Unshare => (ShareRole::CoOwner, "unshare it"),
+        };
+        let sharer = sharer_id.as_str();
+        let access: HashMap<String, Result<FileAccess, ApiError>> = stream::iter(file_ids.clone())
+            .map(|file_id| async move {
+                let access = self.require_role(sharer, &file_id, needed, action).await;
+                (file_id, access)
+            })
+            .buffer_unordered(BULK_SHARE_CONCURRENCY)
+            .collect()
+            .await;
+
+        let mut pairs = Vec::with_capacity(file_ids.len() * recipients.len());
+        for file_id in &file_ids {
+            for recipient in &recipients {
+                pairs.push((file_id.clone(), recipient.clone()));
+            }
+        }
+        let access = &access;
+        let results: Vec<dtos::BulkShareResult> = stream::iter(pairs)
+            .map(|(file_id, recipient)| {
+                let sharer_id = sharer_id.clone();
+                let expires_at = expires_at.clone();
+                async move {
+                    let outcome = match (
+                        &access[&file_id],
+                        share_target(recipient.user_id.clone(), recipient.email.clone()),
+                    ) {
+                        (Err(e), _) => Err(e.clone()),
+                        (_, Err(e)) => Err(e),
+                        (Ok(access), Ok(target)) => match request.action {
+                            dtos::BulkShareAction::Share => {
+                                match self
+                                    .share_with_access(
+                                        sharer_id,
+                                        file_id.clone(),
+                                        target,
+                                        request.role,
+                                        expires_at,
+                                        access,
+                                    )
+                                    .await
+                                {
+                                    ApiResponse::Success(r) => {
+                                        Ok((r.shared || r.pending, r.pending))
+                                    }
+                                    ApiResponse::Error(e) => Err(e),
+                                }
+                            }
+                            dtos::BulkShareAction::Unshare => {
+                                match self
+                                    .unshare_with_access(sharer_id, file_id.clone(), target, access)
+                                    .await
+                                {
+                                    ApiResponse::Success(r) => Ok((r.unshared, false)),
+                                    ApiResponse::Error(e) => Err(e),
+                                }
+                            }
+                        },
+                    };
+
+                    let (success, pending, error) = match outcome {
+                        Ok((success, pending)) => (success, pending, None),
+                        Err(e) => (false, false, Some(e)),
+                    };
+                    dtos::BulkShareResult {
+                        file_id,
+                        recipient,
+                        success,
+                        pending,
+                        error,
+                    }
+                }
+            })
+            .buffered(BULK_SHARE_CONCURRENCY)
+            .collect()
+            .await;
+
+        let succeeded = results.iter().filter(|r| r.success).count();
+        ApiResponse::ok(dtos::BulkShareResponse {
+            failed: results.len() - succeeded,
+            succeeded,
+            results,
+        })
+    }
+
+    /// Creates a public link to a file. Needs the resharer role, like sharing with a user.
+    #[instrument(name = "cheatsheet.create_share_link", skip_all)]
+    pub async fn create_share_link(
+        &self,
+        user_id: String,
+        file_id: String,
+        request: dtos::CreateShareLinkRequest,
+    ) -> ApiResponse<dtos::CreateShareLinkResponse> {
+        let expires_at = match share_expiry(request.expires_at, request.expires_in_secs) {
+            Ok(expires_at) => expires_at,
+            Err(e) => return e.into(),
+        };
+        let password = match link_password(request.password) {
+            Ok(password) => password,
+            Err(e) => return e.into(),
+        };
+
+        if let Err(e) = self
+            .require_role(
+                &user_id,
+                &file_id,
+                ShareRole::Resharer,
+                "create public links",
+            )
+            .await
+        {
+            return e.into();
+        }
+
+        self.user_service
+            .create_share_link(file_id, user_id, password, expires_at)
+            .await
+    }
+
+    #[instrument(name = "cheatsheet.get_share_links", skip_all)]
+    pub async fn get_share_links(
+        &self,
+        user_id: String,
+        file_id: String,
+    ) -> ApiResponse<dtos::GetShareLinksResponse> {
+        if let Err(e) = self
+            .require_role(&user_id, &file_id, ShareRole::Resharer, "view public links")
+            .await
+        {
+            return e.into();
+        }
+
+        match self
+            .user_service
+            .get_file_share_links(user_id, file_id)
+            .await
+        {
+            ApiResponse::Success(links) => ApiResponse::ok(dtos::GetShareLinksResponse { links }),
+            ApiResponse::Error(e) => e.into(),
+        }
+    }
+
+    /// Revokes a public link. Co-owners may revoke any link to the file, resharers only the
+    /// links they created.
+    #[instrument(name = "cheatsheet.revoke_share_link", skip_all)]
+    pub async fn revoke_share_link(
+        &self,
+        user_id: String,
+        file_id: String,
+        link_id: String,
+    ) -> ApiResponse<dtos::RevokeShareLinkResponse> {
+        let role = match self
+            .require_role(
+                &user_id,
+                &file_id,
+                ShareRole::Resharer,
+                "revoke public links",
+            )
+            .await
+        {
+            Ok((_, role)) => role,
+            Err(e) => return e.into(),
+        };
+
+        if role < ShareRole::CoOwner {
+            let links = match self
+                .user_service
+                .get_file_share_links(user_id.clone(), file_id.clone())
+                .await
+            {
+                ApiResponse::Success(links) => links,
+                ApiResponse::Error(e) => return e.into(),
+            };
+            if links
+                .iter()
+                .any(|link| link.id == link_id && link.created_by != user_id)
+            {
+                return ApiError::new(
+                    StatusCode::FORBIDDEN,
+                    ErrorCode::PermissionDenied,
+                    "Only co-owners can revoke links other users created",
+                )
+                .into();
+            }
+        }
+
+        match self
+            .user_service
+            .revoke_share_link(user_id, link_id, file_id)
+            .await
+        {
+            ApiResponse::Success(true) => {
+                ApiResponse::ok(dtos::RevokeShareLinkResponse { revoked: true })
+            }
+            ApiResponse::Success(false) => ApiError::new(
+                StatusCode::NOT_FOUND,
+                ErrorCode::NotFound,
+                "Share link not found",
+            )
+            .into(),
+            ApiResponse::Error(e) => e.into(),
+        }
+    }
+
+    /// Opens a public link without an account, returning a short-lived download URL. A link
+    /// stops working once its creator can no longer share the file.
+    #[instrument(name = "cheatsheet.resolve_share_link", skip_all)]
+    pub async fn resolve_share_link(
+        &self,
+        token: String,
+        request: dtos::ResolveShareLinkRequest,
+    ) -> ApiResponse<dtos::ResolveShareLinkResponse> {
+        let link = match self
+            .user_service
+            .resolve_share_link(token, request.password)
+            .await
+        {
+            ApiResponse::Success(link) => link,
+            ApiResponse::Error(e) => return e.into(),
+        };
+        let not_found = || {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                ErrorCode::NotFound,
+                "Share link not found",
+            )
+        };
+
+        // the file is read as the link's creator, so their current access applies
+        let data = match self.fetch_file(&link.created_by, &link.file_id).await {
+            Ok(data) => data.data,
+            Err(e) if e.status_code().is_client_error() => return not_found().into(),
+            Err(e) => return e.into(),
+        };
+        if role_of(&data, &link.created_by).is_none_or(|role| role < ShareRole::Resharer) {
+            return not_found().into();
+        }
+
+        match self
+            .presign_get(
+                &data.file.Key,
+                &link.created_by,
+                Some(self.share_link_url_ttl_secs),
+            )
+            .await
+        {
+            Ok(presigned) => ApiResponse::ok(dtos::ResolveShareLinkResponse {
+                name: data.file.Name,
+                url: presigned.url,
+                expires_in: presigned.expiresIn.to_string(),
+            }),
+            Err(e) => e.into(),
+        }
+    }
+
+    /// Waits for queued and running generations to finish, then cancels any still going after
+    /// `timeout` so clients following them learn the outcome before the gateway exits
+    pub async fn drain_generations(&self, timeout: Duration) {
+        if tokio::time::timeout(timeout, self.jobs.wait_until_idle())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+        let cancelled = self.jobs.cancel_all(ApiError::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::ServiceUnavailable,
+            "The gateway shut down before the generation finished, please submit it again",
+        ));
+        warn!(
+            "Cancelled {} generation jobs still in progress at shutdown",
+            cancelled
+        );
+    }
+
+    /// Counts a generation against the user's daily quota. Returns `None` when no quota is
+    /// configured; otherwise the caller must not submit the job unless it was allowed.
+    pub fn reserve_generation(&self, user_id: &str) -> Option<RateLimitDecision> {
+        let decision = self.daily_quota.as_ref()?.check(user_id);
+        if !decision.allowed {
+            warn!("User {} has used up their daily generation quota", user_id);
+            metrics::record_rate_limited("generation_quota");
+        }
+        Some(decision)
+    }
+
+    /// Returns a generation reserved with [`reserve_generation`](Self::reserve_generation)
+    /// whose job could not be submitted
+    pub fn refund_generation(&self, user_id: &str) {
+        if let Some(quota) = &self.daily_quota {
+            quota.refund(user_id);
+        }
+    }
+
+    /// Queues a generation job and returns immediately; the job runs in the background
+    #[instrument(name = "cheatsheet.submit_generation", skip_all)]
+    pub async fn submit_generation(
         &self,
         file_ids: Vec<String>,
         user_id: String,
     ) -> ApiResponse<dtos::GenerateResponse> {
+        let job = match self.jobs.create(&user_id) {
+            Ok(job) => job,
+            Err(e) => return e.into(),
+        };
+        let job_id = job.job_id.clone();
+
+        let service = self.clone();
+        let span = info_span!("generation_job", job_id = %job_id);
+        let task = request_id::scope(request_id::current(), async move {
+            let _slot = service.jobs.acquire_slot().await;
+            service.jobs.mark_running(&job_id);
+            let result = tokio::select! {
+                result = service.generate(&job_id, file_ids, user_id.clone()) => result,
+                () = service.follow_progress(&job_id, &user_id) => unreachable!("progress polling never ends"),
+            };
+            service.jobs.finish(&job_id, result);
+        });
+        let handle = tokio::spawn(task.instrument(span));
+        self.jobs
+            .set_abort_handle(&job.job_id, handle.abort_handle());
+
+        ApiResponse::ok(job)
+    }
+
+    pub async fn get_generation(
+        &self,
+        job_id: String,
+        user_id: String,
+    ) -> ApiResponse<dtos::GenerateResponse> {
+        match self.jobs.get(&job_id, &user_id) {
+            Some(job) => ApiResponse::ok(job),
+            None => ApiResponse::not_found("Generation job not found"),
+        }
+    }
+
+    /// Subscribes to every state change of a job, for streaming its progress
+    pub fn subscribe_generation(
+        &self,
+        job_id: String,
+        user_id: String,
+    ) -> ApiResponse<watch::Receiver<dtos::GenerateResponse>> {
+        match self.jobs.subscribe(&job_id, &user_id) {
+            Some(receiver) => ApiResponse::ok(receiver),
+            None => ApiResponse::not_found("Generation job not found"),
+        }
+    }
+
+    #[instrument(name = "cheatsheet.cancel_generation", skip_all)]
+    pub async fn cancel_generation(
+        &self,
+        job_id: String,
+        user_id: String,
+    ) -> ApiResponse<dtos::GenerateResponse> {
+        match self.jobs.cancel(&job_id, &user_id) {
+            Some(job) => ApiResponse::ok(job),
+            None => ApiResponse::not_found("Generation job not found"),
+        }
+    }
+
+    /// Runs one generation against the cheatsheet API, which replies once the worker has
+    /// merged the PDFs. The job id doubles as the backend's request id so its progress can
+    /// be looked up while this call is pending.
+    #[instrument(name = "cheatsheet.generate", skip_all)]
+    async fn generate(
+        &self,
+        job_id: &str,
+        file_ids: Vec<String>,
+        user_id: String,
+    ) -> Result<types::GenerateData, ApiError> {
         let url = format!("{}/generate", self.cheatsheet_api_url);
 
         let body = serde_json::json!({
             "file_ids": file_ids,
+            "request_id": job_id,
         });
 
         let request = self
             .client
             .post(&url)
             .json(&body)
-            .header("X-User-Id", user_id);
-        let response = match self.send_request(request).await {
-            Ok(r) => r,
-            Err(e) => return ApiResponse::internal_error(&e),
-        };
+            .header("X-User-Id", user_id)
+            .timeout(Duration::from_secs(self.policy.generate_timeout_secs));
+        let response = self.send_request("generate", request).await?;
 
-        let data: types::ServiceResponse<dtos::GenerateResponse> =
-            match self.parse_json(response).await {
-                Ok(d) => d,
-                Err((status, msg)) => return ApiResponse::error(status, &msg),
+        let data: types::ServiceResponse<types::GenerateData> = self.parse_json(response).await?;
+        Ok(data.data)
+    }
+
+    /// Mirrors the stage the cheatsheet backend reports for a pending generation onto the
+    /// job. Never returns; it is dropped once the generation call finishes. Failed polls
+    /// are skipped, so a backend that doesn't report progress just leaves the job `queued`.
+    async fn follow_progress(&self, job_id: &str, user_id: &str) {
+        let url = format!(
+            "{}/generate/{}",
+            self.cheatsheet_api_url,
+            urlencoding::encode(job_id)
+        );
+        let mut ticker = tokio::time::interval(self.progress_poll);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            let request = self.client.get(&url).header("X-User-Id", user_id);
+            let response = match self.attempt("generation_progress", request).await {
+                Ok(Ok(response)) if response.status().is_success() => response,
+                _ => continue,
             };
+            match response
+                .json::<types::ServiceResponse<types::GenerationProgressData>>()
+                .await
+            {
+                Ok(data) => self.jobs.set_stage(job_id, data.data.stage),
+                Err(e) => debug!("Ignoring generation progress for {}: {:?}", job_id, e),
+            }
+        }
+    }
+}
+
+/// The role `user_id` holds on the file: owner for its creator, otherwise the role of their
+/// share. `None` when the file is not shared with them or their share has expired.
+fn role_of(data: &types::FileData, user_id: &str) -> Option<ShareRole> {
+    if data.file.UserID == user_id {
+        return Some(ShareRole::Owner);
+    }
+    let now = now();
+    data.shares
+        .iter()
+        .find(|share| share.UserID == user_id && !share_expired(share, now))
+        .map(|share| ShareRole::parse(&share.Role))
+}
+
+fn now() -> DateTime<Utc> {
+    SystemTime::now().into()
+}
+
+fn share_expired(share: &types::Share, now: DateTime<Utc>) -> bool {
+    share
+        .ExpiresAt
+        .as_deref()
+        .and_then(|expires_at| DateTime::parse_from_rfc3339(expires_at).ok())
+        .is_some_and(|expires_at| expires_at <= now)
+}
+
+/// When a share being created should expire, as RFC 3339: at `expires_at`, or
+/// `expires_in_secs` from now. `None` for a permanent share.
+fn share_expiry(
+    expires_at: Option<String>,
+    expires_in_secs: Option<u64>,
+) -> Result<Option<String>, ApiError> {
+    let invalid = |field: &str, message: &str| {
+        ApiError::bad_request("Invalid share request")
+            .with_details(vec![FieldError::new(field, message)])
+    };
+    let now = now();
+    let expires_at = match (expires_at, expires_in_secs) {
+        (None, None) => return Ok(None),
+        (Some(_), Some(_)) => {
+            return Err(invalid(
+                "expires_at",
+                "at most one of expires_at and expires_in_secs may be set",
+            ));
+        }
+        (Some(expires_at), None) => DateTime::parse_from_rfc3339(&expires_at)
+            .map_err(|_| invalid("expires_at", "must be an RFC 3339 timestamp"))?
+            .with_timezone(&Utc),
+        (None, Some(secs)) => i64::try_from(secs)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|delta| now.checked_add_signed(delta))
+            .ok_or_else(|| invalid("expires_in_secs", "is too large"))?,
+    };
+    if expires_at <= now {
+        return Err(invalid("expires_at", "must be in the future"));
+    }
+    Ok(Some(expires_at.to_rfc3339_opts(SecondsFormat::Secs, true)))
+}
+
+/// A file as the cheatsheet API returned it, with the role the caller holds on it
+type FileAccess = (types::ServiceResponse<types::FileData>, ShareRole);
+
+/// The password protecting a new link, or `None` when it is left open
+fn link_password(password: Option<String>) -> Result<Option<String>, ApiError> {
+    let password = password.filter(|password| !password.is_empty());
+    if password
+        .as_ref()
+        .is_some_and(|password| password.chars().count() < MIN_LINK_PASSWORD_CHARS)
+    {
+        return Err(
+            ApiError::bad_request("Invalid share link request").with_details(vec![
+                FieldError::new(
+                    "password",
+                    &format!("must be at least {} characters", MIN_LINK_PASSWORD_CHARS),
+                ),
+            ]),
+        );
+    }
+    Ok(password)
+}
+
+enum ShareTarget {
+    User(String),
+    Email(String),
+}
+
+/// Share and unshare requests name the other user by id or by email, never both
+fn share_target(user_id: Option<String>, email: Option<String>) -> Result<ShareTarget, ApiError> {
+    match (user_id, email) {
+        (Some(user_id), None) => Ok(ShareTarget::User(user_id)),
+        (None, Some(email)) => Ok(ShareTarget::Email(email)),
+        _ => Err(
+            ApiError::bad_request("Invalid share request").with_details(vec![FieldError::new(
+                "user_id",
+                "exactly one of user_id and email must be set",
+            )]),
+        ),
+    }
+}
+
+/// Drops repeated items, keeping the first occurrence of each
+fn unique<T: Clone + Eq + std::hash::Hash>(items: Vec<T>) -> Vec<T> {
+    let mut seen = HashSet::new();
+    items
+        .into_iter()
+        .filter(|item| seen.insert(item.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::{
+        Json, Router,
+        routing::{get, post},
+    };
+    use tokio::net::TcpListener;
+    use tokio::sync::Notify;
+    use tonic::transport::Channel;
+
+    use super::*;
+    use crate::dtos::{GenerationStage, GenerationStatus};
+    use crate::proto::user::user_client::UserClient;
+    use crate::services::token_cache::TokenCache;
+
+    /// Stands in for the cheatsheet API's generation endpoints and file lookup. Each
+    /// generation stays pending until `release` is notified once for it; progress always
+    /// reports `summarising`. Every file id and key resolves to a file owned by user 1 and
+    /// shared with user 2 as a viewer, counted in `file_lookups`; every share succeeds.
+    struct FakeBackend {
+        url: String,
+        release: Arc<Notify>,
+        file_lookups: Arc<AtomicUsize>,
+    }
+
+    async fn fake_backend() -> FakeBackend {
+        let release = Arc::new(Notify::new());
+        let pending = release.clone();
+        let file_lookups = Arc::new(AtomicUsize::new(0));
+        let lookups = file_lookups.clone();
+        let app = Router::new()
+            .route(
+                "/generate",
+                post(move || async move {
+                    pending.notified().await;
+                    Json(serde_json::json!({
+                        "success": true,
+                        "data": { "file_id": "generated", "key": "cheatsheets/1/generated.pdf" },
+                    }))
+                }),
+            )
+            .route(
+                "/generate/{request_id}",
+                get(|| async {
+                    Json(serde_json::json!({ "success": true, "data": { "stage": "summarising" } }))
+                }),
+            )
+            .route("/files/by-key", get(|| async { Json(file_data()) }))
+            .route(
+                "/files/{file_id}",
+                get(move || async move {
+                    lookups.fetch_add(1, Ordering::SeqCst);
+                    Json(file_data())
+                }),
+            )
+            .route(
+                "/share",
+                post(|| async {
+                    Json(serde_json::json!({ "success": true, "data": { "shared": true } }))
+                }),
+            );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        FakeBackend {
+            url,
+            release,
+            file_lookups,
+        }
+    }
 
-        ApiResponse::ok(data.data)
+    fn file_data() -> serde_json::Value {
+        serde_json::json!({
+            "success": true,
+            "data": {
+                "file": {
+                    "ID": "f1",
+                    "UserID": "1",
+                    "CreatedAt": "2026-01-01T00:00:00Z",
+                    "Name": "notes.pdf",
+                    "Key": "slides/1/notes.pdf",
+                },
+                "shares": [{ "UserID": "2", "Role": "viewer", "FileID": "f1" }],
+            },
+        })
+    }
+
+    fn service(url: &str) -> CheatsheetService {
+        let user_client =
+            UserClient::new(Channel::from_static("http://127.0.0.1:1").connect_lazy());
+        CheatsheetService::new(
+            url.to_string(),
+            RequestSigner::new("secret"),
+            CheatsheetClientConfig {
+                connect_timeout_ms: 1000,
+                request_timeout_secs: 5,
+                generate_timeout_secs: 5,
+                max_retries: 0,
+                retry_base_delay_ms: 10,
+                breaker_failure_threshold: 0,
+                breaker_open_secs: 1,
+            },
+            GenerationConfig {
+                max_concurrent: 1,
+                job_ttl_secs: 60,
+                progress_poll_ms: 10,
+                daily_quota: 0,
+                max_active_per_user: 0,
+            },
+            ShareLinkConfig { url_ttl_secs: 60 },
+            UserService::new(
+                user_client,
+                TokenCache::new(Duration::from_secs(1), 10),
+                RequestSigner::new("secret"),
+            ),
+        )
+        .unwrap()
+    }
+
+    fn success<T>(response: ApiResponse<T>) -> T {
+        match response {
+            ApiResponse::Success(data) => data,
+            ApiResponse::Error(e) => panic!("unexpected error: {}", e.message),
+        }
+    }
+
+    async fn wait_for(
+        service: &CheatsheetService,
+        job_id: &str,
+        done: impl Fn(&dtos::GenerateResponse) -> bool,
+    ) -> dtos::GenerateResponse {
+        let mut receiver =
+            success(service.subscribe_generation(job_id.to_string(), "1".to_string()));
+        tokio::time::timeout(Duration::from_secs(5), receiver.wait_for(|job| done(job)))
+            .await
+            .expect("job did not reach the expected state in time")
+            .expect("job was dropped")
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn generation_follows_backend_progress_until_done() {
+        let backend = fake_backend().await;
+        let service = service(&backend.url);
+
+        let job = success(
+            service
+                .submit_generation(vec!["a".to_string()], "1".to_string())
+                .await,
+        );
+        assert_eq!(job.status, GenerationStatus::Queued);
+
+        let running = wait_for(&service, &job.job_id, |job| {
+            job.stage == GenerationStage::Summarising
+        })
+        .await;
+        assert_eq!(running.status, GenerationStatus::Running);
+
+        backend.release.notify_one();
+        let done = wait_for(&service, &job.job_id, |job| job.status.is_terminal()).await;
+        assert_eq!(done.status, GenerationStatus::Succeeded);
+        assert_eq!(done.stage, GenerationStage::Done);
+        assert_eq!(done.key.as_deref(), Some("cheatsheets/1/generated.pdf"));
+    }
+
+    #[tokio::test]
+    async fn jobs_beyond_max_concurrent_wait_queued() {
+        let backend = fake_backend().await;
+        let service = service(&backend.url);
+
+        let first = success(
+            service
+                .submit_generation(vec!["a".to_string()], "1".to_string())
+                .await,
+        );
+        let second = success(
+            service
+                .submit_generation(vec!["b".to_string()], "1".to_string())
+                .await,
+        );
+        wait_for(&service, &first.job_id, |job| {
+            job.stage == GenerationStage::Summarising
+        })
+        .await;
+        let waiting = success(
+            service
+                .get_generation(second.job_id.clone(), "1".to_string())
+                .await,
+        );
+        assert_eq!(waiting.status, GenerationStatus::Queued);
+
+        backend.release.notify_one();
+        wait_for(&service, &first.job_id, |job| job.status.is_terminal()).await;
+        wait_for(&service, &second.job_id, |job| {
+            job.stage == GenerationStage::Summarising
+        })
+        .await;
+        backend.release.notify_one();
+        let done = wait_for(&service, &second.job_id, |job| job.status.is_terminal()).await;
+        assert_eq!(done.status, GenerationStatus::Succeeded);
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_tracking_a_running_generation() {
+        let backend = fake_backend().await;
+        let service = service(&backend.url);
+
+        let job = success(
+            service
+                .submit_generation(vec!["a".to_string()], "1".to_string())
+                .await,
+        );
+        wait_for(&service, &job.job_id, |job| {
+            job.stage == GenerationStage::Summarising
+        })
+        .await;
+
+        let cancelled = success(
+            service
+                .cancel_generation(job.job_id.clone(), "1".to_string())
+                .await,
+        );
+        assert_eq!(cancelled.status, GenerationStatus::Cancelled);
+
+        // the backend finishing afterwards does not revive the job
+        backend.release.notify_one();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let job = success(service.get_generation(job.job_id, "1".to_string()).await);
+        assert_eq!(job.status, GenerationStatus::Cancelled);
+        assert_eq!(job.key, None);
+    }
+
+    #[tokio::test]
+    async fn key_access_follows_the_file_role() {
+        let backend = fake_backend().await;
+        let service = service(&backend.url);
+        let key = "slides/1/notes.pdf";
+
+        let check = |user_id: &'static str, needed: ShareRole| {
+            let service = &service;
+            async move {
+                service
+                    .require_role_for_key(user_id, key, needed, "test it")
+                    .await
+                    .map(|file| file.ID)
+                    .map_err(|e| e.status_code())
+            }
+        };
+        assert_eq!(check("1", ShareRole::CoOwner).await, Ok("f1".to_string()));
+        assert_eq!(check("2", ShareRole::Viewer).await, Ok("f1".to_string()));
+        assert_eq!(
+            check("2", ShareRole::CoOwner).await,
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            check("3", ShareRole::Viewer).await,
+            Err(StatusCode::FORBIDDEN)
+        );
+    }
+
+    #[tokio::test]
+    async fn bulk_share_authorizes_each_file_once() {
+        let backend = fake_backend().await;
+        let service = service(&backend.url);
+        let recipients = ["3", "4", "5"].map(|user_id| dtos::ShareRecipient {
+            user_id: Some(user_id.to_string()),
+            email: None,
+        });
+
+        let response = success(
+            service
+                .bulk_share(
+                    "1".to_string(),
+                    dtos::BulkShareRequest {
+                        action: dtos::BulkShareAction::Share,
+                        file_ids: vec!["f1".to_string(), "f2".to_string()],
+                        recipients: recipients.to_vec(),
+                        role: ShareRole::Viewer,
+                        expires_at: None,
+                        expires_in_secs: None,
+                    },
+                )
+                .await,
+        );
+        assert_eq!((response.succeeded, response.failed), (6, 0));
+        assert_eq!(backend.file_lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn link_passwords_need_eight_characters() {
+        let e = link_password(Some("1234567".to_string())).unwrap_err();
+        assert_eq!(e.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(e.details[0].field, "password");
+
+        assert_eq!(
+            link_password(Some("12345678".to_string())).unwrap(),
+            Some("12345678".to_string())
+        );
+        assert_eq!(link_password(Some(String::new())).unwrap(), None);
     }
 }
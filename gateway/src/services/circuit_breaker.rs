@@ -0,0 +1,207 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+enum State {
+    Closed {
+        consecutive_failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// The open period has elapsed and a single trial call is in flight. The trial's
+    /// outcome is never recorded if its caller goes away, e.g. the client disconnects, so
+    /// another trial is let through once `open_for` has passed since this one started.
+    HalfOpen {
+        probe_started: Instant,
+    },
+}
+
+/// Consecutive-failure circuit breaker guarding calls to a backend service.
+/// After `failure_threshold` failures in a row every call is rejected for `open_for`,
+/// then one trial call decides whether to close the circuit again.
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    open_for: Duration,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold,
+            open_for,
+            state: Mutex::new(State::Closed {
+                consecutive_failures: 0,
+            }),
+        }
+    }
+
+    /// Whether a call may be attempted right now
+    pub fn allow(&self) -> bool {
+        self.allow_at(Instant::now())
+    }
+
+    fn allow_at(&self, now: Instant) -> bool {
+        if self.failure_threshold == 0 {
+            return true;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { until } if now >= until => {
+                *state = State::HalfOpen { probe_started: now };
+                true
+            }
+            State::HalfOpen { probe_started } if now >= probe_started + self.open_for => {
+                warn!(
+                    "Trial call to {} never reported back, trying another",
+                    self.name
+                );
+                *state = State::HalfOpen { probe_started: now };
+                true
+            }
+            State::Open { .. } | State::HalfOpen { .. } => false,
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if matches!(*state, State::HalfOpen { .. }) {
+            info!("Circuit for {} closed", self.name);
+        }
+        *state = State::Closed {
+            consecutive_failures: 0,
+        };
+    }
+
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now())
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        if self.failure_threshold == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let trip = match *state {
+            State::Closed {
+                consecutive_failures,
+            } => {
+                let consecutive_failures = consecutive_failures + 1;
+                *state = State::Closed {
+                    consecutive_failures,
+                };
+                consecutive_failures >= self.failure_threshold
+            }
+            State::HalfOpen { .. } => true,
+            State::Open { .. } => false,
+        };
+
+        if trip {
+            warn!("Circuit for {} opened for {:?}", self.name, self.open_for);
+            *state = State::Open {
+                until: now + self.open_for,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN_FOR: Duration = Duration::from_secs(30);
+
+    fn breaker() -> CircuitBreaker {
+        CircuitBreaker::new("test", 2, OPEN_FOR)
+    }
+
+    #[test]
+    fn opens_after_threshold_then_closes_after_successful_trial() {
+        let breaker = breaker();
+        let start = Instant::now();
+
+        assert!(breaker.allow_at(start));
+        breaker.record_failure_at(start);
+        assert!(
+            breaker.allow_at(start),
+            "one failure is below the threshold"
+        );
+        breaker.record_failure_at(start);
+        assert!(!breaker.allow_at(start), "open after two failures in a row");
+        assert!(!breaker.allow_at(start + OPEN_FOR / 2));
+
+        assert!(
+            breaker.allow_at(start + OPEN_FOR),
+            "trial call once open period ends"
+        );
+        assert!(
+            !breaker.allow_at(start + OPEN_FOR),
+            "only one trial at a time"
+        );
+        breaker.record_success();
+        assert!(breaker.allow_at(start + OPEN_FOR));
+        assert!(breaker.allow_at(start + OPEN_FOR));
+    }
+
+    #[test]
+    fn failed_trial_reopens() {
+        let breaker = breaker();
+        let start = Instant::now();
+        breaker.record_failure_at(start);
+        breaker.record_failure_at(start);
+
+        let trial = start + OPEN_FOR;
+        assert!(breaker.allow_at(trial));
+        breaker.record_failure_at(trial);
+        assert!(!breaker.allow_at(trial));
+        assert!(!breaker.allow_at(trial + OPEN_FOR / 2));
+        assert!(breaker.allow_at(trial + OPEN_FOR));
+    }
+
+    #[test]
+    fn success_resets_failure_count() {
+        let breaker = breaker();
+        let start = Instant::now();
+        breaker.record_failure_at(start);
+        breaker.record_success();
+        breaker.record_failure_at(start);
+        assert!(breaker.allow_at(start));
+    }
+
+    #[test]
+    fn dropped_trial_does_not_keep_circuit_open() {
+        let breaker = breaker();
+        let start = Instant::now();
+        breaker.record_failure_at(start);
+        breaker.record_failure_at(start);
+
+        // the trial's caller goes away without recording an outcome
+        let trial = start + OPEN_FOR;
+        assert!(breaker.allow_at(trial));
+        assert!(!breaker.allow_at(trial + OPEN_FOR / 2));
+
+        assert!(
+            breaker.allow_at(trial + OPEN_FOR),
+            "another trial after the deadline"
+        );
+        breaker.record_success();
+        assert!(breaker.allow_at(trial + OPEN_FOR));
+    }
+
+    #[test]
+    fn zero_threshold_disables_breaker() {
+        let breaker = CircuitBreaker::new("test", 0, OPEN_FOR);
+        let start = Instant::now();
+        for _ in 0..10 {
+            breaker.record_failure_at(start);
+        }
+        assert!(breaker.allow_at(start));
+    }
+}
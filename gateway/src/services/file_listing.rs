@@ -0,0 +1,155 @@
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+
+use crate::dtos::{ApiError, FieldError, FileOwnership, FileSort, ListFilesQuery, SortOrder};
+
+/// Largest page a client may ask for
+const MAX_LIMIT: usize = 100;
+
+/// Validates a listing request and turns it into query parameters for the cheatsheet API's
+/// `GET /files`, which filters, sorts, counts and pages the files. Cursors are the
+/// cheatsheet API's own and are passed through untouched.
+pub fn upstream_query(query: &ListFilesQuery) -> Result<Vec<(&'static str, String)>, ApiError> {
+    let mut errors = Vec::new();
+    let created_after = parse_bound("created_after", &query.created_after, &mut errors);
+    let created_before = parse_bound("created_before", &query.created_before, &mut errors);
+    if query
+        .limit
+        .is_some_and(|limit| limit == 0 || limit > MAX_LIMIT)
+    {
+        errors.push(FieldError::new(
+            "limit",
+            &format!("must be between 1 and {}", MAX_LIMIT),
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::bad_request("Invalid file listing request").with_details(errors));
+    }
+
+    let mut upstream = Vec::new();
+    if let Some(file_type) = query.file_type {
+        upstream.push(("type", file_type.to_string()));
+    }
+    if let Some(ownership) = query.ownership {
+        let ownership = match ownership {
+            FileOwnership::Mine => "mine",
+            FileOwnership::Shared => "shared",
+        };
+        upstream.push(("ownership", ownership.to_string()));
+    }
+    if let Some(name) = &query.name {
+        upstream.push(("name", name.clone()));
+    }
+    for (field, bound) in [
+        ("created_after", created_after),
+        ("created_before", created_before),
+    ] {
+        if let Some(bound) = bound {
+            upstream.push((field, bound.to_rfc3339_opts(SecondsFormat::AutoSi, true)));
+        }
+    }
+    if let Some(sort) = query.sort {
+        let sort = match sort {
+            FileSort::CreatedAt => "created_at",
+            FileSort::Name => "name",
+        };
+        let order = match query.order {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        };
+        upstream.push(("sort", sort.to_string()));
+        upstream.push(("order", order.to_string()));
+    }
+    if let Some(limit) = query.limit {
+        upstream.push(("limit", limit.to_string()));
+    }
+    if let Some(cursor) = &query.cursor {
+        upstream.push(("cursor", cursor.clone()));
+    }
+    Ok(upstream)
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Accepts a full RFC 3339 timestamp or a plain date, meaning midnight UTC
+fn parse_bound(
+    field: &str,
+    value: &Option<String>,
+    errors: &mut Vec<FieldError>,
+) -> Option<DateTime<Utc>> {
+    let value = value.as_deref()?;
+    let parsed = parse_timestamp(value).or_else(|| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|midnight| midnight.and_utc())
+    });
+    if parsed.is_none() {
+        errors.push(FieldError::new(
+            field,
+            "must be an RFC 3339 timestamp or a YYYY-MM-DD date",
+        ));
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(params: &str) -> ListFilesQuery {
+        let uri: hyper::Uri = format!("/files?{}", params).parse().unwrap();
+        axum::extract::Query::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn pushes_filters_upstream() {
+        let upstream = upstream_query(&query(
+            "type=slides&ownership=shared&name=exam&created_after=2026-01-01",
+        ))
+        .unwrap();
+
+        assert_eq!(
+            upstream,
+            vec![
+                ("type", "slides".to_string()),
+                ("ownership", "shared".to_string()),
+                ("name", "exam".to_string()),
+                ("created_after", "2026-01-01T00:00:00Z".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pushes_sort_and_page_upstream() {
+        let upstream = upstream_query(&query("sort=name&limit=2&cursor=opaque")).unwrap();
+
+        assert_eq!(
+            upstream,
+            vec![
+                ("sort", "name".to_string()),
+                ("order", "desc".to_string()),
+                ("limit", "2".to_string()),
+                ("cursor", "opaque".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_order_out_without_sort() {
+        let upstream = upstream_query(&query("order=asc")).unwrap();
+        assert!(upstream.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_limit_and_dates() {
+        let Err(e) = upstream_query(&query("limit=0&created_before=yesterday")) else {
+            panic!("expected a validation error");
+        };
+        let fields: Vec<&str> = e.details.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(fields, ["created_before", "limit"]);
+    }
+}
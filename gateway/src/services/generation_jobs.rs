@@ -0,0 +1,411 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore, watch};
+use tokio::task::AbortHandle;
+use tokio::time::MissedTickBehavior;
+use uuid::Uuid;
+
+use crate::dtos::{self, ApiError, GenerationStage, GenerationStatus};
+use crate::services::types;
+
+struct Job {
+    user_id: String,
+    /// Current state of the job; every change is pushed to subscribers
+    view: watch::Sender<dtos::GenerateResponse>,
+    finished_at: Option<Instant>,
+    abort: Option<AbortHandle>,
+}
+
+/// In-memory registry of cheatsheet generation jobs. At most `max_concurrent` jobs talk to
+/// the cheatsheet API at once; the rest wait as `queued`. Finished jobs are kept for `ttl`
+/// so clients can still poll the outcome.
+pub struct GenerationJobs {
+    ttl: Duration,
+    /// Queued or running jobs one user may have at once; 0 means no cap
+    max_active_per_user: usize,
+    slots: Arc<Semaphore>,
+    jobs: Mutex<HashMap<String, Job>>,
+    /// Woken whenever a job reaches a terminal state
+    finished: Notify,
+}
+
+impl GenerationJobs {
+    pub fn new(max_concurrent: usize, ttl: Duration, max_active_per_user: usize) -> Self {
+        Self {
+            ttl,
+            max_active_per_user,
+            slots: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            jobs: Mutex::new(HashMap::new()),
+            finished: Notify::new(),
+        }
+    }
+
+    /// Evicts expired jobs in the background, so memory is returned even when no new jobs
+    /// are submitted. Stops once the registry is dropped.
+    pub fn spawn_purger(self: &Arc<Self>) {
+        let jobs = Arc::downgrade(self);
+        let every = self
+            .ttl
+            .clamp(Duration::from_secs(1), Duration::from_secs(60));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                match Weak::upgrade(&jobs) {
+                    Some(jobs) => jobs.purge(Instant::now()),
+                    None => return,
+                }
+            }
+        });
+    }
+
+    /// Registers a queued job, unless the user already has as many active jobs as allowed
+    pub fn create(&self, user_id: &str) -> Result<dtos::GenerateResponse, ApiError> {
+        let view = dtos::GenerateResponse {
+            job_id: Uuid::new_v4().to_string(),
+            status: GenerationStatus::Queued,
+            stage: GenerationStage::Queued,
+            file_id: None,
+            key: None,
+            error: None,
+        };
+
+        let mut jobs = self.jobs.lock().unwrap();
+        if self.max_active_per_user > 0 {
+            let active = jobs
+                .values()
+                .filter(|job| job.user_id == user_id && job.finished_at.is_none())
+                .count();
+            if active >= self.max_active_per_user {
+                return Err(ApiError::rate_limited(&format!(
+                    "At most {} generation jobs may be in progress at once",
+                    self.max_active_per_user
+                )));
+            }
+        }
+        jobs.insert(
+            view.job_id.clone(),
+            Job {
+                user_id: user_id.to_string(),
+                view: watch::Sender::new(view.clone()),
+                finished_at: None,
+                abort: None,
+            },
+        );
+        Ok(view)
+    }
+
+    /// Drops jobs that finished more than `ttl` before `now`
+    fn purge(&self, now: Instant) {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.retain(|_, job| {
+            job.finished_at
+                .is_none_or(|finished_at| now.duration_since(finished_at) < self.ttl)
+        });
+    }
+
+    /// Remembers how to stop the task driving a job, unless it has already finished
+    pub fn set_abort_handle(&self, job_id: &str, handle: AbortHandle) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(job) = jobs.get_mut(job_id)
+            && !job.view.borrow().status.is_terminal()
+        {
+            job.abort = Some(handle);
+        }
+    }
+
+    /// Waits for a free generation slot
+    pub async fn acquire_slot(&self) -> OwnedSemaphorePermit {
+        self.slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("generation semaphore is never closed")
+    }
+
+    /// Jobs are only visible to the user who submitted them
+    pub fn get(&self, job_id: &str, user_id: &str) -> Option<dtos::GenerateResponse> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(job_id)
+            .filter(|job| job.user_id == user_id)
+            .map(|job| job.view.borrow().clone())
+    }
+
+    /// Follows every change to a job until it is evicted. The receiver starts out holding
+    /// the current state.
+    pub fn subscribe(
+        &self,
+        job_id: &str,
+        user_id: &str,
+    ) -> Option<watch::Receiver<dtos::GenerateResponse>> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(job_id)
+            .filter(|job| job.user_id == user_id)
+            .map(|job| job.view.subscribe())
+    }
+
+    pub fn mark_running(&self, job_id: &str) {
+        self.update(job_id, |view| {
+            view.status = GenerationStatus::Running;
+            true
+        });
+    }
+
+    /// Records the stage the cheatsheet backend reports for a running job. Terminal stages
+    /// are only ever set by [`finish`](Self::finish) and [`cancel`](Self::cancel).
+    pub fn set_stage(&self, job_id: &str, stage: GenerationStage) {
+        if stage.is_terminal() {
+            return;
+        }
+        self.update(job_id, |view| {
+            let changed = view.stage != stage;
+            view.stage = stage;
+            changed
+        });
+    }
+
+    pub fn finish(&self, job_id: &str, result: Result<types::GenerateData, ApiError>) {
+        self.update(job_id, |view| match result {
+            Ok(data) => {
+                view.status = GenerationStatus::Succeeded;
+                view.stage = GenerationStage::Done;
+                view.file_id = Some(data.file_id);
+                view.key = Some(data.key);
+                true
+            }
+            Err(e) => {
+                view.status = GenerationStatus::Failed;
+                view.stage = GenerationStage::Failed;
+                view.error = Some(e);
+                true
+            }
+        });
+    }
+
+    /// Cancels a queued or running job. Finished jobs are returned unchanged.
+    ///
+    /// This only stops the gateway waiting on the job: the cheatsheet API has no way to
+    /// call back a generation that is already with its workers, so one that was running may
+    /// still finish and store its file.
+    pub fn cancel(&self, job_id: &str, user_id: &str) -> Option<dtos::GenerateResponse> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.get_mut(job_id).filter(|job| job.user_id == user_id)?;
+
+        if job.cancel(None) {
+            self.finished.notify_waiters();
+        }
+        Some(job.view.borrow().clone())
+    }
+
+    /// Resolves once no job is queued or running
+    pub async fn wait_until_idle(&self) {
+        loop {
+            let finished = self.finished.notified();
+            tokio::pin!(finished);
+            // register before checking, so a job finishing in between still wakes us
+            finished.as_mut().enable();
+            if self.active() == 0 {
+                return;
+            }
+            finished.await;
+        }
+    }
+
+    /// Cancels every queued or running job with `reason`, returning how many there were
+    pub fn cancel_all(&self, reason: ApiError) -> usize {
+        let mut jobs = self.jobs.lock().unwrap();
+        let mut cancelled = 0;
+        for job in jobs.values_mut() {
+            if job.cancel(Some(reason.clone())) {
+                cancelled += 1;
+            }
+        }
+        if cancelled > 0 {
+            self.finished.notify_waiters();
+        }
+        cancelled
+    }
+
+    fn active(&self) -> usize {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.values()
+            .filter(|job| job.finished_at.is_none())
+            .count()
+    }
+
+    /// Applies `f` to a job that hasn't reached a terminal state yet. `f` returns whether it
+    /// changed anything, so subscribers aren't woken for no-op updates.
+    fn update(&self, job_id: &str, f: impl FnOnce(&mut dtos::GenerateResponse) -> bool) {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(job) = jobs.get_mut(job_id)
+            && !job.view.borrow().status.is_terminal()
+        {
+            job.view.send_if_modified(f);
+            if job.view.borrow().status.is_terminal() {
+                job.finished_at = Some(Instant::now());
+                job.abort = None;
+                self.finished.notify_waiters();
+            }
+        }
+    }
+}
+
+impl Job {
+    /// Stops the task driving the job and marks it cancelled, unless it has already
+    /// finished. Returns whether it was cancelled.
+    fn cancel(&mut self, error: Option<ApiError>) -> bool {
+        if self.view.borrow().status.is_terminal() {
+            return false;
+        }
+        if let Some(abort) = self.abort.take() {
+            abort.abort();
+        }
+        self.view.send_modify(|view| {
+            view.status = GenerationStatus::Cancelled;
+            view.stage = GenerationStage::Cancelled;
+            view.error = error;
+        });
+        self.finished_at = Some(Instant::now());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn succeeded() -> Result<types::GenerateData, ApiError> {
+        Ok(types::GenerateData {
+            file_id: "file".to_string(),
+            key: "cheatsheets/1/file.pdf".to_string(),
+        })
+    }
+
+    #[test]
+    fn caps_active_jobs_per_user() {
+        let jobs = GenerationJobs::new(1, TTL, 2);
+        let first = jobs.create("1").unwrap();
+        jobs.create("1").unwrap();
+
+        let err = jobs.create("1").unwrap_err();
+        assert_eq!(err.code, dtos::ErrorCode::RateLimited);
+        jobs.create("2").expect("other users have their own cap");
+
+        jobs.finish(&first.job_id, succeeded());
+        jobs.create("1").expect("finished jobs do not count");
+    }
+
+    #[test]
+    fn zero_cap_allows_any_number_of_jobs() {
+        let jobs = GenerationJobs::new(1, TTL, 0);
+        for _ in 0..10 {
+            jobs.create("1").unwrap();
+        }
+    }
+
+    #[test]
+    fn purges_only_jobs_finished_longer_than_ttl_ago() {
+        let jobs = GenerationJobs::new(1, TTL, 0);
+        let finished = jobs.create("1").unwrap();
+        let active = jobs.create("1").unwrap();
+        jobs.finish(&finished.job_id, succeeded());
+
+        jobs.purge(Instant::now());
+        assert!(jobs.get(&finished.job_id, "1").is_some());
+
+        jobs.purge(Instant::now() + TTL);
+        assert!(jobs.get(&finished.job_id, "1").is_none());
+        assert!(jobs.get(&active.job_id, "1").is_some());
+    }
+
+    #[test]
+    fn jobs_are_only_visible_to_their_owner() {
+        let jobs = GenerationJobs::new(1, TTL, 0);
+        let job = jobs.create("1").unwrap();
+
+        assert!(jobs.get(&job.job_id, "2").is_none());
+        assert!(jobs.subscribe(&job.job_id, "2").is_none());
+        assert!(jobs.cancel(&job.job_id, "2").is_none());
+    }
+
+    #[test]
+    fn cancelled_job_ignores_later_updates() {
+        let jobs = GenerationJobs::new(1, TTL, 0);
+        let job = jobs.create("1").unwrap();
+        jobs.mark_running(&job.job_id);
+
+        let cancelled = jobs.cancel(&job.job_id, "1").unwrap();
+        assert_eq!(cancelled.status, GenerationStatus::Cancelled);
+
+        jobs.set_stage(&job.job_id, GenerationStage::Rendering);
+        jobs.finish(&job.job_id, succeeded());
+        let view = jobs.get(&job.job_id, "1").unwrap();
+        assert_eq!(view.status, GenerationStatus::Cancelled);
+        assert_eq!(view.key, None);
+    }
+
+    #[test]
+    fn terminal_stages_come_only_from_finish() {
+        let jobs = GenerationJobs::new(1, TTL, 0);
+        let job = jobs.create("1").unwrap();
+
+        jobs.set_stage(&job.job_id, GenerationStage::Done);
+        assert_eq!(
+            jobs.get(&job.job_id, "1").unwrap().stage,
+            GenerationStage::Queued
+        );
+
+        jobs.finish(&job.job_id, succeeded());
+        let view = jobs.get(&job.job_id, "1").unwrap();
+        assert_eq!(view.status, GenerationStatus::Succeeded);
+        assert_eq!(view.stage, GenerationStage::Done);
+    }
+
+    #[tokio::test]
+    async fn waits_until_every_job_has_finished() {
+        let jobs = Arc::new(GenerationJobs::new(1, TTL, 0));
+        let first = jobs.create("1").unwrap();
+        let second = jobs.create("2").unwrap();
+
+        let idle = tokio::spawn({
+            let jobs = jobs.clone();
+            async move { jobs.wait_until_idle().await }
+        });
+        jobs.finish(&first.job_id, succeeded());
+        tokio::task::yield_now().await;
+        assert!(!idle.is_finished());
+
+        jobs.cancel(&second.job_id, "2");
+        tokio::time::timeout(Duration::from_secs(1), idle)
+            .await
+            .expect("still waiting after the last job finished")
+            .unwrap();
+    }
+
+    #[test]
+    fn cancel_all_leaves_finished_jobs_alone() {
+        let jobs = GenerationJobs::new(1, TTL, 0);
+        let finished = jobs.create("1").unwrap();
+        let running = jobs.create("1").unwrap();
+        jobs.finish(&finished.job_id, succeeded());
+        jobs.mark_running(&running.job_id);
+
+        let cancelled = jobs.cancel_all(ApiError::internal("shutting down"));
+        assert_eq!(cancelled, 1);
+        let view = jobs.get(&running.job_id, "1").unwrap();
+        assert_eq!(view.status, GenerationStatus::Cancelled);
+        assert_eq!(
+            view.error.map(|e| e.message).as_deref(),
+            Some("shutting down")
+        );
+        assert_eq!(
+            jobs.get(&finished.job_id, "1").unwrap().status,
+            GenerationStatus::Succeeded
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use tonic::transport::Channel;
+use tonic_health::pb::HealthCheckRequest;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tracing::{instrument, warn};
+
+use crate::dtos;
+use openexam_common::shutdown::Shutdown;
+
+/// Upper bound on each dependency check so a hung backend can't stall the probe
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// gRPC service name the user service reports health for
+const USER_SERVICE_NAME: &str = "user.User";
+
+#[derive(Debug, Clone)]
+pub struct HealthService {
+    user_health: HealthClient<Channel>,
+    cheatsheet_api_url: String,
+    client: reqwest::Client,
+    shutdown: Shutdown,
+}
+
+impl HealthService {
+    pub fn new(user_channel: Channel, cheatsheet_api_url: String, shutdown: Shutdown) -> Self {
+        Self {
+            user_health: HealthClient::new(user_channel),
+            cheatsheet_api_url,
+            client: reqwest::Client::builder()
+                .timeout(CHECK_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            shutdown,
+        }
+    }
+
+    /// Checks every downstream dependency concurrently. A draining gateway reports itself
+    /// as not ready so the load balancer stops sending it traffic
+    #[instrument(name = "health.readiness", skip_all)]
+    pub async fn readiness(&self) -> dtos::ReadinessResponse {
+        if self.shutdown.is_shutting_down() {
+            return dtos::ReadinessResponse {
+                status: "not_ready".to_string(),
+                checks: vec![dependency_check(
+                    "gateway",
+                    Err("shutting down".to_string()),
+                )],
+            };
+        }
+
+        let (user, cheatsheet) = tokio::join!(self.check_user_service(), self.check_cheatsheet());
+        let checks = vec![
+            dependency_check("user_service", user),
+            dependency_check("cheatsheet_api", cheatsheet),
+        ];
+
+        let status = if checks.iter().all(|check| check.healthy) {
+            "ready"
+        } else {
+            "not_ready"
+        };
+        dtos::ReadinessResponse {
+            status: status.to_string(),
+            checks,
+        }
+    }
+
+    async fn check_user_service(&self) -> Result<(), String> {
+        let mut client = self.user_health.clone();
+        let request = HealthCheckRequest {
+            service: USER_SERVICE_NAME.to_string(),
+        };
+
+        let response = tokio::time::timeout(CHECK_TIMEOUT, client.check(request))
+            .await
+            .map_err(|_| "timed out".to_string())?
+            .map_err(|e| e.message().to_string())?;
+
+        match response.into_inner().status() {
+            ServingStatus::Serving => Ok(()),
+            status => Err(format!("reported {:?}", status)),
+        }
+    }
+
+    async fn check_cheatsheet(&self) -> Result<(), String> {
+        let url = format!("{}/healthz", self.cheatsheet_api_url);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("responded with {}", response.status()))
+        }
+    }
+}
+
+fn dependency_check(name: &str, result: Result<(), String>) -> dtos::DependencyCheck {
+    if let Err(e) = &result {
+        warn!("Readiness check for {} failed: {}", name, e);
+    }
+    dtos::DependencyCheck {
+        name: name.to_string(),
+        healthy: result.is_ok(),
+        error: result.err(),
+    }
+}
@@ -1,4 +1,10 @@
 pub mod cheatsheet;
+pub mod circuit_breaker;
+pub mod file_listing;
+pub mod generation_jobs;
+pub mod health;
+pub mod rate_limiter;
 pub mod response;
+pub mod token_cache;
 pub mod types;
 pub mod user;
@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, header::RETRY_AFTER};
+
+use crate::config::config::{RateLimit, RateLimitConfig};
+
+const LIMIT_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+const REMAINING_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+const RESET_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Outcome of counting one request against a limit
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the current window ends and the count starts over
+    pub reset_after: Duration,
+}
+
+impl RateLimitDecision {
+    /// Sets the `X-RateLimit-*` headers, plus `Retry-After` when the request was rejected.
+    /// Headers that are already present are left alone, so the tightest limit a handler
+    /// reported wins over the route limit.
+    pub fn apply_headers(&self, headers: &mut HeaderMap) {
+        let reset_secs = self.reset_after.as_secs_f64().ceil() as u64;
+        headers
+            .entry(LIMIT_HEADER)
+            .or_insert_with(|| HeaderValue::from(self.limit));
+        headers
+            .entry(REMAINING_HEADER)
+            .or_insert_with(|| HeaderValue::from(self.remaining));
+        headers
+            .entry(RESET_HEADER)
+            .or_insert_with(|| HeaderValue::from(reset_secs));
+        if !self.allowed {
+            headers
+                .entry(RETRY_AFTER)
+                .or_insert_with(|| HeaderValue::from(reset_secs));
+        }
+    }
+}
+
+struct Windows {
+    /// Window the map was last swept in; entries from older windows are dropped on rollover
+    current: u64,
+    counts: HashMap<String, (u64, u32)>,
+}
+
+/// Fixed-window request counter per key. Windows are aligned to multiples of `window`
+/// since the Unix epoch, so a one-day window resets at midnight UTC.
+/// Counts live in memory and are per gateway instance.
+pub struct FixedWindowLimiter {
+    limit: u32,
+    window_ms: u64,
+    windows: Mutex<Windows>,
+}
+
+impl FixedWindowLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            window_ms: (window.as_millis() as u64).max(1),
+            windows: Mutex::new(Windows {
+                current: 0,
+                counts: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Counts a request for `key` unless the key has used up its limit for this window
+    pub fn check(&self, key: &str) -> RateLimitDecision {
+        self.check_at(key, now_ms())
+    }
+
+    /// Gives back a request counted for `key` that did not go ahead, if its window is still
+    /// the current one
+    pub fn refund(&self, key: &str) {
+        self.refund_at(key, now_ms());
+    }
+
+    fn check_at(&self, key: &str, now_ms: u64) -> RateLimitDecision {
+        let window = now_ms / self.window_ms;
+        let reset_after = Duration::from_millis((window + 1) * self.window_ms - now_ms);
+
+        let mut windows = self.windows.lock().unwrap();
+        if windows.current != window {
+            windows.counts.retain(|_, (w, _)| *w == window);
+            windows.current = window;
+        }
+
+        let (counted_window, count) = windows.counts.entry(key.to_string()).or_insert((window, 0));
+        if *counted_window != window {
+            *counted_window = window;
+            *count = 0;
+        }
+
+        let allowed = *count < self.limit;
+        if allowed {
+            *count += 1;
+        }
+        RateLimitDecision {
+            allowed,
+            limit: self.limit,
+            remaining: self.limit - *count,
+            reset_after,
+        }
+    }
+
+    fn refund_at(&self, key: &str, now_ms: u64) {
+        let window = now_ms / self.window_ms;
+        let mut windows = self.windows.lock().unwrap();
+        if let Some((counted_window, count)) = windows.counts.get_mut(key)
+            && *counted_window == window
+        {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+struct RouteLimiter {
+    method: Method,
+    path: String,
+    limiter: FixedWindowLimiter,
+}
+
+/// Per-client request limits: one limiter per configured route, and a shared default
+/// limiter for every other route
+#[derive(Clone)]
+pub struct RateLimiter {
+    enabled: bool,
+    trust_forwarded_for: bool,
+    default: Arc<FixedWindowLimiter>,
+    routes: Arc<Vec<RouteLimiter>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        let limiter = |limit: &RateLimit| FixedWindowLimiter::new(limit.requests, limit.window());
+        let routes = config
+            .routes
+            .iter()
+            .map(|route| RouteLimiter {
+                method: route.method.clone(),
+                path: route.path.clone(),
+                limiter: limiter(&route.limit),
+            })
+            .collect();
+
+        Self {
+            enabled: config.enabled,
+            trust_forwarded_for: config.trust_forwarded_for,
+            default: Arc::new(limiter(&config.default)),
+            routes: Arc::new(routes),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Whether the client address may be taken from `X-Forwarded-For`, i.e. the gateway
+    /// runs behind a proxy that sets it
+    pub fn trust_forwarded_for(&self) -> bool {
+        self.trust_forwarded_for
+    }
+
+    /// Counts a request by `client` to the route template `path`. Returns which limit was
+    /// applied, for metrics, along with the decision.
+    pub fn check(&self, method: &Method, path: &str, client: &str) -> (&str, RateLimitDecision) {
+        match self
+            .routes
+            .iter()
+            .find(|route| route.method == method && route.path == path)
+        {
+            Some(route) => (route.path.as_str(), route.limiter.check(client)),
+            None => ("default", self.default.check(client)),
+        }
+    }
+}
+
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("enabled", &self.enabled)
+            .field("trust_forwarded_for", &self.trust_forwarded_for)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+    const WINDOW_MS: u64 = 60_000;
+
+    #[test]
+    fn allows_up_to_the_limit_within_a_window() {
+        let limiter = FixedWindowLimiter::new(2, WINDOW);
+
+        let first = limiter.check_at("a", 1_000);
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 1);
+        assert_eq!(first.reset_after, Duration::from_millis(WINDOW_MS - 1_000));
+        assert!(limiter.check_at("a", 2_000).allowed);
+
+        let rejected = limiter.check_at("a", WINDOW_MS - 1);
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.remaining, 0);
+        assert_eq!(rejected.reset_after, Duration::from_millis(1));
+    }
+
+    #[test]
+    fn starts_over_at_the_window_boundary() {
+        let limiter = FixedWindowLimiter::new(1, WINDOW);
+        assert!(limiter.check_at("a", WINDOW_MS - 1).allowed);
+        assert!(!limiter.check_at("a", WINDOW_MS - 1).allowed);
+
+        let next = limiter.check_at("a", WINDOW_MS);
+        assert!(next.allowed);
+        assert_eq!(next.reset_after, WINDOW);
+    }
+
+    #[test]
+    fn counts_keys_separately() {
+        let limiter = FixedWindowLimiter::new(1, WINDOW);
+        assert!(limiter.check_at("a", 0).allowed);
+        assert!(limiter.check_at("b", 0).allowed);
+        assert!(!limiter.check_at("a", 0).allowed);
+        assert!(!limiter.check_at("b", 0).allowed);
+    }
+
+    #[test]
+    fn refund_frees_a_request_in_the_same_window_only() {
+        let limiter = FixedWindowLimiter::new(1, WINDOW);
+        assert!(limiter.check_at("a", 0).allowed);
+        limiter.refund_at("a", 0);
+        assert!(limiter.check_at("a", 0).allowed);
+
+        // a refund after the window rolled over must not credit the new window
+        limiter.refund_at("a", WINDOW_MS);
+        assert!(limiter.check_at("a", WINDOW_MS).allowed);
+        assert!(!limiter.check_at("a", WINDOW_MS).allowed);
+    }
+
+    #[test]
+    fn refund_of_unknown_key_is_ignored() {
+        let limiter = FixedWindowLimiter::new(1, WINDOW);
+        limiter.refund_at("a", 0);
+        assert!(limiter.check_at("a", 0).allowed);
+        assert!(!limiter.check_at("a", 0).allowed);
+    }
+}
@@ -1,12 +1,17 @@
-use axum::{Json, response::IntoResponse};
+use axum::{
+    Json,
+    response::{IntoResponse, Response},
+};
 use hyper::StatusCode;
-use serde::Serialize;
+use tonic::Code;
+use tracing::{error, warn};
+
+use crate::dtos::{ApiError, ErrorCode, FieldError};
+use crate::middleware::request_id;
 
-#[derive(Serialize)]
-#[serde(untagged)]
 pub enum ApiResponse<T> {
     Success(T),
-    Error { status: u16, message: String },
+    Error(ApiError),
 }
 
 impl<T> ApiResponse<T> {
@@ -14,41 +19,191 @@ impl<T> ApiResponse<T> {
         ApiResponse::Success(data)
     }
 
-    pub fn created(data: T) -> Self {
-        ApiResponse::Success(data)
+    pub fn not_found(msg: &str) -> Self {
+        ApiResponse::Error(ApiError::new(
+            StatusCode::NOT_FOUND,
+            ErrorCode::NotFound,
+            msg,
+        ))
     }
 
-    pub fn error(status: u16, msg: &str) -> Self {
-        ApiResponse::Error {
-            status,
-            message: msg.to_string(),
-        }
+    pub fn internal_error(msg: &str) -> Self {
+        ApiResponse::Error(ApiError::internal(msg))
     }
 
-    pub fn not_found(msg: &str) -> Self {
-        ApiResponse::Error {
-            status: StatusCode::NOT_FOUND.as_u16(),
-            message: msg.to_string(),
-        }
+    pub fn from_grpc_status(context: &str, status: &tonic::Status) -> Self {
+        ApiResponse::Error(ApiError::from_grpc_status(context, status))
     }
 
-    pub fn internal_error(msg: &str) -> Self {
-        ApiResponse::Error {
-            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
-            message: msg.to_string(),
+    /// Re-types an error so it can be returned from a call with a different success type
+    pub fn cast_error<U>(self) -> ApiResponse<U> {
+        match self {
+            ApiResponse::Success(_) => ApiResponse::internal_error("Unexpected success"),
+            ApiResponse::Error(e) => ApiResponse::Error(e),
         }
     }
 
-    pub fn into_axum_response(self) -> impl IntoResponse
+    pub fn into_axum_response(self) -> Response
     where
-        T: Serialize,
+        T: serde::Serialize,
     {
-        let status = match &self {
-            ApiResponse::Success(_) => StatusCode::OK,
-            ApiResponse::Error { status, .. } => {
-                StatusCode::from_u16(*status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+        match self {
+            ApiResponse::Success(data) => (StatusCode::OK, Json(data)).into_response(),
+            ApiResponse::Error(e) => e.into_response(),
+        }
+    }
+}
+
+impl<T> From<ApiError> for ApiResponse<T> {
+    fn from(e: ApiError) -> Self {
+        ApiResponse::Error(e)
+    }
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: ErrorCode, message: &str) -> Self {
+        Self {
+            status: status.as_u16(),
+            code,
+            message: message.to_string(),
+            details: Vec::new(),
+            request_id: None,
+        }
+    }
+
+    /// Builds an error for a plain HTTP status, e.g. one returned by the cheatsheet API
+    pub fn from_status(status: u16, message: &str) -> Self {
+        let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        Self::new(status, error_code(status), message)
+    }
+
+    pub fn bad_request(message: &str) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, ErrorCode::InvalidArgument, message)
+    }
+
+    pub fn unauthenticated(message: &str) -> Self {
+        Self::new(
+            StatusCode::UNAUTHORIZED,
+            ErrorCode::Unauthenticated,
+            message,
+        )
+    }
+
+    pub fn rate_limited(message: &str) -> Self {
+        Self::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::RateLimited,
+            message,
+        )
+    }
+
+    pub fn internal(message: &str) -> Self {
+        Self::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::InternalError,
+            message,
+        )
+    }
+
+    pub fn with_details(mut self, details: Vec<FieldError>) -> Self {
+        self.details = details;
+        self
+    }
+
+    /// Translates a failed gRPC call into an HTTP error. `context` is only logged,
+    /// and backend messages are only forwarded for client errors.
+    pub fn from_grpc_status(context: &str, status: &tonic::Status) -> Self {
+        let error = match status.code() {
+            Code::Unauthenticated => Self::unauthenticated(status.message()),
+            Code::NotFound => {
+                Self::new(StatusCode::NOT_FOUND, ErrorCode::NotFound, status.message())
             }
+            Code::InvalidArgument => Self::bad_request(status.message()),
+            Code::ResourceExhausted => Self::rate_limited(status.message()),
+            Code::PermissionDenied => Self::new(
+                StatusCode::FORBIDDEN,
+                ErrorCode::PermissionDenied,
+                status.message(),
+            ),
+            Code::Unavailable => Self::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::ServiceUnavailable,
+                "User service is unavailable",
+            ),
+            Code::DeadlineExceeded => Self::new(
+                StatusCode::GATEWAY_TIMEOUT,
+                ErrorCode::DeadlineExceeded,
+                "User service timed out",
+            ),
+            _ => Self::internal("Internal server error"),
         };
-        (status, Json(self))
+        // client errors are the caller's doing, so they should not page anyone
+        if error.status_code().is_server_error() {
+            error!("{} error: {:?}", context, status);
+        } else {
+            warn!("{} rejected: {:?}", context, status);
+        }
+        error
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(mut self) -> Response {
+        if self.request_id.is_none() {
+            self.request_id = request_id::current();
+        }
+        (self.status_code(), Json(self)).into_response()
+    }
+}
+
+fn error_code(status: StatusCode) -> ErrorCode {
+    match status {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => ErrorCode::InvalidArgument,
+        StatusCode::UNAUTHORIZED => ErrorCode::Unauthenticated,
+        StatusCode::FORBIDDEN => ErrorCode::PermissionDenied,
+        StatusCode::NOT_FOUND => ErrorCode::NotFound,
+        StatusCode::CONFLICT => ErrorCode::Conflict,
+        StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+        StatusCode::SERVICE_UNAVAILABLE => ErrorCode::ServiceUnavailable,
+        StatusCode::GATEWAY_TIMEOUT => ErrorCode::DeadlineExceeded,
+        StatusCode::BAD_GATEWAY => ErrorCode::UpstreamError,
+        s if s.is_client_error() => ErrorCode::InvalidArgument,
+        _ => ErrorCode::InternalError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_grpc_codes_to_http_statuses() {
+        let cases = [
+            (Code::Unauthenticated, StatusCode::UNAUTHORIZED),
+            (Code::NotFound, StatusCode::NOT_FOUND),
+            (Code::InvalidArgument, StatusCode::BAD_REQUEST),
+            (Code::ResourceExhausted, StatusCode::TOO_MANY_REQUESTS),
+            (Code::PermissionDenied, StatusCode::FORBIDDEN),
+            (Code::Unavailable, StatusCode::SERVICE_UNAVAILABLE),
+            (Code::DeadlineExceeded, StatusCode::GATEWAY_TIMEOUT),
+            (Code::Internal, StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (code, expected) in cases {
+            let error = ApiError::from_grpc_status("Test", &tonic::Status::new(code, "message"));
+            assert_eq!(error.status_code(), expected, "{:?}", code);
+        }
+    }
+
+    #[test]
+    fn hides_internal_messages() {
+        let error = ApiError::from_grpc_status(
+            "Test",
+            &tonic::Status::internal("connection to 10.0.0.3 refused"),
+        );
+        assert_eq!(error.message, "Internal server error");
     }
 }
@@ -0,0 +1,160 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use hashlink::LruCache;
+use sha2::{Digest, Sha256};
+
+use crate::dtos;
+
+type TokenHash = [u8; 32];
+
+struct Entry {
+    user: dtos::ValidateTokenResponse,
+    expires_at: Instant,
+}
+
+/// Bounded in-memory cache of successful token validations, keyed by token hash. An entry
+/// lives for `ttl` at most and never past the token's own expiry; when the cache is full
+/// the least recently used entry makes room.
+pub struct TokenCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<LruCache<TokenHash, Entry>>,
+}
+
+impl TokenCache {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: Mutex::new(LruCache::new(max_entries.max(1))),
+        }
+    }
+
+    fn hash(token: &str) -> TokenHash {
+        Sha256::digest(token.as_bytes()).into()
+    }
+
+    pub fn get(&self, token: &str) -> Option<dtos::ValidateTokenResponse> {
+        let key = Self::hash(token);
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.user.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Caches a validation of a token that expires at `token_expires_at`, if known
+    pub fn insert(
+        &self,
+        token: &str,
+        user: dtos::ValidateTokenResponse,
+        token_expires_at: Option<SystemTime>,
+    ) {
+        let remaining = token_expires_at.map_or(self.ttl, |expires_at| {
+            expires_at
+                .duration_since(SystemTime::now())
+                .unwrap_or_default()
+        });
+        let ttl = self.ttl.min(remaining);
+        if self.max_entries == 0 || ttl.is_zero() {
+            return;
+        }
+
+        self.entries.lock().unwrap().insert(
+            Self::hash(token),
+            Entry {
+                user,
+                expires_at: Instant::now() + ttl,
+            },
+        );
+    }
+
+    pub fn evict(&self, token: &str) {
+        self.entries.lock().unwrap().remove(&Self::hash(token));
+    }
+
+    /// Drops every cached token belonging to the user, e.g. after signing out everywhere
+    pub fn evict_user(&self, user_id: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        let keys: Vec<TokenHash> = entries
+            .iter()
+            .filter(|(_, entry)| entry.user.id == user_id)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in keys {
+            entries.remove(&key);
+        }
+    }
+}
+
+impl std::fmt::Debug for TokenCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenCache")
+            .field("ttl", &self.ttl)
+            .field("max_entries", &self.max_entries)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    fn user(id: &str) -> dtos::ValidateTokenResponse {
+        dtos::ValidateTokenResponse {
+            id: id.to_string(),
+            email: format!("{}@example.com", id),
+            name: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn does_not_outlive_the_token() {
+        let cache = TokenCache::new(TTL, 10);
+        cache.insert("expired", user("1"), Some(SystemTime::now()));
+        cache.insert(
+            "valid",
+            user("1"),
+            Some(SystemTime::now() + Duration::from_secs(3600)),
+        );
+        cache.insert("unknown expiry", user("1"), None);
+
+        assert!(cache.get("expired").is_none());
+        assert!(cache.get("valid").is_some());
+        assert!(cache.get("unknown expiry").is_some());
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_entry_when_full() {
+        let cache = TokenCache::new(TTL, 2);
+        cache.insert("a", user("1"), None);
+        cache.insert("b", user("2"), None);
+        cache.get("a");
+        cache.insert("c", user("3"), None);
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+    }
+
+    #[test]
+    fn evicts_every_token_of_a_user() {
+        let cache = TokenCache::new(TTL, 10);
+        cache.insert("a", user("1"), None);
+        cache.insert("b", user("1"), None);
+        cache.insert("c", user("2"), None);
+
+        cache.evict_user("1");
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+    }
+}
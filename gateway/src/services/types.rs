@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::dtos::GenerationStage;
+
 #[derive(Debug, Deserialize)]
 pub struct ServiceResponse<T> {
     pub data: T,
@@ -9,6 +11,12 @@ pub struct ServiceResponse<T> {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EmptyResponse {}
 
+/// Error body returned by the cheatsheet API
+#[derive(Debug, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+}
+
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize)]
 pub struct GetPresignedUploadUrlData {
@@ -37,12 +45,29 @@ pub struct File {
 #[derive(Debug, Deserialize)]
 pub struct FilesData {
     pub files: Vec<File>,
+    /// The caller's own shares of the listed files
+    #[serde(default)]
+    pub shares: Vec<Share>,
+    /// Number of files matching the filters, across all pages
+    #[serde(default)]
+    pub total: usize,
+    /// Continues a listing that was asked for with a `limit`
+    #[serde(default, rename = "nextCursor")]
+    pub next_cursor: Option<String>,
 }
 
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize)]
 pub struct Share {
     pub UserID: String,
+    /// Empty on shares made before roles existed, and on the owner's own share
+    #[serde(default)]
+    pub Role: String,
+    #[serde(default)]
+    pub FileID: String,
+    /// RFC 3339; `None` for permanent shares
+    #[serde(default)]
+    pub ExpiresAt: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,3 +85,14 @@ pub struct ShareData {
 pub struct UnshareData {
     pub unshared: bool,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateData {
+    pub file_id: String,
+    pub key: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerationProgressData {
+    pub stage: GenerationStage,
+}
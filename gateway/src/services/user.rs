@@ -1,9 +1,26 @@
-use log::error;
+use openexam_common::request_signer::{
+    RequestSigner, SIGNATURE_HEADER, TIMESTAMP_HEADER, USER_ID_HEADER,
+};
+use prost::Message;
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tonic::Status;
 use tonic::transport::Channel;
+use tracing::instrument;
 
 use crate::dtos;
-use crate::proto::user::{GetAllUsersRequest, ValidateTokenRequest};
+use crate::metrics;
+use crate::middleware::request_id;
+use crate::proto::user::{
+    self as proto, CreateShareLinkRequest, GetFileInvitationsRequest, GetFileShareLinksRequest,
+    GetUsersByIdsRequest, InviteByEmailRequest, LogoutRequest, RefreshTokenRequest,
+    ResolveShareLinkRequest, RevokeInvitationRequest, RevokeShareLinkRequest, SearchUsersRequest,
+    ValidateTokenRequest,
+};
+use crate::services::token_cache::TokenCache;
+use crate::telemetry;
 use crate::{
     proto::user::{GetGoogleLoginUrlRequest, LoginRequest, user_client::UserClient},
     services::response::ApiResponse,
@@ -12,32 +29,64 @@ use crate::{
 #[derive(Debug, Clone)]
 pub struct UserService {
     user_client: Arc<UserClient<Channel>>,
+    token_cache: Arc<TokenCache>,
+    signer: RequestSigner,
 }
 
 impl UserService {
-    pub fn new(user_client: UserClient<Channel>) -> Self {
+    pub fn new(
+        user_client: UserClient<Channel>,
+        token_cache: TokenCache,
+        signer: RequestSigner,
+    ) -> Self {
         Self {
             user_client: Arc::new(user_client),
+            token_cache: Arc::new(token_cache),
+            signer,
         }
     }
 
+    /// Like [`outgoing`], for calls that act for `user_id`. The user service only trusts
+    /// the user such a call names when it is signed, along with the method and message.
+    fn signed<T: Message>(&self, path: &str, user_id: &str, message: T) -> tonic::Request<T> {
+        let (timestamp, signature) = self
+            .signer
+            .sign_call(path, user_id, &message.encode_to_vec());
+        let mut request = outgoing(message);
+        let metadata = request.metadata_mut();
+        for (key, value) in [
+            (USER_ID_HEADER, user_id.to_string()),
+            (TIMESTAMP_HEADER, timestamp),
+            (SIGNATURE_HEADER, signature),
+        ] {
+            if let Ok(value) = value.parse() {
+                metadata.insert(key, value);
+            }
+        }
+        request
+    }
+
+    #[instrument(name = "user_grpc.get_google_login_url", skip_all)]
     pub async fn get_google_login_url(&self) -> ApiResponse<String> {
         let mut client = (*self.user_client).clone();
         let request = GetGoogleLoginUrlRequest {};
 
-        match client.get_google_login_url(request).await {
+        match observe(
+            "get_google_login_url",
+            client.get_google_login_url(outgoing(request)),
+        )
+        .await
+        {
             Ok(response) => ApiResponse::ok(response.into_inner().url),
-            Err(e) => {
-                error!("Get google login url error: {:?}", e);
-                ApiResponse::internal_error(&format!("Get google login url error: {:?}", e))
-            }
+            Err(e) => ApiResponse::from_grpc_status("Get google login url", &e),
         }
     }
+    #[instrument(name = "user_grpc.login", skip_all)]
     pub async fn login(&self, request: dtos::LoginRequest) -> ApiResponse<dtos::LoginResponse> {
         let mut client = (*self.user_client).clone();
         let request = LoginRequest { code: request.code };
 
-        match client.login(request).await {
+        match observe("login", client.login(outgoing(request))).await {
             Ok(response) => {
                 let response = response.into_inner();
                 ApiResponse::ok(dtos::LoginResponse {
@@ -45,60 +94,401 @@ impl UserService {
                     email: response.email,
                     name: response.name,
                     token: response.token,
+                    refresh_token: response.refresh_token,
                 })
             }
-            Err(e) => {
-                error!("Login error: {:?}", e);
-                ApiResponse::internal_error(&format!("Login error: {:?}", e))
-            }
+            Err(e) => ApiResponse::from_grpc_status("Login", &e),
         }
     }
 
+    #[instrument(name = "user_grpc.validate_token", skip_all)]
     pub async fn validate_token(
         &self,
         request: dtos::ValidateTokenRequest,
     ) -> ApiResponse<dtos::ValidateTokenResponse> {
+        let cached = self.token_cache.get(&request.token);
+        metrics::record_token_cache_lookup(cached.is_some());
+        if let Some(user) = cached {
+            return ApiResponse::ok(user);
+        }
+
         let mut client = (*self.user_client).clone();
+        let token = request.token;
         let request = ValidateTokenRequest {
-            token: request.token,
+            token: token.clone(),
         };
 
-        match client.validate_token(request).await {
+        match observe("validate_token", client.validate_token(outgoing(request))).await {
             Ok(response) => {
                 let response = response.into_inner();
-                ApiResponse::ok(dtos::ValidateTokenResponse {
+                let user = dtos::ValidateTokenResponse {
                     id: response.id,
                     email: response.email,
                     name: response.name,
+                };
+                // older user services leave the expiry unset
+                let expires_at = (response.expires_at > 0)
+                    .then(|| UNIX_EPOCH + Duration::from_secs(response.expires_at));
+                self.token_cache.insert(&token, user.clone(), expires_at);
+                ApiResponse::ok(user)
+            }
+            Err(e) => ApiResponse::from_grpc_status("Validate token", &e),
+        }
+    }
+
+    #[instrument(name = "user_grpc.refresh_token", skip_all)]
+    pub async fn refresh_token(
+        &self,
+        request: dtos::RefreshTokenRequest,
+    ) -> ApiResponse<dtos::RefreshTokenResponse> {
+        let mut client = (*self.user_client).clone();
+        let request = RefreshTokenRequest {
+            refresh_token: request.refresh_token,
+        };
+
+        match observe("refresh_token", client.refresh_token(outgoing(request))).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                ApiResponse::ok(dtos::RefreshTokenResponse {
+                    token: response.token,
+                    refresh_token: response.refresh_token,
                 })
             }
-            Err(e) => {
-                error!("Validate token error: {:?}", e);
-                ApiResponse::internal_error(&format!("Validate token error: {:?}", e))
+            Err(e) => ApiResponse::from_grpc_status("Refresh token", &e),
+        }
+    }
+
+    #[instrument(name = "user_grpc.logout", skip_all)]
+    pub async fn logout(&self, request: dtos::LogoutRequest) -> ApiResponse<dtos::LogoutResponse> {
+        let mut client = (*self.user_client).clone();
+        let token = request.token;
+        let all_sessions = request.all_sessions;
+        let request = LogoutRequest {
+            token: token.clone(),
+            all_sessions,
+        };
+
+        match observe("logout", client.logout(outgoing(request))).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                self.token_cache.evict(&token);
+                if all_sessions {
+                    self.token_cache.evict_user(&response.user_id);
+                }
+                ApiResponse::ok(dtos::LogoutResponse {
+                    success: response.success,
+                })
+            }
+            Err(e) => ApiResponse::from_grpc_status("Logout", &e),
+        }
+    }
+
+    #[instrument(name = "user_grpc.search_users", skip_all)]
+    pub async fn search_users(
+        &self,
+        query: dtos::SearchUsersQuery,
+    ) -> ApiResponse<dtos::SearchUsersResponse> {
+        let mut client = (*self.user_client).clone();
+        let request = SearchUsersRequest {
+            query: query.q,
+            limit: query.limit.unwrap_or_default(),
+            cursor: query.cursor.unwrap_or_default(),
+        };
+
+        match observe("search_users", client.search_users(outgoing(request))).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                ApiResponse::ok(dtos::SearchUsersResponse {
+                    users: response
+                        .users
+                        .into_iter()
+                        .map(|user| dtos::UserProfile {
+                            id: user.id,
+                            name: user.name,
+                        })
+                        .collect(),
+                    next_cursor: Some(response.next_cursor).filter(|cursor| !cursor.is_empty()),
+                })
             }
+            Err(e) => ApiResponse::from_grpc_status("Search users", &e),
         }
     }
 
-    pub async fn get_all_users(&self) -> ApiResponse<dtos::GetAllUsersResponse> {
+    /// Display names for the given user ids. Ids that don't belong to any user are missing
+    /// from the map.
+    #[instrument(name = "user_grpc.get_users_by_ids", skip_all)]
+    pub async fn get_user_names(&self, ids: Vec<String>) -> ApiResponse<HashMap<String, String>> {
+        if ids.is_empty() {
+            return ApiResponse::ok(HashMap::new());
+        }
+
         let mut client = (*self.user_client).clone();
-        let request = GetAllUsersRequest {};
+        let request = GetUsersByIdsRequest { ids };
 
-        match client.get_all_users(request).await {
-            Ok(response) => ApiResponse::ok(dtos::GetAllUsersResponse {
-                users: response
+        match observe(
+            "get_users_by_ids",
+            client.get_users_by_ids(outgoing(request)),
+        )
+        .await
+        {
+            Ok(response) => ApiResponse::ok(
+                response
                     .into_inner()
                     .users
                     .into_iter()
-                    .map(|user| dtos::UserProfile {
-                        id: user.id,
-                        name: user.name,
+                    .map(|user| (user.id, user.name))
+                    .collect(),
+            ),
+            Err(e) => ApiResponse::from_grpc_status("Get users by ids", &e),
+        }
+    }
+
+    /// Resolves `email` to a registered user's id, or records a pending invitation to the
+    /// file and returns `None`. The user service checks that `invited_by` may share the file
+    /// at `role`.
+    #[instrument(name = "user_grpc.invite_by_email", skip_all)]
+    pub async fn invite_by_email(
+        &self,
+        email: String,
+        file_id: String,
+        invited_by: String,
+        role: dtos::ShareRole,
+        expires_at: Option<String>,
+    ) -> ApiResponse<Option<String>> {
+        let mut client = (*self.user_client).clone();
+        let request = self.signed(
+            "/user.User/InviteByEmail",
+            &invited_by,
+            InviteByEmailRequest {
+                email,
+                file_id,
+                invited_by: invited_by.clone(),
+                role: role.as_str().to_string(),
+                expires_at: expires_at.unwrap_or_default(),
+            },
+        );
+
+        match observe("invite_by_email", client.invite_by_email(request)).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                ApiResponse::ok((!response.pending).then_some(response.user_id))
+            }
+            Err(e) => ApiResponse::from_grpc_status("Invite by email", &e),
+        }
+    }
+
+    /// Returns whether a pending invitation existed. Needs the co-owner role on the file.
+    #[instrument(name = "user_grpc.revoke_invitation", skip_all)]
+    pub async fn revoke_invitation(
+        &self,
+        caller_id: String,
+        email: String,
+        file_id: String,
+    ) -> ApiResponse<bool> {
+        let mut client = (*self.user_client).clone();
+        let request = self.signed(
+            "/user.User/RevokeInvitation",
+            &caller_id,
+            RevokeInvitationRequest { email, file_id },
+        );
+
+        match observe("revoke_invitation", client.revoke_invitation(request)).await {
+            Ok(response) => ApiResponse::ok(response.into_inner().revoked),
+            Err(e) => ApiResponse::from_grpc_status("Revoke invitation", &e),
+        }
+    }
+
+    /// Pending invitations to the file. Needs the co-owner role on it.
+    #[instrument(name = "user_grpc.get_file_invitations", skip_all)]
+    pub async fn get_file_invitations(
+        &self,
+        caller_id: String,
+        file_id: String,
+    ) -> ApiResponse<Vec<dtos::PendingInvitation>> {
+        let mut client = (*self.user_client).clone();
+        let request = self.signed(
+            "/user.User/GetFileInvitations",
+            &caller_id,
+            GetFileInvitationsRequest { file_id },
+        );
+
+        match observe("get_file_invitations", client.get_file_invitations(request)).await {
+            Ok(response) => ApiResponse::ok(
+                response
+                    .into_inner()
+                    .invitations
+                    .into_iter()
+                    .map(|invitation| dtos::PendingInvitation {
+                        email: invitation.email,
+                        invited_at: chrono::DateTime::from_timestamp(invitation.created_at, 0)
+                            .unwrap_or_default()
+                            .to_rfc3339(),
+                        role: dtos::ShareRole::parse(&invitation.role),
+                        expires_at: Some(invitation.expires_at)
+                            .filter(|expires_at| !expires_at.is_empty()),
                     })
                     .collect(),
-            }),
-            Err(e) => {
-                error!("Get all users error: {:?}", e);
-                ApiResponse::internal_error(&format!("Get all users error: {:?}", e))
+            ),
+            Err(e) => ApiResponse::from_grpc_status("Get file invitations", &e),
+        }
+    }
+
+    /// Creates a public link to the file. The user service checks that `created_by` may
+    /// share the file.
+    #[instrument(name = "user_grpc.create_share_link", skip_all)]
+    pub async fn create_share_link(
+        &self,
+        file_id: String,
+        created_by: String,
+        password: Option<String>,
+        expires_at: Option<String>,
+    ) -> ApiResponse<dtos::CreateShareLinkResponse> {
+        let mut client = (*self.user_client).clone();
+        let request = self.signed(
+            "/user.User/CreateShareLink",
+            &created_by,
+            CreateShareLinkRequest {
+                file_id,
+                created_by: created_by.clone(),
+                password: password.unwrap_or_default(),
+                expires_at: expires_at.unwrap_or_default(),
+            },
+        );
+
+        match observe("create_share_link", client.create_share_link(request)).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                match response.link {
+                    Some(link) => ApiResponse::ok(dtos::CreateShareLinkResponse {
+                        link: share_link(link),
+                        token: response.token,
+                    }),
+                    None => ApiResponse::internal_error("User service returned no share link"),
+                }
             }
+            Err(e) => ApiResponse::from_grpc_status("Create share link", &e),
+        }
+    }
+
+    /// Links to the file. Needs the resharer role on it.
+    #[instrument(name = "user_grpc.get_file_share_links", skip_all)]
+    pub async fn get_file_share_links(
+        &self,
+        caller_id: String,
+        file_id: String,
+    ) -> ApiResponse<Vec<dtos::ShareLink>> {
+        let mut client = (*self.user_client).clone();
+        let request = self.signed(
+            "/user.User/GetFileShareLinks",
+            &caller_id,
+            GetFileShareLinksRequest { file_id },
+        );
+
+        match observe("get_file_share_links", client.get_file_share_links(request)).await {
+            Ok(response) => ApiResponse::ok(
+                response
+                    .into_inner()
+                    .links
+                    .into_iter()
+                    .map(share_link)
+                    .collect(),
+            ),
+            Err(e) => ApiResponse::from_grpc_status("Get file share links", &e),
+        }
+    }
+
+    /// Returns whether the link existed. Co-owners may revoke any link to the file,
+    /// resharers only their own.
+    #[instrument(name = "user_grpc.revoke_share_link", skip_all)]
+    pub async fn revoke_share_link(
+        &self,
+        caller_id: String,
+        id: String,
+        file_id: String,
+    ) -> ApiResponse<bool> {
+        let mut client = (*self.user_client).clone();
+        let request = self.signed(
+            "/user.User/RevokeShareLink",
+            &caller_id,
+            RevokeShareLinkRequest { id, file_id },
+        );
+
+        match observe("revoke_share_link", client.revoke_share_link(request)).await {
+            Ok(response) => ApiResponse::ok(response.into_inner().revoked),
+            Err(e) => ApiResponse::from_grpc_status("Revoke share link", &e),
+        }
+    }
+
+    /// The link a token belongs to. Fails with 404 for unknown or expired tokens, and 401
+    /// when the link's password is missing or wrong.
+    #[instrument(name = "user_grpc.resolve_share_link", skip_all)]
+    pub async fn resolve_share_link(
+        &self,
+        token: String,
+        password: Option<String>,
+    ) -> ApiResponse<dtos::ShareLink> {
+        let mut client = (*self.user_client).clone();
+        let request = ResolveShareLinkRequest {
+            token,
+            password: password.unwrap_or_default(),
+        };
+
+        match observe(
+            "resolve_share_link",
+            client.resolve_share_link(outgoing(request)),
+        )
+        .await
+        {
+            Ok(response) => match response.into_inner().link {
+                Some(link) => ApiResponse::ok(share_link(link)),
+                None => ApiResponse::internal_error("User service returned no share link"),
+            },
+            Err(e) => ApiResponse::from_grpc_status("Resolve share link", &e),
         }
     }
 }
+
+fn share_link(link: proto::ShareLink) -> dtos::ShareLink {
+    dtos::ShareLink {
+        id: link.id,
+        file_id: link.file_id,
+        created_by: link.created_by,
+        created_at: chrono::DateTime::from_timestamp(link.created_at, 0)
+            .unwrap_or_default()
+            .to_rfc3339(),
+        expires_at: Some(link.expires_at).filter(|expires_at| !expires_at.is_empty()),
+        has_password: link.has_password,
+    }
+}
+
+/// Wraps a message in a tonic request carrying the current request id and trace context
+fn outgoing<T>(message: T) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    if let Some(id) = request_id::current()
+        && let Ok(value) = id.parse()
+    {
+        request.metadata_mut().insert("x-request-id", value);
+    }
+    telemetry::inject_grpc_metadata(request.metadata_mut());
+    request
+}
+
+/// Times a user service call and records its outcome by gRPC code
+async fn observe<T>(
+    operation: &'static str,
+    call: impl Future<Output = Result<tonic::Response<T>, Status>>,
+) -> Result<tonic::Response<T>, Status> {
+    let started = Instant::now();
+    let result = call.await;
+    let outcome = match &result {
+        Ok(_) => tonic::Code::Ok,
+        Err(status) => status.code(),
+    };
+    metrics::record_upstream_call(
+        "user_grpc",
+        operation,
+        &format!("{:?}", outcome),
+        started.elapsed(),
+    );
+    result
+}
@@ -0,0 +1,59 @@
+use axum::http::{HeaderMap, Request};
+use openexam_common::telemetry::continue_trace;
+use opentelemetry::{global, propagation::Injector};
+use opentelemetry_http::HeaderInjector;
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tracing::{Span, field::Empty, info_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::middleware::request_id::REQUEST_ID_HEADER;
+
+/// Root span of an incoming HTTP request, continuing the caller's trace if it sent one
+pub fn make_http_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-");
+
+    let span = info_span!(
+        "http_request",
+        otel.name = %format!("{} {}", request.method(), request.uri().path()),
+        http.method = %request.method(),
+        http.path = %request.uri().path(),
+        http.status_code = Empty,
+        request_id = %request_id,
+    );
+
+    continue_trace(&span, request.headers());
+    span
+}
+
+/// Writes the current trace context into outgoing HTTP headers
+pub fn inject_http_headers(headers: &mut HeaderMap) {
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut HeaderInjector(headers))
+    });
+}
+
+/// Writes the current trace context into outgoing gRPC metadata
+pub fn inject_grpc_metadata(metadata: &mut MetadataMap) {
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut MetadataInjector(metadata))
+    });
+}
+
+struct MetadataInjector<'a>(&'a mut MetadataMap);
+
+impl Injector for MetadataInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(key), Ok(value)) = (
+            MetadataKey::from_bytes(key.as_bytes()),
+            MetadataValue::try_from(&value),
+        ) {
+            self.0.insert(key, value);
+        }
+    }
+}
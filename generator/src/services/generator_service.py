@@ -14,7 +14,7 @@ from config import Config
 from services.pdf_service import PDFService
 from services.s3_service import S3Service
 from utils.logger import setup_logger
-from services.cheatsheet_service import generate_cheatsheet
+from services.cheatsheet_service import generate_summary, write_in_pdf
 logger = setup_logger(__name__)
 
 
@@ -58,6 +58,7 @@ class GeneratorService:
             logger.info(f"Retrieved {len(files)} files from DynamoDB")
 
             # 2. Download PDFs from S3
+            self._send_progress(response_queue_url, request_id, "downloading_sources")
             pdf_contents = []
             for file in files:
                 key = file.get("key")
@@ -72,9 +73,14 @@ class GeneratorService:
             if not pdf_contents:
                 raise Exception("No valid PDF files found to merge")
 
-            # 3. Generate Cheatsheet PDFs
-            logger.info(f"Generating Cheatsheet from {len(pdf_contents)} PDFs")
-            cheatsheet = generate_cheatsheet(pdf_contents)
+            # 3. Summarise the PDFs and render the cheatsheet
+            self._send_progress(response_queue_url, request_id, "summarising")
+            logger.info(f"Summarising {len(pdf_contents)} PDFs")
+            summary = generate_summary(pdf_contents)
+
+            self._send_progress(response_queue_url, request_id, "rendering")
+            logger.info("Rendering cheatsheet PDF")
+            cheatsheet = write_in_pdf(summary)
             # 4. Upload Cheatsheet PDF to S3
             prefix = uuid.uuid4().hex[:6]
             output_key = f"cheatsheets/{user_id}/{prefix}_cheatsheet.pdf"
@@ -183,3 +189,28 @@ class GeneratorService:
         except ClientError as e:
             logger.error(f"Failed to send response to SQS: {str(e)}")
             # Don't raise - response queue failure shouldn't fail the generation
+
+    def _send_progress(
+        self, response_queue_url: str, request_id: str, stage: str
+    ) -> None:
+        """
+        Report which stage a generation has reached on the response queue.
+
+        Progress messages carry a "stage" instead of "success" so the consumer
+        can tell them apart from the final response.
+
+        Args:
+            response_queue_url: SQS response queue URL
+            request_id: Request ID from the original request
+            stage: Stage the generation is entering
+        """
+        try:
+            payload = {"request_id": request_id, "stage": stage}
+            self.sqs_client.send_message(
+                QueueUrl=response_queue_url, MessageBody=json.dumps(payload)
+            )
+            logger.info(f"Progress sent for request {request_id}: {stage}")
+
+        except ClientError as e:
+            logger.warning(f"Failed to send progress to SQS: {str(e)}")
+            # Progress is best-effort and must never fail the generation
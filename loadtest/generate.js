@@ -21,11 +21,27 @@ export default function () {
     Authorization: `Bearer ${TOKEN}`,
     "Content-Type": "application/json",
   };
-  // Allow up to 120 seconds per request, normally it take around 30s if server has no workload
-  const res = http.post(BASE_URL, payload, { headers, timeout: "120s" });
+  // Submitting returns a job right away; poll it until the worker is done
+  const res = http.post(BASE_URL, payload, { headers });
 
   check(res, {
-    "status 200": (r) => r.status === 200,
+    "status 202": (r) => r.status === 202,
+  });
+  if (res.status !== 202) {
+    return;
+  }
+
+  const jobId = res.json("job_id");
+  let status = res.json("status");
+  // normally it takes around 30s if server has no workload, give up after 120s
+  for (let i = 0; i < 60 && (status === "queued" || status === "running"); i++) {
+    sleep(2);
+    const poll = http.get(`${BASE_URL}/${jobId}`, { headers });
+    status = poll.json("status");
+  }
+
+  check(status, {
+    "job succeeded": (s) => s === "succeeded",
   });
 
   // simulate user waiting between generations (~30 seconds)
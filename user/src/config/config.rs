@@ -1,10 +1,18 @@
 use std::env;
 
+use anyhow::Context;
+
+/// Shortest JWT_SECRET accepted; HS256 keys should be at least as long as the hash
+const MIN_JWT_SECRET_BYTES: usize = 32;
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub database: DatabaseConfig,
     pub oauth: OAuthConfig,
+    pub jwt: JwtConfig,
     pub server: ServerConfig,
+    pub telemetry: TelemetryConfig,
+    pub cheatsheet: CheatsheetConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -22,9 +30,37 @@ pub struct OAuthConfig {
     pub token_url: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub secret: String,
+    pub issuer: String,
+    pub ttl_secs: u64,
+    pub refresh_ttl_secs: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub grpc_addr: String,
+    pub metrics_addr: String,
+    /// How long readiness fails before the listener stops, so load balancers stop routing here
+    pub shutdown_pre_stop_delay_secs: u64,
+    pub shutdown_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    pub service_name: String,
+    pub otlp_endpoint: Option<String>,
+}
+
+/// Where pending share invitations are granted when the invited email signs up
+#[derive(Debug, Clone)]
+pub struct CheatsheetConfig {
+    pub api_url: String,
+    pub request_timeout_secs: u64,
+    /// Shared with the cheatsheet service, which only accepts requests signed with it, and
+    /// with the gateway, whose calls acting for a user must be signed with it
+    pub signing_secret: String,
 }
 
 impl Config {
@@ -32,7 +68,10 @@ impl Config {
         Ok(Self {
             database: DatabaseConfig::from_env()?,
             oauth: OAuthConfig::from_env()?,
+            jwt: JwtConfig::from_env()?,
             server: ServerConfig::from_env()?,
+            telemetry: TelemetryConfig::from_env()?,
+            cheatsheet: CheatsheetConfig::from_env()?,
         })
     }
 }
@@ -64,10 +103,70 @@ impl OAuthConfig {
     }
 }
 
+impl JwtConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            secret: env::var("JWT_SECRET")
+                .ok()
+                .filter(|v| v.len() >= MIN_JWT_SECRET_BYTES)
+                .with_context(|| {
+                    format!("JWT_SECRET must be set to at least {MIN_JWT_SECRET_BYTES} bytes")
+                })?,
+            issuer: env::var("JWT_ISSUER").unwrap_or_else(|_| "openexam-user".to_string()),
+            ttl_secs: env::var("JWT_TTL_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .expect("JWT_TTL_SECS must be a valid number"),
+            refresh_ttl_secs: env::var("REFRESH_TOKEN_TTL_SECS")
+                .unwrap_or_else(|_| "2592000".to_string())
+                .parse()
+                .expect("REFRESH_TOKEN_TTL_SECS must be a valid number"),
+        })
+    }
+}
+
 impl ServerConfig {
     fn from_env() -> anyhow::Result<Self> {
         Ok(Self {
             grpc_addr: env::var("GRPC_ADDR").unwrap_or_else(|_| "127.0.0.1:50051".to_string()),
+            metrics_addr: env::var("METRICS_ADDR").unwrap_or_else(|_| "127.0.0.1:9100".to_string()),
+            shutdown_pre_stop_delay_secs: env::var("SHUTDOWN_PRE_STOP_DELAY_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .expect("SHUTDOWN_PRE_STOP_DELAY_SECS must be a valid number"),
+            shutdown_timeout_secs: env::var("SHUTDOWN_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .expect("SHUTDOWN_TIMEOUT_SECS must be a valid number"),
+        })
+    }
+}
+
+impl TelemetryConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            service_name: env::var("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|_| "openexam-user".to_string()),
+            otlp_endpoint: env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .ok()
+                .filter(|v| !v.is_empty()),
+        })
+    }
+}
+
+impl CheatsheetConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            api_url: env::var("CHEATSHEET_API_URL")
+                .unwrap_or_else(|_| "http://127.0.0.1:3002".to_string()),
+            request_timeout_secs: env::var("CHEATSHEET_REQUEST_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .context("CHEATSHEET_REQUEST_TIMEOUT_SECS must be a valid number")?,
+            signing_secret: env::var("INTERNAL_SIGNING_SECRET")
+                .ok()
+                .filter(|v| !v.is_empty())
+                .context("INTERNAL_SIGNING_SECRET must be set")?,
         })
     }
 }
@@ -1,13 +1,55 @@
+use std::time::Duration;
+
 use crate::config::config::DatabaseConfig;
 use sqlx::{PgPool, postgres::PgPoolOptions};
+use tonic::Status;
+use tracing::{info, warn};
+
+/// How long a query waits for a pooled connection before giving up
+const ACQUIRE_TIMEOUT: Duration = Duration::from_secs(5);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
 
-pub async fn connect(config: &DatabaseConfig) -> PgPool {
+/// Creates the pool without touching the network; connections are opened on first use and
+/// re-opened by sqlx after Postgres restarts
+pub fn connect(config: &DatabaseConfig) -> anyhow::Result<PgPool> {
     let pool = PgPoolOptions::new()
         .max_connections(config.max_connections)
-        .connect(&config.url)
-        .await
-        .expect("Failed to connect to Postgres");
+        .acquire_timeout(ACQUIRE_TIMEOUT)
+        .connect_lazy(&config.url)?;
+    Ok(pool)
+}
+
+/// Creates the schema, retrying with exponential backoff until Postgres is reachable
+pub async fn ensure_schema(pool: &PgPool) {
+    let mut delay = INITIAL_BACKOFF;
+    loop {
+        match create_tables(pool).await {
+            Ok(()) => {
+                info!("Database schema is ready");
+                return;
+            }
+            Err(e) => {
+                warn!("Database not ready, retrying in {:?}: {:?}", delay, e);
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+/// Maps a repository error to a gRPC status. Connectivity problems become UNAVAILABLE so
+/// callers can tell a database outage apart from a bug
+pub fn error_status(e: &anyhow::Error, message: &str) -> Status {
+    match e.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_)) => {
+            Status::unavailable("Database is unavailable")
+        }
+        _ => Status::internal(message),
+    }
+}
 
+async fn create_tables(pool: &PgPool) -> Result<(), sqlx::Error> {
     // Auto-create table if it doesn't exist
     sqlx::query(
         r#"
@@ -18,9 +60,109 @@ pub async fn connect(config: &DatabaseConfig) -> PgPool {
         )
         "#,
     )
-    .execute(&pool)
-    .await
-    .expect("Failed to create users table");
+    .execute(pool)
+    .await?;
+
+    // Back user search; queries must use the exact same expressions to hit them
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS users_search_idx
+            ON users USING GIN (to_tsvector('simple', name || ' ' || email))
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS users_email_prefix_idx
+            ON users (lower(email) text_pattern_ops)
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS refresh_tokens (
+            session_id TEXT PRIMARY KEY,
+            user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+            token_hash TEXT NOT NULL UNIQUE,
+            expires_at TIMESTAMPTZ NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Holds both revoked access token ids (jti) and revoked session ids (sid)
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS revoked_tokens (
+            id TEXT PRIMARY KEY,
+            expires_at TIMESTAMPTZ NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Files shared with an email that has no account yet; `email` is stored lowercased.
+    // `role` is the share role granted on claim, and `expires_at` when that share expires,
+    // NULL for a permanent share.
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS share_invitations (
+            email TEXT NOT NULL,
+            file_id TEXT NOT NULL,
+            invited_by INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            role TEXT NOT NULL DEFAULT 'viewer',
+            expires_at TIMESTAMPTZ,
+            PRIMARY KEY (email, file_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS share_invitations_file_id_idx
+            ON share_invitations (file_id)
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Public links to a file; only hashes of the token and password are stored.
+    // `failed_attempts` counts wrong passwords towards locking the link until `locked_until`.
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS share_links (
+            id SERIAL PRIMARY KEY,
+            token_hash TEXT NOT NULL UNIQUE,
+            file_id TEXT NOT NULL,
+            created_by INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+            password_hash TEXT,
+            expires_at TIMESTAMPTZ,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            failed_attempts INTEGER NOT NULL DEFAULT 0,
+            locked_until TIMESTAMPTZ
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS share_links_file_id_idx
+            ON share_links (file_id)
+        "#,
+    )
+    .execute(pool)
+    .await?;
 
-    pool
+    Ok(())
 }
@@ -1,17 +1,33 @@
 use crate::proto::user::user_server::{User, UserServer};
 use crate::proto::user::{
-    GetAllUsersReply, GetAllUsersRequest, GetGoogleLoginUrlReply, GetGoogleLoginUrlRequest,
-    LoginReply, LoginRequest, ValidateTokenReply, ValidateTokenRequest,
+    CreateShareLinkReply, CreateShareLinkRequest, GetFileInvitationsReply,
+    GetFileInvitationsRequest, GetFileShareLinksReply, GetFileShareLinksRequest,
+    GetGoogleLoginUrlReply, GetGoogleLoginUrlRequest, GetUsersByIdsReply, GetUsersByIdsRequest,
+    InviteByEmailReply, InviteByEmailRequest, LoginReply, LoginRequest, LogoutReply, LogoutRequest,
+    RefreshTokenReply, RefreshTokenRequest, ResolveShareLinkReply, ResolveShareLinkRequest,
+    RevokeInvitationReply, RevokeInvitationRequest, RevokeShareLinkReply, RevokeShareLinkRequest,
+    SearchUsersReply, SearchUsersRequest, ValidateTokenReply, ValidateTokenRequest,
 };
 use crate::services::auth::AuthService;
+use crate::services::invitation::InvitationService;
+use crate::services::share_link::ShareLinkService;
 use crate::services::user::UserService;
+use openexam_common::request_signer::{
+    SIGNATURE_HEADER, TIMESTAMP_HEADER, USER_ID_HEADER, Verifier,
+};
+use prost::Message;
 use std::sync::Arc;
+use tonic::metadata::MetadataMap;
 use tonic::{Request, Response, Status};
+use tracing::warn;
 
 #[derive(Debug)]
 pub struct MyUser {
     pub auth_service: AuthService,
     pub user_service: Arc<UserService>,
+    pub invitation_service: Arc<InvitationService>,
+    pub share_link_service: ShareLinkService,
+    pub verifier: Verifier,
 }
 
 #[tonic::async_trait]
@@ -20,7 +36,7 @@ impl User for MyUser {
         &self,
         request: Request<GetGoogleLoginUrlRequest>,
     ) -> Result<Response<GetGoogleLoginUrlReply>, Status> {
-        self.auth_service.get_google_login_url(request)
+        Ok(self.auth_service.get_google_login_url(request))
     }
 
     async fn login(&self, request: Request<LoginRequest>) -> Result<Response<LoginReply>, Status> {
@@ -34,26 +50,193 @@ impl User for MyUser {
         self.auth_service.validate_token(request).await
     }
 
-    async fn get_all_users(
+    async fn refresh_token(
+        &self,
+        request: Request<RefreshTokenRequest>,
+    ) -> Result<Response<RefreshTokenReply>, Status> {
+        self.auth_service.refresh_token(request).await
+    }
+
+    async fn logout(
+        &self,
+        request: Request<LogoutRequest>,
+    ) -> Result<Response<LogoutReply>, Status> {
+        self.auth_service.logout(request).await
+    }
+
+    async fn get_users_by_ids(
+        &self,
+        request: Request<GetUsersByIdsRequest>,
+    ) -> Result<Response<GetUsersByIdsReply>, Status> {
+        self.user_service.get_by_ids(request.into_inner().ids).await
+    }
+
+    async fn search_users(
+        &self,
+        request: Request<SearchUsersRequest>,
+    ) -> Result<Response<SearchUsersReply>, Status> {
+        let request = request.into_inner();
+        self.user_service
+            .search(&request.query, request.limit, &request.cursor)
+            .await
+    }
+
+    async fn invite_by_email(
+        &self,
+        request: Request<InviteByEmailRequest>,
+    ) -> Result<Response<InviteByEmailReply>, Status> {
+        let caller = self.caller(&request, "/user.User/InviteByEmail")?;
+        let request = request.into_inner();
+        if request.invited_by != caller.to_string() {
+            return Err(Status::permission_denied(
+                "Invitations can only be made by the calling user",
+            ));
+        }
+        self.invitation_service
+            .invite(
+                &request.email,
+                &request.file_id,
+                caller,
+                &request.role,
+                &request.expires_at,
+            )
+            .await
+    }
+
+    async fn revoke_invitation(
+        &self,
+        request: Request<RevokeInvitationRequest>,
+    ) -> Result<Response<RevokeInvitationReply>, Status> {
+        let caller = self.caller(&request, "/user.User/RevokeInvitation")?;
+        let request = request.into_inner();
+        self.invitation_service
+            .revoke(caller, &request.email, &request.file_id)
+            .await
+    }
+
+    async fn get_file_invitations(
+        &self,
+        request: Request<GetFileInvitationsRequest>,
+    ) -> Result<Response<GetFileInvitationsReply>, Status> {
+        let caller = self.caller(&request, "/user.User/GetFileInvitations")?;
+        self.invitation_service
+            .list_for_file(caller, &request.into_inner().file_id)
+            .await
+    }
+
+    async fn create_share_link(
+        &self,
+        request: Request<CreateShareLinkRequest>,
+    ) -> Result<Response<CreateShareLinkReply>, Status> {
+        let caller = self.caller(&request, "/user.User/CreateShareLink")?;
+        let request = request.into_inner();
+        if request.created_by != caller.to_string() {
+            return Err(Status::permission_denied(
+                "Share links can only be created by the calling user",
+            ));
+        }
+        self.share_link_service
+            .create(
+                &request.file_id,
+                caller,
+                &request.password,
+                &request.expires_at,
+            )
+            .await
+    }
+
+    async fn get_file_share_links(
         &self,
-        _: Request<GetAllUsersRequest>,
-    ) -> Result<Response<GetAllUsersReply>, Status> {
-        self.user_service.get_all().await
+        request: Request<GetFileShareLinksRequest>,
+    ) -> Result<Response<GetFileShareLinksReply>, Status> {
+        let caller = self.caller(&request, "/user.User/GetFileShareLinks")?;
+        self.share_link_service
+            .list_for_file(caller, &request.into_inner().file_id)
+            .await
+    }
+
+    async fn revoke_share_link(
+        &self,
+        request: Request<RevokeShareLinkRequest>,
+    ) -> Result<Response<RevokeShareLinkReply>, Status> {
+        let caller = self.caller(&request, "/user.User/RevokeShareLink")?;
+        let request = request.into_inner();
+        self.share_link_service
+            .revoke(caller, &request.id, &request.file_id)
+            .await
+    }
+
+    async fn resolve_share_link(
+        &self,
+        request: Request<ResolveShareLinkRequest>,
+    ) -> Result<Response<ResolveShareLinkReply>, Status> {
+        let request = request.into_inner();
+        self.share_link_service
+            .resolve(&request.token, &request.password)
+            .await
     }
 }
 
 impl MyUser {
-    pub fn new(auth_service: AuthService, user_service: Arc<UserService>) -> Self {
+    pub fn new(
+        auth_service: AuthService,
+        user_service: Arc<UserService>,
+        invitation_service: Arc<InvitationService>,
+        share_link_service: ShareLinkService,
+        verifier: Verifier,
+    ) -> Self {
         Self {
             auth_service,
             user_service,
+            invitation_service,
+            share_link_service,
+            verifier,
         }
     }
+
+    /// The user a call acts for. Anyone who can reach this service could name any user, so
+    /// calls that act for one must be signed by the gateway over their method path, user
+    /// and message.
+    // fails with the Status the handler returns as is, so there is nothing to gain by boxing it
+    #[allow(clippy::result_large_err)]
+    fn caller<T: Message>(&self, request: &Request<T>, path: &str) -> Result<i32, Status> {
+        let metadata = request.metadata();
+        let user_id = metadata_str(metadata, USER_ID_HEADER);
+        if let Err(e) = self.verifier.verify_call(
+            metadata_str(metadata, TIMESTAMP_HEADER),
+            metadata_str(metadata, SIGNATURE_HEADER),
+            path,
+            user_id,
+            &request.get_ref().encode_to_vec(),
+        ) {
+            warn!("Refused unsigned call to {}: {:?}", path, e);
+            return Err(Status::unauthenticated("Call is not signed by the gateway"));
+        }
+        user_id
+            .parse()
+            .map_err(|_| Status::invalid_argument("User ids must be numeric"))
+    }
+}
+
+fn metadata_str<'a>(metadata: &'a MetadataMap, key: &str) -> &'a str {
+    metadata
+        .get(key)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
 }
 
 pub fn auth_server(
     auth_service: AuthService,
     user_service: Arc<UserService>,
+    invitation_service: Arc<InvitationService>,
+    share_link_service: ShareLinkService,
+    verifier: Verifier,
 ) -> UserServer<MyUser> {
-    UserServer::new(MyUser::new(auth_service, user_service))
+    UserServer::new(MyUser::new(
+        auth_service,
+        user_service,
+        invitation_service,
+        share_link_service,
+        verifier,
+    ))
 }
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tonic_health::ServingStatus;
+use tonic_health::server::HealthReporter;
+use tracing::warn;
+
+use crate::proto::user::user_server::SERVICE_NAME;
+use openexam_common::shutdown::Shutdown;
+
+/// How often the database is probed
+const PROBE_INTERVAL: Duration = Duration::from_secs(5);
+/// A probe that can't get a connection within this long counts as a failure
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Keeps `grpc.health.v1` in sync with Postgres: both the overall status and `user.User`
+/// report NOT_SERVING until the first probe succeeds, whenever the pool cannot hand out a
+/// connection, and for good once shutdown starts. Expects the schema to exist already.
+pub async fn spawn_health_monitor(mut reporter: HealthReporter, pool: PgPool, shutdown: Shutdown) {
+    reporter
+        .set_service_status("", ServingStatus::NotServing)
+        .await;
+    reporter
+        .set_service_status(SERVICE_NAME, ServingStatus::NotServing)
+        .await;
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PROBE_INTERVAL);
+        let mut last = Some(ServingStatus::NotServing);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown.wait() => {
+                    reporter.set_service_status("", ServingStatus::NotServing).await;
+                    reporter.set_service_status(SERVICE_NAME, ServingStatus::NotServing).await;
+                    return;
+                }
+            }
+
+            let status = match tokio::time::timeout(PROBE_TIMEOUT, pool.acquire()).await {
+                Ok(Ok(_)) => ServingStatus::Serving,
+                Ok(Err(e)) => {
+                    warn!(
+                        "Health probe could not acquire a database connection: {:?}",
+                        e
+                    );
+                    ServingStatus::NotServing
+                }
+                Err(_) => {
+                    warn!("Health probe timed out acquiring a database connection");
+                    ServingStatus::NotServing
+                }
+            };
+
+            if last != Some(status) {
+                reporter.set_service_status("", status).await;
+                reporter.set_service_status(SERVICE_NAME, status).await;
+                last = Some(status);
+            }
+        }
+    });
+}
@@ -1,45 +1,130 @@
-use crate::db::connect;
+use crate::db::{connect, ensure_schema};
 use crate::grpc::auth_server;
+use crate::middleware::{MetricsLayer, TraceLayer};
+use crate::repositories::invitation::InvitationRepo;
+use crate::repositories::session::SessionRepo;
+use crate::repositories::share_link::ShareLinkRepo;
 use crate::repositories::user::UserRepo;
 use crate::services::auth::AuthService;
+use crate::services::cheatsheet::CheatsheetClient;
+use crate::services::invitation::InvitationService;
 use crate::services::oauth::OAuthService;
+use crate::services::session::SessionService;
+use crate::services::share_link::ShareLinkService;
+use crate::services::token::TokenService;
 use crate::services::user::UserService;
+use openexam_common::request_signer::Verifier;
+use openexam_common::shutdown::Shutdown;
+use openexam_common::telemetry;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::task;
+use std::time::Duration;
 use tonic::transport::Server;
+use tracing::info;
 
 mod config;
 mod db;
 mod grpc;
+mod health;
+mod metrics;
+mod middleware;
 mod models;
 mod proto;
 mod repositories;
 mod services;
 
+/// How far a signed call's timestamp may be from now, either way, as the cheatsheet service
+/// allows by default
+const MAX_SIGNATURE_SKEW: Duration = Duration::from_secs(60);
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    env_logger::init();
     dotenvy::dotenv().ok();
     let config = config::config::Config::from_env()?;
+    let _telemetry = telemetry::init(
+        &config.telemetry.service_name,
+        config.telemetry.otlp_endpoint.as_deref(),
+    )?;
+
+    let shutdown = Shutdown::new(Duration::from_secs(
+        config.server.shutdown_pre_stop_delay_secs,
+    ));
+    shutdown.listen_for_signals();
+
+    let metrics_addr: SocketAddr = config.server.metrics_addr.parse()?;
+    metrics::init(metrics_addr)?;
+    info!("Metrics available on http://{}/metrics", metrics_addr);
+
+    let pool = connect(&config.database)?;
+    metrics::spawn_pool_monitor(pool.clone(), config.database.max_connections);
 
-    let pool = connect(&config.database).await;
+    // nothing is served until the tables exist, so readiness stays false until then
+    tokio::select! {
+        _ = ensure_schema(&pool) => {}
+        _ = shutdown.wait() => {
+            info!("Shut down before the database schema was ready");
+            return Ok(());
+        }
+    }
 
-    let user_repo = UserRepo::new(pool);
+    let (health_reporter, health_server) = tonic_health::server::health_reporter();
+    health::spawn_health_monitor(health_reporter, pool.clone(), shutdown.clone()).await;
+
+    let user_repo = UserRepo::new(pool.clone());
     let user_service = Arc::new(UserService::new(user_repo));
 
+    let session_repo = SessionRepo::new(pool.clone());
+    let session_service = SessionService::new(session_repo);
+
+    let invitation_repo = InvitationRepo::new(pool.clone());
+    let verifier = Verifier::new(&config.cheatsheet.signing_secret, MAX_SIGNATURE_SKEW);
+    let cheatsheet_client = Arc::new(CheatsheetClient::new(config.cheatsheet)?);
+    let invitation_service = Arc::new(InvitationService::new(
+        invitation_repo,
+        user_service.clone(),
+        cheatsheet_client.clone(),
+    ));
+
+    let share_link_service =
+        ShareLinkService::new(ShareLinkRepo::new(pool.clone()), cheatsheet_client);
+
     let oauth_service = OAuthService::new(config.oauth)?;
-    let auth_service = AuthService::new(user_service.clone(), oauth_service)?;
+    let token_service = TokenService::new(config.jwt);
+    let auth_service = AuthService::new(
+        user_service.clone(),
+        oauth_service,
+        token_service,
+        session_service,
+        invitation_service.clone(),
+    )?;
 
     let grpc_addr: SocketAddr = config.server.grpc_addr.parse()?;
-    let grpc = Server::builder().add_service(auth_server(auth_service, user_service.clone()));
+    let grpc = Server::builder()
+        .layer(TraceLayer)
+        .layer(MetricsLayer)
+        .add_service(health_server)
+        .add_service(auth_server(
+            auth_service,
+            user_service.clone(),
+            invitation_service,
+            share_link_service,
+            verifier,
+        ));
 
-    println!("Server running on http://{}", grpc_addr);
+    info!("Server running on http://{}", grpc_addr);
 
-    let grpc_task = task::spawn(async move {
-        grpc.serve(grpc_addr).await.unwrap();
+    let server = grpc.serve_with_shutdown(grpc_addr, {
+        let shutdown = shutdown.clone();
+        async move { shutdown.wait_for_stop().await }
     });
+    shutdown
+        .drain(
+            server,
+            Duration::from_secs(config.server.shutdown_timeout_secs),
+        )
+        .await?;
 
-    tokio::try_join!(grpc_task)?;
+    pool.close().await;
+    info!("Database pool closed");
     Ok(())
 }
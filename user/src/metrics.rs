@@ -0,0 +1,67 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+use sqlx::PgPool;
+
+/// Latency buckets in seconds shared by every duration histogram
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// How often the connection pool gauges are refreshed
+const POOL_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Installs the global Prometheus recorder and serves `/metrics` on its own port, next to
+/// the gRPC listener
+pub fn init(addr: SocketAddr) -> anyhow::Result<()> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Suffix("_duration_seconds".to_string()),
+            LATENCY_BUCKETS,
+        )?
+        .with_http_listener(addr)
+        .install()?;
+    Ok(())
+}
+
+pub fn record_grpc_request(method: &str, code: tonic::Code, latency: Duration) {
+    let method = method.to_string();
+    counter!(
+        "grpc_server_requests_total",
+        "method" => method.clone(),
+        "code" => format!("{:?}", code),
+    )
+    .increment(1);
+    histogram!("grpc_server_request_duration_seconds", "method" => method)
+        .record(latency.as_secs_f64());
+}
+
+/// `stage` is the step of the OAuth login that failed: `token_exchange` or `profile`
+pub fn record_oauth_failure(stage: &'static str) {
+    counter!("oauth_exchange_failures_total", "stage" => stage).increment(1);
+}
+
+/// `outcome` of granting a pending share invitation at login: `granted`, `rejected` or
+/// `unavailable`
+pub fn record_invitation_claim(outcome: &'static str) {
+    counter!("share_invitation_claims_total", "outcome" => outcome).increment(1);
+}
+
+/// Periodically samples the sqlx pool so saturation shows up before requests start queueing
+pub fn spawn_pool_monitor(pool: PgPool, max_connections: u32) {
+    gauge!("db_pool_max_connections").set(max_connections as f64);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POOL_SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            let size = pool.size();
+            let idle = pool.num_idle() as u32;
+            gauge!("db_pool_connections", "state" => "idle").set(idle as f64);
+            gauge!("db_pool_connections", "state" => "active")
+                .set(size.saturating_sub(idle) as f64);
+        }
+    });
+}
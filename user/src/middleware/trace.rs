@@ -0,0 +1,64 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use openexam_common::telemetry::continue_trace;
+use tonic::codegen::http;
+use tower::{Layer, Service};
+use tracing::{Instrument, info_span};
+
+/// Metadata key the gateway uses to forward its request id
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Tower layer that opens a span per gRPC call, continuing the caller's W3C trace and
+/// tagging every log line of the call with the gateway's request id
+#[derive(Debug, Clone, Default)]
+pub struct TraceLayer;
+
+impl<S> Layer<S> for TraceLayer {
+    type Service = TraceService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TraceService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<http::Request<B>> for TraceService<S>
+where
+    S: Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("-");
+        let path = request.uri().path();
+
+        let span = info_span!(
+            "grpc_request",
+            otel.name = %path,
+            rpc.system = "grpc",
+            rpc.method = %path,
+            request_id = %request_id,
+        );
+        continue_trace(&span, request.headers());
+
+        let future = self.inner.call(request);
+        Box::pin(future.instrument(span))
+    }
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Invitation {
+    pub email: String,
+    pub file_id: String,
+    pub invited_by: i32,
+    pub role: String,
+    /// RFC 3339, `None` for a permanent share
+    pub expires_at: Option<String>,
+    /// The invitation expired before it was claimed
+    pub expired: bool,
+    /// Unix seconds
+    pub created_at: i64,
+}
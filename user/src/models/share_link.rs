@@ -0,0 +1,16 @@
+#[derive(Debug, sqlx::FromRow)]
+pub struct ShareLink {
+    pub id: i32,
+    pub file_id: String,
+    pub created_by: i32,
+    /// Argon2 PHC string, `None` for a link without a password
+    pub password_hash: Option<String>,
+    /// RFC 3339, `None` for a link that never expires
+    pub expires_at: Option<String>,
+    /// Unix seconds
+    pub created_at: i64,
+    /// Wrong passwords entered since the link was last opened or locked
+    pub failed_attempts: i32,
+    /// Whether too many wrong passwords have locked the link for now
+    pub locked: bool,
+}
@@ -0,0 +1,87 @@
+use crate::models::invitation::Invitation;
+use sqlx::PgPool;
+use tracing::instrument;
+
+#[derive(Debug)]
+pub struct InvitationRepo {
+    pool: PgPool,
+}
+
+impl InvitationRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records an invitation, or refreshes an existing one for the same email and file
+    #[instrument(name = "db.share_invitations.create", skip_all, fields(db.system = "postgresql"))]
+    pub async fn create(
+        &self,
+        email: &str,
+        file_id: &str,
+        invited_by: i32,
+        role: &str,
+        expires_at: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO share_invitations (email, file_id, invited_by, role, expires_at)
+            VALUES ($1, $2, $3, $4, $5::TIMESTAMPTZ)
+            ON CONFLICT (email, file_id) DO UPDATE
+            SET invited_by = $3, role = $4, expires_at = $5::TIMESTAMPTZ, created_at = NOW()
+            "#,
+        )
+        .bind(email)
+        .bind(file_id)
+        .bind(invited_by)
+        .bind(role)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Invitations of the file that have not expired
+    #[instrument(name = "db.share_invitations.list_for_file", skip_all, fields(db.system = "postgresql"))]
+    pub async fn list_for_file(&self, file_id: &str) -> anyhow::Result<Vec<Invitation>> {
+        let invitations = sqlx::query_as::<_, Invitation>(
+            r#"
+            SELECT email, file_id, invited_by, role, EXTRACT(EPOCH FROM created_at)::BIGINT AS created_at,
+                to_char(expires_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS expires_at,
+                COALESCE(expires_at <= NOW(), FALSE) AS expired
+            FROM share_invitations
+            WHERE file_id = $1 AND (expires_at IS NULL OR expires_at > NOW())
+            ORDER BY created_at
+            "#,
+        )
+        .bind(file_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(invitations)
+    }
+
+    #[instrument(name = "db.share_invitations.list_for_email", skip_all, fields(db.system = "postgresql"))]
+    pub async fn list_for_email(&self, email: &str) -> anyhow::Result<Vec<Invitation>> {
+        let invitations = sqlx::query_as::<_, Invitation>(
+            r#"
+            SELECT email, file_id, invited_by, role, EXTRACT(EPOCH FROM created_at)::BIGINT AS created_at,
+                to_char(expires_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS expires_at,
+                COALESCE(expires_at <= NOW(), FALSE) AS expired
+            FROM share_invitations WHERE email = $1 ORDER BY created_at
+            "#,
+        )
+        .bind(email)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(invitations)
+    }
+
+    #[instrument(name = "db.share_invitations.delete", skip_all, fields(db.system = "postgresql"))]
+    pub async fn delete(&self, email: &str, file_id: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query("DELETE FROM share_invitations WHERE email = $1 AND file_id = $2")
+            .bind(email)
+            .bind(file_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
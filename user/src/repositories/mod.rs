@@ -1 +1,4 @@
+pub mod invitation;
+pub mod session;
+pub mod share_link;
 pub mod user;
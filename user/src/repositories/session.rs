@@ -0,0 +1,122 @@
+use crate::models::session::Session;
+use sqlx::PgPool;
+use tracing::instrument;
+
+#[derive(Debug)]
+pub struct SessionRepo {
+    pool: PgPool,
+}
+
+impl SessionRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(name = "db.sessions.create", skip_all, fields(db.system = "postgresql"))]
+    pub async fn create(
+        &self,
+        session_id: &str,
+        user_id: i32,
+        token_hash: &str,
+        expires_at: u64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO refresh_tokens (session_id, user_id, token_hash, expires_at) VALUES ($1, $2, $3, to_timestamp($4))",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(token_hash)
+        .bind(expires_at as f64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Swaps a live refresh token for a new one, returning the session it belongs to
+    #[instrument(name = "db.sessions.rotate", skip_all, fields(db.system = "postgresql"))]
+    pub async fn rotate(
+        &self,
+        token_hash: &str,
+        new_token_hash: &str,
+        expires_at: u64,
+    ) -> anyhow::Result<Option<Session>> {
+        let session = sqlx::query_as::<_, Session>(
+            "UPDATE refresh_tokens SET token_hash = $2, expires_at = to_timestamp($3) WHERE token_hash = $1 AND expires_at > NOW() RETURNING session_id, user_id",
+        )
+        .bind(token_hash)
+        .bind(new_token_hash)
+        .bind(expires_at as f64)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(session)
+    }
+
+    #[instrument(name = "db.sessions.revoke_token", skip_all, fields(db.system = "postgresql"))]
+    pub async fn revoke_token(&self, jti: &str, expires_at: u64) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO revoked_tokens (id, expires_at) VALUES ($1, to_timestamp($2)) ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(jti)
+        .bind(expires_at as f64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "db.sessions.revoke_session", skip_all, fields(db.system = "postgresql"))]
+    pub async fn revoke_session(&self, session_id: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            WITH deleted AS (
+                DELETE FROM refresh_tokens WHERE session_id = $1 RETURNING session_id, expires_at
+            )
+            INSERT INTO revoked_tokens (id, expires_at)
+            SELECT session_id, expires_at FROM deleted
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(session_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "db.sessions.revoke_all_sessions", skip_all, fields(db.system = "postgresql"))]
+    pub async fn revoke_all_sessions(&self, user_id: i32) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            WITH deleted AS (
+                DELETE FROM refresh_tokens WHERE user_id = $1 RETURNING session_id, expires_at
+            )
+            INSERT INTO revoked_tokens (id, expires_at)
+            SELECT session_id, expires_at FROM deleted
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    #[instrument(name = "db.sessions.is_revoked", skip_all, fields(db.system = "postgresql"))]
+    pub async fn is_revoked(&self, ids: &[String]) -> anyhow::Result<bool> {
+        let revoked: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE id = ANY($1))")
+                .bind(ids)
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(revoked)
+    }
+
+    #[instrument(name = "db.sessions.purge_expired", skip_all, fields(db.system = "postgresql"))]
+    pub async fn purge_expired(&self) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM refresh_tokens WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::models::share_link::ShareLink;
+use sqlx::PgPool;
+use tracing::instrument;
+
+const COLUMNS: &str = r#"
+    id, file_id, created_by, password_hash,
+    to_char(expires_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS"Z"') AS expires_at,
+    EXTRACT(EPOCH FROM created_at)::BIGINT AS created_at,
+    failed_attempts,
+    COALESCE(locked_until > NOW(), FALSE) AS locked
+"#;
+
+#[derive(Debug)]
+pub struct ShareLinkRepo {
+    pool: PgPool,
+}
+
+impl ShareLinkRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    #[instrument(name = "db.share_links.create", skip_all, fields(db.system = "postgresql"))]
+    pub async fn create(
+        &self,
+        token_hash: &str,
+        file_id: &str,
+        created_by: i32,
+        password_hash: Option<&str>,
+        expires_at: Option<&str>,
+    ) -> anyhow::Result<ShareLink> {
+        let link = sqlx::query_as::<_, ShareLink>(&format!(
+            r#"
+            INSERT INTO share_links (token_hash, file_id, created_by, password_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5::TIMESTAMPTZ)
+            RETURNING {COLUMNS}
+            "#
+        ))
+        .bind(token_hash)
+        .bind(file_id)
+        .bind(created_by)
+        .bind(password_hash)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(link)
+    }
+
+    /// Links of the file that have not expired
+    #[instrument(name = "db.share_links.list_for_file", skip_all, fields(db.system = "postgresql"))]
+    pub async fn list_for_file(&self, file_id: &str) -> anyhow::Result<Vec<ShareLink>> {
+        let links = sqlx::query_as::<_, ShareLink>(&format!(
+            r#"
+            SELECT {COLUMNS} FROM share_links
+            WHERE file_id = $1 AND (expires_at IS NULL OR expires_at > NOW())
+            ORDER BY created_at, id
+            "#
+        ))
+        .bind(file_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(links)
+    }
+
+    /// The link with this token, unless it has expired
+    #[instrument(name = "db.share_links.find_by_token_hash", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find_by_token_hash(&self, token_hash: &str) -> anyhow::Result<Option<ShareLink>> {
+        let link = sqlx::query_as::<_, ShareLink>(&format!(
+            r#"
+            SELECT {COLUMNS} FROM share_links
+            WHERE token_hash = $1 AND (expires_at IS NULL OR expires_at > NOW())
+            "#
+        ))
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(link)
+    }
+
+    /// Counts a wrong password. The attempt that reaches `max_attempts` locks the link for
+    /// `lockout_secs` and starts the count over.
+    #[instrument(name = "db.share_links.record_failed_attempt", skip_all, fields(db.system = "postgresql"))]
+    pub async fn record_failed_attempt(
+        &self,
+        id: i32,
+        max_attempts: i32,
+        lockout_secs: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE share_links SET
+                failed_attempts = CASE
+                    WHEN failed_attempts + 1 >= $2 THEN 0
+                    ELSE failed_attempts + 1
+                END,
+                locked_until = CASE
+                    WHEN failed_attempts + 1 >= $2 THEN NOW() + make_interval(secs => $3)
+                    ELSE locked_until
+                END
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(max_attempts)
+        .bind(lockout_secs as f64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "db.share_links.clear_failed_attempts", skip_all, fields(db.system = "postgresql"))]
+    pub async fn clear_failed_attempts(&self, id: i32) -> anyhow::Result<()> {
+        sqlx::query("UPDATE share_links SET failed_attempts = 0 WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(name = "db.share_links.delete", skip_all, fields(db.system = "postgresql"))]
+    /// Deletes the link, only if `created_by` created it when that is given
+    pub async fn delete(
+        &self,
+        id: i32,
+        file_id: &str,
+        created_by: Option<i32>,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM share_links
+            WHERE id = $1 AND file_id = $2 AND ($3::INTEGER IS NULL OR created_by = $3)
+            "#,
+        )
+        .bind(id)
+        .bind(file_id)
+        .bind(created_by)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
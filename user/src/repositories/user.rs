@@ -1,5 +1,6 @@
 use crate::models::user::User;
 use sqlx::PgPool;
+use tracing::instrument;
 
 #[derive(Debug)]
 pub struct UserRepo {
@@ -11,13 +12,7 @@ impl UserRepo {
         Self { pool }
     }
 
-    pub async fn get_all(&self) -> anyhow::Result<Vec<User>> {
-        let users = sqlx::query_as::<_, User>("SELECT id, email, name FROM users")
-            .fetch_all(&self.pool)
-            .await?;
-        Ok(users)
-    }
-
+    #[instrument(name = "db.users.get_one", skip_all, fields(db.system = "postgresql"))]
     pub async fn get_one(&self, id: i32) -> anyhow::Result<Option<User>> {
         let user = sqlx::query_as::<_, User>("SELECT id, email, name FROM users WHERE id = $1")
             .bind(id)
@@ -26,6 +21,57 @@ impl UserRepo {
         Ok(user)
     }
 
+    #[instrument(name = "db.users.get_by_ids", skip_all, fields(db.system = "postgresql"))]
+    pub async fn get_by_ids(&self, ids: &[i32]) -> anyhow::Result<Vec<User>> {
+        let users =
+            sqlx::query_as::<_, User>("SELECT id, email, name FROM users WHERE id = ANY($1)")
+                .bind(ids)
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(users)
+    }
+
+    /// Users whose name or email matches the `simple` text search query `tsquery`, or whose
+    /// email starts with `email_prefix`, ordered by lowercased name then id.
+    /// `after` is the (lowercased name, id) of the last user on the previous page.
+    #[instrument(name = "db.users.search", skip_all, fields(db.system = "postgresql"))]
+    pub async fn search(
+        &self,
+        tsquery: &str,
+        email_prefix: &str,
+        after: Option<(String, i32)>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<User>> {
+        let (after_name, after_id) = after.unzip();
+        let pattern = format!(
+            "{}%",
+            email_prefix
+                .to_lowercase()
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let users = sqlx::query_as::<_, User>(
+            r#"
+            SELECT id, email, name FROM users
+            WHERE (to_tsvector('simple', name || ' ' || email) @@ to_tsquery('simple', $1)
+                   OR lower(email) LIKE $2)
+              AND ($3::TEXT IS NULL OR (lower(name), id) > ($3, $4))
+            ORDER BY lower(name), id
+            LIMIT $5
+            "#,
+        )
+        .bind(tsquery)
+        .bind(pattern)
+        .bind(after_name)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+
+    #[instrument(name = "db.users.find_by_email", skip_all, fields(db.system = "postgresql"))]
     pub async fn find_by_email(&self, email: &str) -> anyhow::Result<Option<User>> {
         let user = sqlx::query_as::<_, User>("SELECT id, email, name FROM users WHERE email = $1")
             .bind(email)
@@ -34,6 +80,19 @@ impl UserRepo {
         Ok(user)
     }
 
+    /// Like `find_by_email`, ignoring case
+    #[instrument(name = "db.users.find_by_email_ignore_case", skip_all, fields(db.system = "postgresql"))]
+    pub async fn find_by_email_ignore_case(&self, email: &str) -> anyhow::Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, email, name FROM users WHERE lower(email) = lower($1) ORDER BY id LIMIT 1",
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(user)
+    }
+
+    #[instrument(name = "db.users.create", skip_all, fields(db.system = "postgresql"))]
     pub async fn create(&self, user: &User) -> anyhow::Result<User> {
         let user = sqlx::query_as::<_, User>(
             "INSERT INTO users (name, email) VALUES ($1, $2) RETURNING id, name, email",
@@ -44,23 +103,4 @@ impl UserRepo {
         .await?;
         Ok(user)
     }
-
-    pub async fn update(&self, id: i32, name: String) -> anyhow::Result<Option<User>> {
-        let updated_user = sqlx::query_as::<_, User>(
-            "UPDATE users SET name = $1 WHERE id = $2 RETURNING id, name",
-        )
-        .bind(name)
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?;
-        Ok(updated_user)
-    }
-
-    pub async fn delete(&self, id: i32) -> anyhow::Result<bool> {
-        let result = sqlx::query("DELETE FROM users WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await?;
-        Ok(result.rows_affected() > 0)
-    }
 }
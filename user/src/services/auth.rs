@@ -2,41 +2,73 @@ use tonic::{Request, Response, Status};
 
 use crate::models::user::User;
 use crate::proto::user::{
-    GetGoogleLoginUrlReply, GetGoogleLoginUrlRequest, LoginReply, LoginRequest, ValidateTokenReply,
+    GetGoogleLoginUrlReply, GetGoogleLoginUrlRequest, LoginReply, LoginRequest, LogoutReply,
+    LogoutRequest, RefreshTokenReply, RefreshTokenRequest, ValidateTokenReply,
     ValidateTokenRequest,
 };
+use crate::services::invitation::InvitationService;
 use crate::services::oauth::OAuthService;
+use crate::services::session::SessionService;
+use crate::services::token::{TokenService, hash_refresh_token, new_refresh_token};
 use crate::services::user::UserService;
-use log::error;
 use std::sync::Arc;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
 
 #[derive(Debug)]
 pub struct AuthService {
     user_service: Arc<UserService>,
     oauth_service: OAuthService,
+    token_service: TokenService,
+    session_service: SessionService,
+    invitation_service: Arc<InvitationService>,
 }
 
 impl AuthService {
     pub fn new(
         user_service: Arc<UserService>,
         oauth_service: OAuthService,
+        token_service: TokenService,
+        session_service: SessionService,
+        invitation_service: Arc<InvitationService>,
     ) -> anyhow::Result<Self> {
         Ok(Self {
             user_service,
             oauth_service,
+            token_service,
+            session_service,
+            invitation_service,
         })
     }
 
+    /// Opens a new session for the user, returning its access and refresh tokens
+    async fn start_session(&self, user: &User) -> Result<(String, String), Status> {
+        let session_id = Uuid::new_v4().to_string();
+        let refresh_token = new_refresh_token();
+        self.session_service
+            .create(
+                &session_id,
+                user.id,
+                &hash_refresh_token(&refresh_token),
+                self.token_service.refresh_expires_at(),
+            )
+            .await?;
+
+        let token = self.token_service.issue(user, &session_id)?;
+        Ok((token, refresh_token))
+    }
+
     pub fn get_google_login_url(
         &self,
         _: Request<GetGoogleLoginUrlRequest>,
-    ) -> Result<Response<GetGoogleLoginUrlReply>, Status> {
+    ) -> Response<GetGoogleLoginUrlReply> {
         let auth_url = self.oauth_service.get_google_login_url();
-        Ok(Response::new(GetGoogleLoginUrlReply {
+        Response::new(GetGoogleLoginUrlReply {
             url: auth_url.to_string(),
-        }))
+        })
     }
 
+    #[instrument(name = "auth.login", skip_all)]
     pub async fn login(
         &self,
         request: Request<LoginRequest>,
@@ -44,6 +76,10 @@ impl AuthService {
         let code = request.into_inner().code;
         let access_token = self.oauth_service.get_access_token(&code).await?;
         let user_info = self.oauth_service.get_profile(&access_token).await?;
+        // invitations and existing accounts are matched by email, so it must be proven
+        if !user_info.verified_email {
+            return Err(Status::permission_denied("Email address is not verified"));
+        }
 
         let user = match self.user_service.find_by_email(&user_info.email).await {
             Ok(Some(user)) => user,
@@ -57,40 +93,47 @@ impl AuthService {
             }
             Err(e) => {
                 error!("Failed to find user by email: {:?}", e);
-                return Err(Status::internal("Database error"));
+                return Err(e);
             }
         };
 
+        // files shared with the email before the account existed; also picks up invitations
+        // an earlier login could not grant
+        self.invitation_service.claim_in_background(user.clone());
+
+        let (token, refresh_token) = self.start_session(&user).await?;
+
         Ok(Response::new(LoginReply {
             id: user.id.to_string(),
             email: user.email,
             name: user.name,
-            token: access_token,
+            token,
+            refresh_token,
         }))
     }
 
+    #[instrument(name = "auth.validate_token", skip_all)]
     pub async fn validate_token(
         &self,
         request: Request<ValidateTokenRequest>,
     ) -> Result<Response<ValidateTokenReply>, Status> {
         let token = request.into_inner().token;
-        let user = self.oauth_service.get_profile(&token).await?;
+        let claims = self.token_service.verify(&token)?;
 
-        let existing_user = match self.user_service.find_by_email(&user.email).await {
-            Ok(Some(user)) => user,
-            Ok(None) => {
-                return Err(Status::not_found("User not found"));
-            }
-            Err(e) => {
-                error!("Failed to find user by email: {:?}", e);
-                return Err(Status::internal("Failed to find user by email"));
-            }
-        };
+        if self
+            .session_service
+            .is_revoked(&[claims.jti.clone(), claims.sid.clone()])
+            .await?
+        {
+            return Err(Status::unauthenticated("Token revoked"));
+        }
+
+        let existing_user = self.user_service.get_one(claims.user_id()?).await?;
 
-        if existing_user.email != user.email {
+        if existing_user.email != claims.email {
             error!(
                 "Invalid token: expected email {} but got {}",
-                existing_user.email, user.email
+                existing_user.email, claims.email
             );
             return Err(Status::unauthenticated("Invalid token"));
         }
@@ -99,6 +142,62 @@ impl AuthService {
             id: existing_user.id.to_string(),
             email: existing_user.email,
             name: existing_user.name,
+            expires_at: claims.exp,
+        }))
+    }
+
+    #[instrument(name = "auth.refresh_token", skip_all)]
+    pub async fn refresh_token(
+        &self,
+        request: Request<RefreshTokenRequest>,
+    ) -> Result<Response<RefreshTokenReply>, Status> {
+        let refresh_token = request.into_inner().refresh_token;
+        let new_refresh_token = new_refresh_token();
+
+        let session = self
+            .session_service
+            .rotate(
+                &hash_refresh_token(&refresh_token),
+                &hash_refresh_token(&new_refresh_token),
+                self.token_service.refresh_expires_at(),
+            )
+            .await?;
+
+        let user = self.user_service.get_one(session.user_id).await?;
+        let token = self.token_service.issue(&user, &session.session_id)?;
+
+        Ok(Response::new(RefreshTokenReply {
+            token,
+            refresh_token: new_refresh_token,
+        }))
+    }
+
+    #[instrument(name = "auth.logout", skip_all)]
+    pub async fn logout(
+        &self,
+        request: Request<LogoutRequest>,
+    ) -> Result<Response<LogoutReply>, Status> {
+        let request = request.into_inner();
+        let claims = self.token_service.verify(&request.token)?;
+
+        self.session_service
+            .revoke_token(&claims.jti, claims.exp)
+            .await?;
+
+        if request.all_sessions {
+            self.session_service
+                .revoke_all_sessions(claims.user_id()?)
+                .await?;
+        } else {
+            self.session_service.revoke_session(&claims.sid).await?;
+        }
+        info!("User {} logged out", claims.sub);
+
+        self.session_service.purge_expired().await;
+
+        Ok(Response::new(LogoutReply {
+            success: true,
+            user_id: claims.sub,
         }))
     }
 }
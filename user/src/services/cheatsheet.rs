@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use openexam_common::request_signer::RequestSigner;
+use serde::Deserialize;
+use tonic::Status;
+use tracing::{error, instrument};
+
+use crate::config::config::CheatsheetConfig;
+
+/// Why the cheatsheet service did not create a share
+#[derive(Debug)]
+pub enum ShareError {
+    /// The request was refused, e.g. the inviter may no longer share the file; retrying won't help
+    Rejected(String),
+    /// The service could not be reached or failed; the share may succeed later
+    Unavailable(String),
+}
+
+/// Roles on a file, lowest first, ranked as the cheatsheet service ranks them
+const ROLES: [&str; 5] = ["viewer", "editor", "resharer", "co_owner", "owner"];
+
+/// Where `role` ranks among [`ROLES`]. Shares made before roles existed have none and count
+/// as viewer.
+pub fn role_rank(role: &str) -> usize {
+    ROLES.iter().position(|r| *r == role).unwrap_or(0)
+}
+
+#[derive(Deserialize)]
+struct RoleResponse {
+    data: RoleData,
+}
+
+#[derive(Deserialize)]
+struct RoleData {
+    role: String,
+}
+
+/// Minimal client for the cheatsheet service, which owns files and their shares
+pub struct CheatsheetClient {
+    client: reqwest::Client,
+    api_url: String,
+    signer: RequestSigner,
+}
+
+impl std::fmt::Debug for CheatsheetClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CheatsheetClient")
+            .field("api_url", &self.api_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CheatsheetClient {
+    pub fn new(config: CheatsheetConfig) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()?;
+        Ok(Self {
+            client,
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            signer: RequestSigner::new(&config.signing_secret),
+        })
+    }
+
+    /// Shares `file_id` with `user_id` as `role`, on behalf of `sharer_id`, who must be allowed
+    /// to grant that role. `expires_at` (RFC 3339) makes the share time-limited.
+    #[instrument(name = "cheatsheet.share", skip_all)]
+    pub async fn share(
+        &self,
+        sharer_id: i32,
+        user_id: i32,
+        file_id: &str,
+        role: &str,
+        expires_at: Option<&str>,
+    ) -> Result<(), ShareError> {
+        let mut request = self
+            .client
+            .post(format!("{}/share", self.api_url))
+            .header("X-User-Id", sharer_id.to_string())
+            .form(&[
+                ("user_id", user_id.to_string()),
+                ("file_id", file_id.to_string()),
+                ("role", role.to_string()),
+                ("expires_at", expires_at.unwrap_or_default().to_string()),
+            ])
+            .build()
+            .map_err(|e| ShareError::Unavailable(e.to_string()))?;
+        self.signer.sign(&mut request);
+
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .map_err(|e| ShareError::Unavailable(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = response.text().await.unwrap_or_default();
+        if status.is_client_error() {
+            Err(ShareError::Rejected(format!("{}: {}", status, body)))
+        } else {
+            Err(ShareError::Unavailable(format!("{}: {}", status, body)))
+        }
+    }
+
+    /// Fails unless `user_id` holds at least the `needed` role on the file, and returns the
+    /// role they hold. Callers check this themselves rather than trusting that whoever
+    /// called them did.
+    #[instrument(name = "cheatsheet.require_role", skip_all)]
+    pub async fn require_role(
+        &self,
+        user_id: i32,
+        file_id: &str,
+        needed: &str,
+    ) -> Result<String, Status> {
+        let unavailable = |e: &dyn std::fmt::Display| {
+            error!(
+                "Failed to check the role of user {} on file {}: {}",
+                user_id, file_id, e
+            );
+            Status::unavailable("Could not check access to the file")
+        };
+        let mut request = self
+            .client
+            .get(format!("{}/files/{}/role", self.api_url, file_id))
+            .header("X-User-Id", user_id.to_string())
+            .build()
+            .map_err(|e| unavailable(&e))?;
+        self.signer.sign(&mut request);
+
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .map_err(|e| unavailable(&e))?;
+        let status = response.status();
+        let role = if status.is_success() {
+            let body: RoleResponse = response.json().await.map_err(|e| unavailable(&e))?;
+            Some(body.data.role)
+        } else if status.is_client_error() {
+            None
+        } else {
+            return Err(unavailable(&status));
+        };
+
+        match role {
+            Some(role) if role_rank(&role) >= role_rank(needed) => Ok(role),
+            _ => Err(Status::permission_denied(format!(
+                "You need the {} role on file {}",
+                needed, file_id
+            ))),
+        }
+    }
+}
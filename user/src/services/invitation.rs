@@ -0,0 +1,320 @@
+use std::sync::Arc;
+
+use tonic::{Response, Status};
+use tracing::{Instrument, Span, error, info, warn};
+
+use crate::db;
+use crate::metrics;
+use crate::models::user::User;
+use crate::proto::user::{
+    GetFileInvitationsReply, Invitation, InviteByEmailReply, RevokeInvitationReply,
+};
+use crate::repositories::invitation::InvitationRepo;
+use crate::services::cheatsheet::{CheatsheetClient, ShareError, role_rank};
+use crate::services::user::UserService;
+
+/// Sharing by email: resolves registered users, and keeps invitations for emails without an
+/// account until they log in for the first time
+#[derive(Debug)]
+pub struct InvitationService {
+    invitation_repo: InvitationRepo,
+    user_service: Arc<UserService>,
+    cheatsheet_client: Arc<CheatsheetClient>,
+}
+
+impl InvitationService {
+    pub fn new(
+        invitation_repo: InvitationRepo,
+        user_service: Arc<UserService>,
+        cheatsheet_client: Arc<CheatsheetClient>,
+    ) -> Self {
+        Self {
+            invitation_repo,
+            user_service,
+            cheatsheet_client,
+        }
+    }
+
+    /// Invites `email` to the file as `role`, on behalf of `invited_by`, who needs at least
+    /// the resharer role on it and may grant up to their own
+    pub async fn invite(
+        &self,
+        email: &str,
+        file_id: &str,
+        invited_by: i32,
+        role: &str,
+        expires_at: &str,
+    ) -> Result<Response<InviteByEmailReply>, Status> {
+        let email = normalize_email(email)
+            .ok_or_else(|| Status::invalid_argument("Invalid email address"))?;
+        if file_id.is_empty() {
+            return Err(Status::invalid_argument("File id is required"));
+        }
+        let role = match role {
+            "" => "viewer",
+            "viewer" | "editor" | "resharer" | "co_owner" => role,
+            _ => return Err(Status::invalid_argument("Unknown share role")),
+        };
+        let needed = if role_rank(role) > role_rank("resharer") {
+            role
+        } else {
+            "resharer"
+        };
+        self.cheatsheet_client
+            .require_role(invited_by, file_id, needed)
+            .await?;
+
+        if let Some(user) = self.user_service.find_by_email_ignore_case(&email).await? {
+            return Ok(Response::new(InviteByEmailReply {
+                user_id: user.id.to_string(),
+                pending: false,
+            }));
+        }
+
+        match self
+            .invitation_repo
+            .create(
+                &email,
+                file_id,
+                invited_by,
+                role,
+                (!expires_at.is_empty()).then_some(expires_at),
+            )
+            .await
+        {
+            Ok(()) => {
+                info!(
+                    "User {} invited {} to file {} as {}",
+                    invited_by, email, file_id, role
+                );
+                Ok(Response::new(InviteByEmailReply {
+                    user_id: String::new(),
+                    pending: true,
+                }))
+            }
+            Err(e) => {
+                error!("Failed to create invitation: {:?}", e);
+                Err(db::error_status(&e, "Failed to create invitation"))
+            }
+        }
+    }
+
+    /// Withdraws a pending invitation. Needs the co-owner role, like unsharing.
+    pub async fn revoke(
+        &self,
+        caller: i32,
+        email: &str,
+        file_id: &str,
+    ) -> Result<Response<RevokeInvitationReply>, Status> {
+        let email = normalize_email(email)
+            .ok_or_else(|| Status::invalid_argument("Invalid email address"))?;
+        self.cheatsheet_client
+            .require_role(caller, file_id, "co_owner")
+            .await?;
+        match self.invitation_repo.delete(&email, file_id).await {
+            Ok(revoked) => Ok(Response::new(RevokeInvitationReply { revoked })),
+            Err(e) => {
+                error!("Failed to revoke invitation: {:?}", e);
+                Err(db::error_status(&e, "Database error"))
+            }
+        }
+    }
+
+    /// Pending invitations to the file, which only its co-owners may see
+    pub async fn list_for_file(
+        &self,
+        caller: i32,
+        file_id: &str,
+    ) -> Result<Response<GetFileInvitationsReply>, Status> {
+        self.cheatsheet_client
+            .require_role(caller, file_id, "co_owner")
+            .await?;
+        match self.invitation_repo.list_for_file(file_id).await {
+            Ok(invitations) => Ok(Response::new(GetFileInvitationsReply {
+                invitations: invitations
+                    .into_iter()
+                    .map(|invitation| Invitation {
+                        email: invitation.email,
+                        invited_by: invitation.invited_by.to_string(),
+                        created_at: invitation.created_at,
+                        role: invitation.role,
+                        expires_at: invitation.expires_at.unwrap_or_default(),
+                    })
+                    .collect(),
+            })),
+            Err(e) => {
+                error!("Failed to list invitations of file {}: {:?}", file_id, e);
+                Err(db::error_status(&e, "Database error"))
+            }
+        }
+    }
+
+    /// Runs [`Self::claim`] in the background so a slow cheatsheet service does not hold up
+    /// the login that triggered it
+    pub fn claim_in_background(self: &Arc<Self>, user: User) {
+        let service = Arc::clone(self);
+        tokio::spawn(async move { service.claim(&user).await }.instrument(Span::current()));
+    }
+
+    /// Grants every pending invitation for the user's email. Invitations that expired or
+    /// that the cheatsheet service refuses are dropped; ones that fail because it is
+    /// unavailable are kept and retried on the next login. Never fails the login itself.
+    pub async fn claim(&self, user: &User) {
+        let email = user.email.to_lowercase();
+        let invitations = match self.invitation_repo.list_for_email(&email).await {
+            Ok(invitations) => invitations,
+            Err(e) => {
+                warn!("Failed to load invitations of user {}: {:?}", user.id, e);
+                return;
+            }
+        };
+
+        for invitation in invitations {
+            let outcome = if invitation.expired {
+                ClaimOutcome::Expired
+            } else {
+                self.cheatsheet_client
+                    .share(
+                        invitation.invited_by,
+                        user.id,
+                        &invitation.file_id,
+                        &invitation.role,
+                        invitation.expires_at.as_deref(),
+                    )
+                    .await
+                    .into()
+            };
+            match &outcome {
+                ClaimOutcome::Expired => info!(
+                    "Dropping expired invitation to file {} for user {}",
+                    invitation.file_id, user.id
+                ),
+                ClaimOutcome::Granted => info!(
+                    "Granted invitation to file {} for user {}",
+                    invitation.file_id, user.id
+                ),
+                ClaimOutcome::Rejected(reason) => warn!(
+                    "Dropping invitation to file {} for user {}: {}",
+                    invitation.file_id, user.id, reason
+                ),
+                ClaimOutcome::Unavailable(reason) => warn!(
+                    "Could not grant invitation to file {} for user {}, will retry: {}",
+                    invitation.file_id, user.id, reason
+                ),
+            }
+            metrics::record_invitation_claim(outcome.label());
+            if !outcome.settled() {
+                continue;
+            }
+
+            if let Err(e) = self
+                .invitation_repo
+                .delete(&invitation.email, &invitation.file_id)
+                .await
+            {
+                warn!("Failed to delete claimed invitation: {:?}", e);
+            }
+        }
+    }
+}
+
+/// What came of trying to claim a pending invitation
+#[derive(Debug)]
+enum ClaimOutcome {
+    Expired,
+    Granted,
+    Rejected(String),
+    Unavailable(String),
+}
+
+impl From<Result<(), ShareError>> for ClaimOutcome {
+    fn from(share: Result<(), ShareError>) -> Self {
+        match share {
+            Ok(()) => ClaimOutcome::Granted,
+            Err(ShareError::Rejected(reason)) => ClaimOutcome::Rejected(reason),
+            Err(ShareError::Unavailable(reason)) => ClaimOutcome::Unavailable(reason),
+        }
+    }
+}
+
+impl ClaimOutcome {
+    /// Label for the invitation claim metric
+    fn label(&self) -> &'static str {
+        match self {
+            ClaimOutcome::Expired => "expired",
+            ClaimOutcome::Granted => "granted",
+            ClaimOutcome::Rejected(_) => "rejected",
+            ClaimOutcome::Unavailable(_) => "unavailable",
+        }
+    }
+
+    /// Whether the invitation is done with and can be deleted. Only an unavailable
+    /// cheatsheet service leaves it pending, to be retried on the next login.
+    fn settled(&self) -> bool {
+        !matches!(self, ClaimOutcome::Unavailable(_))
+    }
+}
+
+/// Lowercases and trims an email, rejecting anything that isn't shaped like one
+fn normalize_email(email: &str) -> Option<String> {
+    let email = email.trim().to_lowercase();
+    match email.split_once('@') {
+        Some((local, domain))
+            if !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !email.contains(char::is_whitespace) =>
+        {
+            Some(email)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_case_and_whitespace() {
+        assert_eq!(
+            normalize_email("  Alice@Example.COM ").as_deref(),
+            Some("alice@example.com")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        for email in [
+            "",
+            "alice",
+            "@example.com",
+            "alice@example",
+            "alice@.com",
+            "alice@com.",
+            "a b@example.com",
+        ] {
+            assert_eq!(normalize_email(email), None, "{email:?}");
+        }
+    }
+
+    #[test]
+    fn drops_expired_and_rejected_invitations() {
+        let outcomes = [
+            ClaimOutcome::Expired,
+            Ok(()).into(),
+            Err(ShareError::Rejected("not a resharer".to_string())).into(),
+        ];
+        for outcome in outcomes {
+            assert!(outcome.settled(), "{outcome:?}");
+        }
+    }
+
+    #[test]
+    fn keeps_invitations_while_the_cheatsheet_service_is_unavailable() {
+        let outcome: ClaimOutcome = Err(ShareError::Unavailable("timed out".to_string())).into();
+        assert!(!outcome.settled());
+        assert_eq!(outcome.label(), "unavailable");
+    }
+}
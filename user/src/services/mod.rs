@@ -1,3 +1,8 @@
 pub mod auth;
+pub mod cheatsheet;
+pub mod invitation;
 pub mod oauth;
+pub mod session;
+pub mod share_link;
+pub mod token;
 pub mod user;
@@ -1,8 +1,10 @@
 use oauth2::{AuthorizationCode, CsrfToken, EndpointNotSet, EndpointSet, Scope, TokenResponse};
 use serde::Deserialize;
 use tonic::Status;
+use tracing::instrument;
 
 use crate::config::config::OAuthConfig;
+use crate::metrics;
 use oauth2::{AuthUrl, ClientId, ClientSecret, RedirectUrl, TokenUrl, basic::BasicClient};
 use reqwest;
 
@@ -10,6 +12,9 @@ use reqwest;
 pub struct UserInfo {
     pub email: String,
     pub name: String,
+    /// Whether the provider has confirmed the user owns `email`
+    #[serde(default, alias = "email_verified")]
+    pub verified_email: bool,
 }
 
 #[derive(Debug)]
@@ -32,7 +37,7 @@ impl OAuthService {
     pub fn get_google_login_url(&self) -> String {
         let (auth_url, _) = self
             .oauth_client
-            .authorize_url(|| CsrfToken::new_random())
+            .authorize_url(CsrfToken::new_random)
             .add_scope(Scope::new("openid".into()))
             .add_scope(Scope::new("email".into()))
             .add_scope(Scope::new("profile".into()))
@@ -40,6 +45,7 @@ impl OAuthService {
         auth_url.to_string()
     }
 
+    #[instrument(name = "oauth.get_access_token", skip_all)]
     pub async fn get_access_token(&self, code: &str) -> Result<String, Status> {
         let code = AuthorizationCode::new(code.to_string());
 
@@ -48,13 +54,17 @@ impl OAuthService {
             .exchange_code(code)
             .request_async(&reqwest::Client::new())
             .await
-            .map_err(|e| Status::internal(format!("Failed to get access token: {}", e)))?;
+            .map_err(|e| {
+                metrics::record_oauth_failure("token_exchange");
+                Status::internal(format!("Failed to get access token: {}", e))
+            })?;
 
         let access_token = token_response.access_token().secret().clone();
 
         Ok(access_token.to_string())
     }
 
+    #[instrument(name = "oauth.get_profile", skip_all)]
     pub async fn get_profile(&self, access_token: &str) -> Result<UserInfo, Status> {
         let client = reqwest::Client::new();
         let response = client
@@ -62,12 +72,15 @@ impl OAuthService {
             .bearer_auth(access_token.to_owned())
             .send()
             .await
-            .map_err(|e| Status::unauthenticated(format!("Unauthenticated: {}", e)))?;
+            .map_err(|e| {
+                metrics::record_oauth_failure("profile");
+                Status::unauthenticated(format!("Unauthenticated: {}", e))
+            })?;
 
-        let user_info = response
-            .json::<UserInfo>()
-            .await
-            .map_err(|e| Status::internal(format!("Failed to get profile: {}", e)))?;
+        let user_info = response.json::<UserInfo>().await.map_err(|e| {
+            metrics::record_oauth_failure("profile");
+            Status::internal(format!("Failed to get profile: {}", e))
+        })?;
         Ok(user_info)
     }
 }
@@ -0,0 +1,100 @@
+use tonic::Status;
+use tracing::{error, info, warn};
+
+use crate::db;
+use crate::models::session::Session;
+use crate::repositories::session::SessionRepo;
+
+#[derive(Debug)]
+pub struct SessionService {
+    session_repo: SessionRepo,
+}
+
+impl SessionService {
+    pub fn new(session_repo: SessionRepo) -> Self {
+        Self { session_repo }
+    }
+
+    pub async fn create(
+        &self,
+        session_id: &str,
+        user_id: i32,
+        token_hash: &str,
+        expires_at: u64,
+    ) -> Result<(), Status> {
+        self.session_repo
+            .create(session_id, user_id, token_hash, expires_at)
+            .await
+            .map_err(|e| {
+                error!("Failed to create session for user {}: {:?}", user_id, e);
+                db::error_status(&e, "Failed to create session")
+            })
+    }
+
+    pub async fn rotate(
+        &self,
+        token_hash: &str,
+        new_token_hash: &str,
+        expires_at: u64,
+    ) -> Result<Session, Status> {
+        match self
+            .session_repo
+            .rotate(token_hash, new_token_hash, expires_at)
+            .await
+        {
+            Ok(Some(session)) => Ok(session),
+            Ok(None) => Err(Status::unauthenticated("Invalid refresh token")),
+            Err(e) => {
+                error!("Failed to rotate refresh token: {:?}", e);
+                Err(db::error_status(&e, "Database error"))
+            }
+        }
+    }
+
+    pub async fn revoke_token(&self, jti: &str, expires_at: u64) -> Result<(), Status> {
+        self.session_repo
+            .revoke_token(jti, expires_at)
+            .await
+            .map_err(|e| {
+                error!("Failed to revoke token {}: {:?}", jti, e);
+                db::error_status(&e, "Database error")
+            })
+    }
+
+    pub async fn revoke_session(&self, session_id: &str) -> Result<(), Status> {
+        self.session_repo
+            .revoke_session(session_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to revoke session {}: {:?}", session_id, e);
+                db::error_status(&e, "Database error")
+            })
+    }
+
+    pub async fn revoke_all_sessions(&self, user_id: i32) -> Result<(), Status> {
+        match self.session_repo.revoke_all_sessions(user_id).await {
+            Ok(count) => {
+                info!("Revoked {} sessions of user {}", count, user_id);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to revoke sessions of user {}: {:?}", user_id, e);
+                Err(db::error_status(&e, "Database error"))
+            }
+        }
+    }
+
+    pub async fn is_revoked(&self, ids: &[String]) -> Result<bool, Status> {
+        self.session_repo.is_revoked(ids).await.map_err(|e| {
+            error!("Failed to check token revocation: {:?}", e);
+            db::error_status(&e, "Database error")
+        })
+    }
+
+    /// Best-effort cleanup of rows that can no longer match a live token
+    pub async fn purge_expired(&self) {
+        if let Err(e) = self.session_repo.purge_expired().await {
+            warn!("Failed to purge expired sessions: {:?}", e);
+        }
+    }
+}
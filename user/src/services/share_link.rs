@@ -0,0 +1,238 @@
+use argon2::Argon2;
+use argon2::password_hash::{
+    PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng,
+};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tonic::{Response, Status};
+use tracing::{error, info};
+
+use crate::db;
+use crate::models::share_link::ShareLink;
+use crate::proto::user::{
+    self, CreateShareLinkReply, GetFileShareLinksReply, ResolveShareLinkReply, RevokeShareLinkReply,
+};
+use crate::repositories::share_link::ShareLinkRepo;
+use crate::services::cheatsheet::{CheatsheetClient, role_rank};
+
+/// Wrong passwords in a row that lock a link
+const MAX_FAILED_PASSWORD_ATTEMPTS: i32 = 5;
+/// How long a locked link refuses every password, including the right one
+const PASSWORD_LOCKOUT_SECS: i64 = 15 * 60;
+
+/// Public links that let anyone holding the token download a file, without an account
+#[derive(Debug)]
+pub struct ShareLinkService {
+    share_link_repo: ShareLinkRepo,
+    cheatsheet_client: Arc<CheatsheetClient>,
+}
+
+impl ShareLinkService {
+    pub fn new(share_link_repo: ShareLinkRepo, cheatsheet_client: Arc<CheatsheetClient>) -> Self {
+        Self {
+            share_link_repo,
+            cheatsheet_client,
+        }
+    }
+
+    /// Creates a link on behalf of `created_by`, who needs at least the resharer role on the
+    /// file, as for sharing it with a user. Opening the link later acts as them.
+    pub async fn create(
+        &self,
+        file_id: &str,
+        created_by: i32,
+        password: &str,
+        expires_at: &str,
+    ) -> Result<Response<CreateShareLinkReply>, Status> {
+        if file_id.is_empty() {
+            return Err(Status::invalid_argument("File id is required"));
+        }
+        self.cheatsheet_client
+            .require_role(created_by, file_id, "resharer")
+            .await?;
+
+        let password_hash = match password {
+            "" => None,
+            password => Some(hash_password(password.to_string()).await?),
+        };
+        let token = new_link_token();
+
+        match self
+            .share_link_repo
+            .create(
+                &hash_link_token(&token),
+                file_id,
+                created_by,
+                password_hash.as_deref(),
+                (!expires_at.is_empty()).then_some(expires_at),
+            )
+            .await
+        {
+            Ok(link) => {
+                info!(
+                    "User {} created share link {} to file {}",
+                    created_by, link.id, file_id
+                );
+                Ok(Response::new(CreateShareLinkReply {
+                    link: Some(to_proto(link)),
+                    token,
+                }))
+            }
+            Err(e) => {
+                error!("Failed to create share link: {:?}", e);
+                Err(db::error_status(&e, "Failed to create share link"))
+            }
+        }
+    }
+
+    /// Links to the file, which its resharers and above may see
+    pub async fn list_for_file(
+        &self,
+        caller: i32,
+        file_id: &str,
+    ) -> Result<Response<GetFileShareLinksReply>, Status> {
+        self.cheatsheet_client
+            .require_role(caller, file_id, "resharer")
+            .await?;
+        match self.share_link_repo.list_for_file(file_id).await {
+            Ok(links) => Ok(Response::new(GetFileShareLinksReply {
+                links: links.into_iter().map(to_proto).collect(),
+            })),
+            Err(e) => {
+                error!("Failed to list share links of file {}: {:?}", file_id, e);
+                Err(db::error_status(&e, "Database error"))
+            }
+        }
+    }
+
+    /// Revokes a link. Co-owners may revoke any link to the file, resharers only the links
+    /// they created; for anyone else's link, a resharer is told it was not found.
+    pub async fn revoke(
+        &self,
+        caller: i32,
+        id: &str,
+        file_id: &str,
+    ) -> Result<Response<RevokeShareLinkReply>, Status> {
+        let id = id
+            .parse::<i32>()
+            .map_err(|_| Status::invalid_argument("Share link ids must be numeric"))?;
+        let role = self
+            .cheatsheet_client
+            .require_role(caller, file_id, "resharer")
+            .await?;
+        let created_by = (role_rank(&role) < role_rank("co_owner")).then_some(caller);
+        match self.share_link_repo.delete(id, file_id, created_by).await {
+            Ok(revoked) => Ok(Response::new(RevokeShareLinkReply { revoked })),
+            Err(e) => {
+                error!("Failed to revoke share link: {:?}", e);
+                Err(db::error_status(&e, "Database error"))
+            }
+        }
+    }
+
+    pub async fn resolve(
+        &self,
+        token: &str,
+        password: &str,
+    ) -> Result<Response<ResolveShareLinkReply>, Status> {
+        let link = match self
+            .share_link_repo
+            .find_by_token_hash(&hash_link_token(token))
+            .await
+        {
+            Ok(Some(link)) => link,
+            Ok(None) => return Err(Status::not_found("Share link not found")),
+            Err(e) => {
+                error!("Failed to look up share link: {:?}", e);
+                return Err(db::error_status(&e, "Database error"));
+            }
+        };
+
+        if let Some(password_hash) = &link.password_hash {
+            if link.locked {
+                return Err(Status::resource_exhausted(
+                    "Too many incorrect passwords, try again later",
+                ));
+            }
+            if password.is_empty() {
+                return Err(Status::unauthenticated("This link requires a password"));
+            }
+            if !verify_password(password.to_string(), password_hash.clone()).await? {
+                if let Err(e) = self
+                    .share_link_repo
+                    .record_failed_attempt(
+                        link.id,
+                        MAX_FAILED_PASSWORD_ATTEMPTS,
+                        PASSWORD_LOCKOUT_SECS,
+                    )
+                    .await
+                {
+                    error!("Failed to count a wrong share link password: {:?}", e);
+                    return Err(db::error_status(&e, "Database error"));
+                }
+                return Err(Status::unauthenticated("Incorrect password"));
+            }
+            if link.failed_attempts > 0
+                && let Err(e) = self.share_link_repo.clear_failed_attempts(link.id).await
+            {
+                error!("Failed to reset share link password attempts: {:?}", e);
+            }
+        }
+
+        Ok(Response::new(ResolveShareLinkReply {
+            link: Some(to_proto(link)),
+        }))
+    }
+}
+
+fn to_proto(link: ShareLink) -> user::ShareLink {
+    user::ShareLink {
+        id: link.id.to_string(),
+        file_id: link.file_id,
+        created_by: link.created_by.to_string(),
+        created_at: link.created_at,
+        expires_at: link.expires_at.unwrap_or_default(),
+        has_password: link.password_hash.is_some(),
+    }
+}
+
+/// Opaque, URL-safe link token; only its hash is stored
+fn new_link_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn hash_link_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Argon2 is deliberately slow, so it runs off the async workers
+async fn hash_password(password: String) -> Result<String, Status> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+    })
+    .await
+    .ok()
+    .and_then(Result::ok)
+    .ok_or_else(|| Status::internal("Failed to hash password"))
+}
+
+async fn verify_password(password: String, password_hash: String) -> Result<bool, Status> {
+    tokio::task::spawn_blocking(move || {
+        let hash = PasswordHash::new(&password_hash).ok()?;
+        Some(
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok(),
+        )
+    })
+    .await
+    .ok()
+    .flatten()
+    .ok_or_else(|| Status::internal("Failed to verify password"))
+}
@@ -0,0 +1,136 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tonic::Status;
+use tracing::error;
+use uuid::Uuid;
+
+use crate::config::config::JwtConfig;
+use crate::models::user::User;
+
+/// Claims carried by a session token issued after a successful OAuth login
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub email: String,
+    pub iss: String,
+    pub iat: u64,
+    pub exp: u64,
+    pub jti: String,
+    pub sid: String,
+}
+
+impl Claims {
+    pub fn user_id(&self) -> Result<i32, TokenError> {
+        self.sub.parse().map_err(|_| TokenError::Invalid)
+    }
+}
+
+/// Why a session token could not be issued or accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    Expired,
+    Invalid,
+    Signing,
+}
+
+impl From<TokenError> for Status {
+    fn from(e: TokenError) -> Self {
+        match e {
+            TokenError::Expired => Status::unauthenticated("Token expired"),
+            TokenError::Invalid => Status::unauthenticated("Invalid token"),
+            TokenError::Signing => Status::internal("Failed to issue token"),
+        }
+    }
+}
+
+pub struct TokenService {
+    issuer: String,
+    ttl_secs: u64,
+    refresh_ttl_secs: u64,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    validation: Validation,
+}
+
+impl std::fmt::Debug for TokenService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenService")
+            .field("issuer", &self.issuer)
+            .field("ttl_secs", &self.ttl_secs)
+            .field("refresh_ttl_secs", &self.refresh_ttl_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TokenService {
+    pub fn new(jwt_config: JwtConfig) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_issuer(&[jwt_config.issuer.as_str()]);
+        validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+        validation.leeway = 0;
+
+        Self {
+            issuer: jwt_config.issuer,
+            ttl_secs: jwt_config.ttl_secs,
+            refresh_ttl_secs: jwt_config.refresh_ttl_secs,
+            encoding_key: EncodingKey::from_secret(jwt_config.secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(jwt_config.secret.as_bytes()),
+            validation,
+        }
+    }
+
+    /// Signs a short-lived access token bound to the given session
+    pub fn issue(&self, user: &User, session_id: &str) -> Result<String, TokenError> {
+        let now = now_secs();
+        let claims = Claims {
+            sub: user.id.to_string(),
+            email: user.email.clone(),
+            iss: self.issuer.clone(),
+            iat: now,
+            exp: now + self.ttl_secs,
+            jti: Uuid::new_v4().to_string(),
+            sid: session_id.to_string(),
+        };
+
+        encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key).map_err(|e| {
+            error!("Failed to sign session token: {:?}", e);
+            TokenError::Signing
+        })
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Claims, TokenError> {
+        decode::<Claims>(token, &self.decoding_key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| match e.kind() {
+                ErrorKind::ExpiredSignature => TokenError::Expired,
+                _ => TokenError::Invalid,
+            })
+    }
+
+    pub fn refresh_expires_at(&self) -> u64 {
+        now_secs() + self.refresh_ttl_secs
+    }
+}
+
+/// Opaque refresh token handed to the client; only its hash is stored
+pub fn new_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+pub fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
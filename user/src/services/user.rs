@@ -1,10 +1,21 @@
-use log::{error, info};
 use tonic::{Response, Status};
+use tracing::{error, info};
 
+use crate::db;
 use crate::models::user::User;
-use crate::proto::user::{GetAllUsersReply, UserProfile};
+use crate::proto::user::{GetUsersByIdsReply, SearchUsersReply, UserProfile};
 use crate::repositories::user::UserRepo;
 
+/// Most ids a single batch lookup may ask for
+const MAX_BATCH_IDS: usize = 1000;
+
+/// Page size for user search when the caller doesn't ask for one
+const DEFAULT_SEARCH_LIMIT: u32 = 20;
+/// Largest page of search results a caller may ask for
+const MAX_SEARCH_LIMIT: u32 = 50;
+/// Shortest search query; shorter ones would match most of the directory
+const MIN_SEARCH_QUERY_LEN: usize = 2;
+
 #[derive(Debug)]
 pub struct UserService {
     user_repo: UserRepo,
@@ -15,11 +26,75 @@ impl UserService {
         Self { user_repo }
     }
 
-    pub async fn get_all(&self) -> Result<Response<GetAllUsersReply>, Status> {
-        match self.user_repo.get_all().await {
-            Ok(users) => {
-                info!("Successfully retrieved {} users", users.len());
-                Ok(Response::new(GetAllUsersReply {
+    /// Looks up several users at once; ids that don't exist are skipped
+    pub async fn get_by_ids(
+        &self,
+        ids: Vec<String>,
+    ) -> Result<Response<GetUsersByIdsReply>, Status> {
+        if ids.len() > MAX_BATCH_IDS {
+            return Err(Status::invalid_argument(format!(
+                "At most {} ids can be looked up at once",
+                MAX_BATCH_IDS
+            )));
+        }
+        match self.user_repo.get_by_ids(&user_ids(&ids)).await {
+            Ok(users) => Ok(Response::new(GetUsersByIdsReply {
+                users: users
+                    .into_iter()
+                    .map(|user| UserProfile {
+                        id: user.id.to_string(),
+                        name: user.name,
+                    })
+                    .collect(),
+            })),
+            Err(e) => {
+                error!("Failed to get users by ids: {:?}", e);
+                Err(db::error_status(&e, "Database error"))
+            }
+        }
+    }
+
+    /// Searches users by name and email. Every term of `query` must prefix a word of the
+    /// name or email, or the whole query must prefix the email.
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: u32,
+        cursor: &str,
+    ) -> Result<Response<SearchUsersReply>, Status> {
+        let query = query.trim();
+        if query.chars().count() < MIN_SEARCH_QUERY_LEN {
+            return Err(Status::invalid_argument(format!(
+                "Search query must be at least {} characters",
+                MIN_SEARCH_QUERY_LEN
+            )));
+        }
+        let limit = match limit {
+            0 => DEFAULT_SEARCH_LIMIT,
+            limit => limit.min(MAX_SEARCH_LIMIT),
+        };
+        let after = match cursor {
+            "" => None,
+            cursor => Some(
+                decode_search_cursor(cursor)
+                    .ok_or_else(|| Status::invalid_argument("Invalid search cursor"))?,
+            ),
+        };
+
+        // fetch one extra row to know whether there is another page
+        match self
+            .user_repo
+            .search(&prefix_tsquery(query), query, after, limit as i64 + 1)
+            .await
+        {
+            Ok(mut users) => {
+                let next_cursor = if users.len() > limit as usize {
+                    users.truncate(limit as usize);
+                    users.last().map(encode_search_cursor).unwrap_or_default()
+                } else {
+                    String::new()
+                };
+                Ok(Response::new(SearchUsersReply {
                     users: users
                         .into_iter()
                         .map(|user| UserProfile {
@@ -27,21 +102,23 @@ impl UserService {
                             name: user.name,
                         })
                         .collect(),
+                    next_cursor,
                 }))
             }
             Err(e) => {
-                error!("Failed to get all users: {:?}", e);
-                Err(Status::internal("Database error"))
+                error!("Failed to search users: {:?}", e);
+                Err(db::error_status(&e, "Database error"))
             }
         }
     }
+
     pub async fn get_one(&self, id: i32) -> Result<User, Status> {
         match self.user_repo.get_one(id).await {
             Ok(Some(user)) => Ok(user),
             Ok(None) => Err(Status::not_found("User not found")),
             Err(e) => {
                 error!("Failed to get one user: {:?}", e);
-                Err(Status::internal("Database error"))
+                Err(db::error_status(&e, "Database error"))
             }
         }
     }
@@ -52,43 +129,111 @@ impl UserService {
             Ok(None) => Ok(None),
             Err(e) => {
                 error!("Failed to find user by email: {:?}", e);
-                Err(Status::internal("Database error"))
+                Err(db::error_status(&e, "Database error"))
             }
         }
     }
 
+    pub async fn find_by_email_ignore_case(&self, email: &str) -> Result<Option<User>, Status> {
+        self.user_repo
+            .find_by_email_ignore_case(email)
+            .await
+            .map_err(|e| {
+                error!("Failed to find user by email: {:?}", e);
+                db::error_status(&e, "Database error")
+            })
+    }
+
     pub async fn create(&self, user: &User) -> Result<User, Status> {
-        match self.user_repo.create(&user).await {
+        match self.user_repo.create(user).await {
             Ok(user) => {
                 info!("Successfully created user: {}", user.email);
                 Ok(user)
             }
             Err(e) => {
                 error!("Failed to create user '{}': {:?}", user.email, e);
-                Err(Status::internal("Failed to create user"))
+                Err(db::error_status(&e, "Failed to create user"))
             }
         }
     }
+}
 
-    pub async fn update(&self, id: i32, name: String) -> Result<User, Status> {
-        match self.user_repo.update(id, name).await {
-            Ok(Some(user)) => Ok(user),
-            Ok(None) => Err(Status::not_found("User not found")),
-            Err(e) => {
-                error!("Failed to update user: {:?}", e);
-                Err(Status::internal("Database error"))
-            }
-        }
+/// Turns free text into a tsquery requiring a word starting with each term. Characters
+/// with a meaning in tsquery syntax are dropped rather than escaped.
+fn prefix_tsquery(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| {
+            term.chars()
+                .filter(|c| c.is_alphanumeric() || matches!(c, '.' | '@' | '-' | '_'))
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("'{}':*", term))
+        .collect::<Vec<_>>()
+        .join(" & ")
+}
+
+/// Cursors are the hex encoded JSON `[lowercased name, id]` of the last user on a page
+fn encode_search_cursor(user: &User) -> String {
+    hex::encode(serde_json::to_vec(&(user.name.to_lowercase(), user.id)).unwrap_or_default())
+}
+
+fn decode_search_cursor(cursor: &str) -> Option<(String, i32)> {
+    serde_json::from_slice(&hex::decode(cursor).ok()?).ok()
+}
+
+/// Distinct numeric ids among `ids`. Anything else cannot name a user, so like an unknown id
+/// it is left out of the reply.
+fn user_ids(ids: &[String]) -> Vec<i32> {
+    let mut ids: Vec<i32> = ids.iter().filter_map(|id| id.parse().ok()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_ids_that_are_not_numeric() {
+        let ids = ["3", "google-oauth2|123", "1", "", "3"].map(String::from);
+        assert_eq!(user_ids(&ids), [1, 3]);
     }
 
-    pub async fn delete(&self, id: i32) -> Result<String, Status> {
-        match self.user_repo.delete(id).await {
-            Ok(true) => Ok("User deleted".to_string()),
-            Ok(false) => Err(Status::not_found("User not found")),
-            Err(e) => {
-                error!("Failed to delete user: {:?}", e);
-                Err(Status::internal("Database error"))
-            }
+    #[test]
+    fn search_cursor_round_trips() {
+        let user = User {
+            id: 7,
+            email: "ada@example.com".to_string(),
+            name: "Ada Lovelace".to_string(),
+        };
+        assert_eq!(
+            decode_search_cursor(&encode_search_cursor(&user)),
+            Some(("ada lovelace".to_string(), 7))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_search_cursors() {
+        for cursor in ["not hex", "", &hex::encode(b"[7, \"ada\"]")] {
+            assert_eq!(decode_search_cursor(cursor), None, "{cursor:?}");
         }
     }
+
+    #[test]
+    fn prefix_tsquery_requires_every_term() {
+        assert_eq!(
+            prefix_tsquery("  Ada  lovelace@Example.com "),
+            "'ada':* & 'lovelace@example.com':*"
+        );
+    }
+
+    #[test]
+    fn prefix_tsquery_drops_tsquery_syntax() {
+        assert_eq!(prefix_tsquery("o'brien | !x:* &"), "'obrien':* & 'x':*");
+        assert_eq!(prefix_tsquery("()  !"), "");
+    }
 }